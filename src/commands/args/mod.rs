@@ -1,3 +1,5 @@
+use std::{fmt, str::FromStr};
+
 use candid::Principal;
 
 pub mod validations;
@@ -7,15 +9,74 @@ pub use validations::{Validate, ValidateError};
 pub enum Canister {
     Name(String),
     Principal(Principal),
+    Qualified(Qualified),
 }
 
-impl From<&str> for Canister {
-    fn from(v: &str) -> Self {
+/// A canister of another environment or project, `[project/][environment:]name`,
+/// e.g. `staging:backend` or `project-x/backend`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Qualified {
+    pub project: Option<String>,
+    pub environment: Option<String>,
+    pub name: String,
+}
+
+impl FromStr for Canister {
+    type Err = String;
+
+    fn from_str(v: &str) -> Result<Self, Self::Err> {
         if let Ok(p) = Principal::from_text(v) {
-            return Self::Principal(p);
+            return Ok(Self::Principal(p));
         }
 
-        Self::Name(v.to_string())
+        if !v.contains([':', '/']) {
+            return Ok(Self::Name(v.to_string()));
+        }
+
+        let (project, rest) = match v.split_once('/') {
+            Some((project, rest)) => (Some(project), rest),
+            None => (None, v),
+        };
+
+        let (environment, name) = match rest.split_once(':') {
+            Some((environment, name)) => (Some(environment), name),
+            None => (None, rest),
+        };
+
+        let parts = [project, environment, Some(name)];
+        if parts
+            .iter()
+            .flatten()
+            .any(|part| part.is_empty() || part.contains([':', '/']))
+        {
+            return Err(format!(
+                "invalid canister reference `{v}`, expected `[project/][environment:]name`"
+            ));
+        }
+
+        Ok(Self::Qualified(Qualified {
+            project: project.map(str::to_owned),
+            environment: environment.map(str::to_owned),
+            name: name.to_owned(),
+        }))
+    }
+}
+
+impl fmt::Display for Canister {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Canister::Name(name) => f.write_str(name),
+            Canister::Principal(cid) => write!(f, "{cid}"),
+            Canister::Qualified(q) => {
+                if let Some(project) = &q.project {
+                    write!(f, "{project}/")?;
+                }
+                if let Some(environment) = &q.environment {
+                    write!(f, "{environment}:")?;
+                }
+                f.write_str(&q.name)
+            }
+        }
     }
 }
 
@@ -35,6 +96,81 @@ impl From<&str> for Network {
     }
 }
 
+/// An amount of cycles, written out or with a suffix, e.g. `1.5T` or `500B`.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Cycles(pub u128);
+
+/// Suffixes from largest to smallest.
+const CYCLES_UNITS: &[(char, u128)] = &[
+    ('T', 1_000_000_000_000),
+    ('B', 1_000_000_000),
+    ('M', 1_000_000),
+    ('K', 1_000),
+];
+
+impl FromStr for Cycles {
+    type Err = String;
+
+    fn from_str(v: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid cycles amount `{v}`, expected e.g. `1.5T` or `500000`");
+
+        let s = v.trim().replace('_', "");
+
+        let (number, unit) = match s.chars().last().map(|c| c.to_ascii_uppercase()) {
+            Some(c) => match CYCLES_UNITS.iter().find(|(u, _)| *u == c) {
+                Some((_, unit)) => (&s[..s.len() - 1], *unit),
+                None => (s.as_str(), 1),
+            },
+            None => return Err(invalid()),
+        };
+
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+
+        let whole: u128 = match whole {
+            "" => 0,
+            w => w.parse().map_err(|_| invalid())?,
+        };
+
+        // Fractions are exact down to a single cycle
+        let digits = fraction.len() as u32;
+        let scale = 10u128.checked_pow(digits).ok_or_else(invalid)?;
+        let fraction: u128 = match fraction {
+            "" => 0,
+            f => f.parse().map_err(|_| invalid())?,
+        };
+
+        let fraction = fraction.checked_mul(unit).ok_or_else(invalid)?;
+
+        if !fraction.is_multiple_of(scale) {
+            return Err(invalid());
+        }
+
+        whole
+            .checked_mul(unit)
+            .and_then(|w| w.checked_add(fraction / scale))
+            .map(Cycles)
+            .ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Cycles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match CYCLES_UNITS.iter().find(|(_, unit)| self.0 >= *unit) {
+            // Three decimals, trailing zeros dropped
+            Some((suffix, unit)) => {
+                let v = format!("{:.3}", self.0 as f64 / *unit as f64);
+                let v = v.trim_end_matches('0').trim_end_matches('.');
+                write!(f, "{v}{suffix}")
+            }
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
 // TODO? Alias arg for transfers
 // can maintain mapping of principal aliases
 // e.g to associate a principal with a person, or with a specific canister
@@ -43,13 +179,13 @@ impl From<&str> for Network {
 mod tests {
     use candid::Principal;
 
-    use crate::commands::args::{Canister, Network};
+    use crate::commands::args::{Canister, Cycles, Network, Qualified};
 
     #[test]
     fn canister_by_name() {
         assert_eq!(
-            Canister::from("my-canister"),
-            Canister::Name("my-canister".to_string()),
+            "my-canister".parse::<Canister>(),
+            Ok(Canister::Name("my-canister".to_string())),
         );
     }
 
@@ -58,11 +194,41 @@ mod tests {
         let cid = "ntyui-iatoh-pfi3f-27wnk-vgdqt-mq3cl-ld7jh-743kl-sde6i-tbm7g-tqe";
 
         assert_eq!(
-            Canister::from(cid),
-            Canister::Principal(Principal::from_text(cid).expect("failed to parse principal")),
+            cid.parse::<Canister>(),
+            Ok(Canister::Principal(
+                Principal::from_text(cid).expect("failed to parse principal")
+            )),
         );
     }
 
+    #[test]
+    fn canister_by_qualified_name() {
+        for (v, project, environment) in [
+            ("staging:backend", None, Some("staging")),
+            ("project-x/backend", Some("project-x"), None),
+            (
+                "project-x/staging:backend",
+                Some("project-x"),
+                Some("staging"),
+            ),
+        ] {
+            assert_eq!(
+                v.parse::<Canister>(),
+                Ok(Canister::Qualified(Qualified {
+                    project: project.map(str::to_owned),
+                    environment: environment.map(str::to_owned),
+                    name: "backend".to_string(),
+                })),
+                "{v}"
+            );
+            assert_eq!(v.parse::<Canister>().unwrap().to_string(), v);
+        }
+
+        for v in ["staging:", ":backend", "a/b/c", "a:b:c", "/backend"] {
+            assert!(v.parse::<Canister>().is_err(), "{v}");
+        }
+    }
+
     #[test]
     fn network_by_name() {
         assert_eq!(
@@ -80,4 +246,24 @@ mod tests {
             Network::Url("http://www.example.com".to_string()),
         );
     }
+
+    #[test]
+    fn cycles_amounts() {
+        for (v, expected) in [
+            ("1.5T", Some(1_500_000_000_000)),
+            ("500b", Some(500_000_000_000)),
+            ("2M", Some(2_000_000)),
+            ("1_000", Some(1_000)),
+            ("0.000000000001T", Some(1)),
+            ("0.999999999999999999999999999999T", None),
+            ("1.5", None),
+            ("T", None),
+            ("lots", None),
+        ] {
+            assert_eq!(v.parse::<Cycles>().ok(), expected.map(Cycles), "{v}");
+        }
+
+        assert_eq!(Cycles(1_500_000_000_000).to_string(), "1.5T");
+        assert_eq!(Cycles(999).to_string(), "999");
+    }
 }
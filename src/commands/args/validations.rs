@@ -1,6 +1,10 @@
-use crate::commands::{
-    Mode,
-    args::{Canister, Network},
+use crate::{
+    commands::{
+        Mode,
+        args::{Canister, Network, Qualified},
+        resolve::{DEFAULT_ENVIRONMENT, NETWORKS},
+    },
+    operations::store::Manifest,
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -54,6 +58,24 @@ const PLEASE_PROVIDE_A_CANISTER_PRINCIPAL_IN_GLOBAL_MODE: &str = r#"
     Please provide a canister principal in global mode.
 "#;
 
+pub fn canister_principals_are_required_in_global_mode<'a>(
+    selection: impl Into<(&'a Vec<Canister>, &'a bool, &'a Vec<String>)>,
+    m: &Mode,
+) -> Option<&'static str> {
+    let (canisters, all, tags) = selection.into();
+    (matches!(m, Mode::Global)
+        && (*all
+            || !tags.is_empty()
+            || canisters
+                .iter()
+                .any(|c| !matches!(c, Canister::Principal(_)))))
+    .then_some(PLEASE_PROVIDE_CANISTER_PRINCIPALS_IN_GLOBAL_MODE)
+}
+
+const PLEASE_PROVIDE_CANISTER_PRINCIPALS_IN_GLOBAL_MODE: &str = r#"
+    Please provide canister principals in global mode.
+"#;
+
 pub fn network_or_environment_not_both<'a>(
     network_environment: impl Into<(&'a Option<Network>, &'a Option<String>)>,
     m: &Mode,
@@ -106,6 +128,47 @@ const A_NETWORK_NAME_IS_REQUIRED_IN_PROJECT_MODE: &str = r#"
     A network `name` is required in project mode.
 "#;
 
+/// Qualified canisters must name a project declared in the manifest `m`, then an environment
+/// and a canister of that project, whose manifest is `target`.
+pub fn qualified_canisters_must_resolve(
+    q: &Qualified,
+    m: &Manifest,
+    target: &Manifest,
+) -> Option<&'static str> {
+    if q.project
+        .as_ref()
+        .is_some_and(|p| !m.projects.contains_key(p))
+    {
+        return Some(PLEASE_DECLARE_THE_PROJECT_UNDER_PROJECTS);
+    }
+
+    // Environments named after a network need not be declared
+    let known = |environment: &String| {
+        environment == DEFAULT_ENVIRONMENT
+            || target.environments.contains_key(environment)
+            || target.networks.contains_key(environment)
+            || NETWORKS.iter().any(|(n, _)| n == environment)
+    };
+
+    if q.environment.as_ref().is_some_and(|env| !known(env)) {
+        return Some(THE_ENVIRONMENT_IS_NOT_DECLARED);
+    }
+
+    (!target.canisters.contains_key(&q.name)).then_some(THE_CANISTER_IS_NOT_DECLARED)
+}
+
+const PLEASE_DECLARE_THE_PROJECT_UNDER_PROJECTS: &str = r#"
+    Please declare the project under `projects` in the manifest.
+"#;
+
+const THE_ENVIRONMENT_IS_NOT_DECLARED: &str = r#"
+    The environment is not declared in the project.
+"#;
+
+const THE_CANISTER_IS_NOT_DECLARED: &str = r#"
+    The canister is not declared in the project.
+"#;
+
 #[cfg(test)]
 mod test_a_canister_id_is_required_in_global_mode {
     use crate::impl_from_args;
@@ -137,6 +200,75 @@ mod test_a_canister_id_is_required_in_global_mode {
     }
 }
 
+#[cfg(test)]
+mod test_canister_principals_are_required_in_global_mode {
+    use candid::Principal;
+
+    use crate::impl_from_args;
+
+    use super::*;
+
+    struct Args {
+        canisters: Vec<Canister>,
+        all: bool,
+        tag: Vec<String>,
+    }
+
+    impl_from_args!(Args, canisters: Vec<Canister>, all: bool, tag: Vec<String>);
+
+    #[test]
+    fn test() {
+        for (args, expected) in [
+            (
+                //
+                // Args
+                Args {
+                    canisters: vec![Canister::Name("my-canister".to_string())],
+                    all: false,
+                    tag: vec![],
+                },
+                //
+                // Output
+                Some(PLEASE_PROVIDE_CANISTER_PRINCIPALS_IN_GLOBAL_MODE),
+            ),
+            (
+                Args {
+                    canisters: vec![],
+                    all: true,
+                    tag: vec![],
+                },
+                Some(PLEASE_PROVIDE_CANISTER_PRINCIPALS_IN_GLOBAL_MODE),
+            ),
+            (
+                Args {
+                    canisters: vec![],
+                    all: false,
+                    tag: vec!["my-tag".to_string()],
+                },
+                Some(PLEASE_PROVIDE_CANISTER_PRINCIPALS_IN_GLOBAL_MODE),
+            ),
+            (
+                Args {
+                    canisters: vec![Canister::Principal(Principal::anonymous())],
+                    all: false,
+                    tag: vec![],
+                },
+                None,
+            ),
+        ] {
+            let out = canister_principals_are_required_in_global_mode(&args, &Mode::Global);
+            assert_eq!(out, expected, "invalid validation output: {out:?}");
+
+            // Names, tags and `--all` resolve in project mode
+            let out = canister_principals_are_required_in_global_mode(
+                &args,
+                &Mode::Project("dir".into()),
+            );
+            assert_eq!(out, None, "invalid validation output: {out:?}");
+        }
+    }
+}
+
 #[cfg(test)]
 mod test_network_or_environment_not_both {
     use crate::{
@@ -284,3 +416,58 @@ mod test_a_network_name_is_required_in_project_mode {
         }
     }
 }
+
+#[cfg(test)]
+mod test_qualified_canisters_must_resolve {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn q(v: &str) -> Qualified {
+        match v.parse() {
+            Ok(Canister::Qualified(q)) => q,
+            out => panic!("not a qualified canister: {out:?}"),
+        }
+    }
+
+    #[test]
+    fn test() {
+        let m: Manifest = serde_yaml_ng::from_str(indoc! {r#"
+            canisters:
+              backend: {}
+            environments:
+              staging:
+                network: ic
+            projects:
+              project-x:
+                path: ../project-x
+        "#})
+        .unwrap();
+
+        let target: Manifest = serde_yaml_ng::from_str("canisters: { frontend: {} }").unwrap();
+
+        for (v, target, out) in [
+            ("staging:backend", &m, None),
+            ("ic:backend", &m, None),
+            ("project-x/frontend", &target, None),
+            (
+                "project-y/frontend",
+                &m,
+                Some(PLEASE_DECLARE_THE_PROJECT_UNDER_PROJECTS),
+            ),
+            ("prod:backend", &m, Some(THE_ENVIRONMENT_IS_NOT_DECLARED)),
+            (
+                "project-x/staging:frontend",
+                &target,
+                Some(THE_ENVIRONMENT_IS_NOT_DECLARED),
+            ),
+            ("staging:frontend", &m, Some(THE_CANISTER_IS_NOT_DECLARED)),
+        ] {
+            assert_eq!(
+                qualified_canisters_must_resolve(&q(v), &m, target),
+                out,
+                "{v}"
+            );
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use clap::{Parser, Subcommand};
+
+mod prune;
+pub use prune::*;
+
+#[derive(Parser)]
+pub struct Command {
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    Prune(PruneArgs),
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeSet;
+
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{Validate, ValidateError},
+        resolve::DEFAULT_ENVIRONMENT,
+    },
+    operations::{
+        build::{CacheError, exports},
+        store::{ListError, LoadError},
+    },
+};
+
+#[derive(Args)]
+pub struct PruneArgs {
+    // Remove every cached artifact, including those matching the current inputs
+    #[arg(long)]
+    pub all: bool,
+}
+
+impl Validate for PruneArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // the cache lives in the project
+        match mode {
+            Mode::Global => Err(ValidateError::Unexpected(anyhow::format_err!(
+                "The build cache is only available in project mode."
+            ))),
+            Mode::Project(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("the build cache is only available in project mode")]
+    ProjectRequired,
+
+    #[error("failed to prune the build cache")]
+    Prune(#[from] CacheError),
+
+    #[error(transparent)]
+    Load(#[from] LoadError),
+
+    #[error(transparent)]
+    List(#[from] ListError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Removes cached artifacts that no canister would be built from anymore.
+pub async fn prune(ctx: &Context, args: &PruneArgs) -> Result<(), CommandError> {
+    let Mode::Project(dir) = &ctx.mode else {
+        return Err(CommandError::ProjectRequired);
+    };
+
+    let cache = (ctx.ops.build.cache)(dir);
+
+    let mut keep = BTreeSet::new();
+
+    if !args.all {
+        let m = (ctx.ops.store.manifest)(dir).load().await?;
+        let ids = (ctx.ops.store.ids)(dir);
+
+        let environments: BTreeSet<&str> = m
+            .environments
+            .keys()
+            .map(String::as_str)
+            .chain([DEFAULT_ENVIRONMENT])
+            .collect();
+
+        // Builds differ by the dependency IDs of each environment
+        for environment in environments {
+            let deployed = ids.list(environment).await?;
+
+            // A canister whose inputs cannot be hashed right now has nothing to keep
+            for (name, c) in &m.canisters {
+                let env = exports(&m, name, environment, &deployed);
+                if let Ok(Some(key)) = cache.key(name, c, &env).await {
+                    keep.insert(key);
+                }
+            }
+        }
+    }
+
+    let pruned = cache.prune(&keep).await?;
+
+    println!(
+        "Removed {} cached artifacts ({} bytes)",
+        pruned.artifacts, pruned.bytes
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, collections::BTreeSet, sync::Arc};
+
+    use anyhow::Error;
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode,
+            build::cache::{PruneArgs, prune},
+        },
+        operations::{
+            self,
+            build::{MockCache, Pruned},
+            store::{self, Canister, Manifest, MockLoad, MockStore},
+        },
+    };
+
+    #[tokio::test]
+    async fn prune_keeps_current_artifacts() -> Result<(), Error> {
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            build: operations::build::Initializers {
+                cache: Box::new(|_| {
+                    let mut m = MockCache::new();
+                    m.expect_key()
+                        .returning(|_, c, _| Ok(c.tags.first().map(|t| format!("key-{t}"))));
+                    m.expect_prune()
+                        .with(eq(BTreeSet::from(["key-backend".to_string()])))
+                        .once()
+                        .returning(|_| {
+                            Ok(Pruned {
+                                artifacts: 2,
+                                bytes: 1024,
+                            })
+                        });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_list().returning(|_| Ok(BTreeMap::new()));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| {
+                        Ok(Manifest {
+                            canisters: BTreeMap::from([
+                                (
+                                    "backend".to_string(),
+                                    Canister {
+                                        tags: vec!["backend".to_string()],
+                                        ..Default::default()
+                                    },
+                                ),
+                                // No inputs, never cached
+                                ("frontend".to_string(), Canister::default()),
+                            ]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        prune(&ctx, &PruneArgs { all: false }).await?;
+
+        Ok(())
+    }
+}
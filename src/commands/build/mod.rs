@@ -1,25 +1,725 @@
-use anyhow::Error;
-use clap::Args;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use crate::commands::Context;
+use clap::{Args, Parser, Subcommand};
+use futures::{StreamExt, stream::FuturesUnordered};
+use sha2::{Digest, Sha256};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{Validate, ValidateError},
+        resolve,
+    },
+    operations::{
+        build::{
+            Build, BuildError, Cache, CacheError, GraphError, OUTPUT, Process, ProcessError,
+            closure, exports, graph, steps,
+        },
+        store::{Artifact, ArtifactsError, Canister, ListError, LoadError, Recipe, Record},
+    },
+};
+
+pub mod cache;
+
+#[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct Command {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    #[command(flatten)]
+    pub build: BuildArgs,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    Cache(cache::Command),
+}
+
+/// Where the build leaves the wasm module of a canister.
+pub fn wasm(dir: &Path, name: &str) -> PathBuf {
+    dir.join(OUTPUT).join(format!("{name}.wasm"))
+}
 
 #[derive(Args)]
 pub struct BuildArgs {
-    name: Option<String>,
+    // Canister to build along with its dependencies, every canister with a recipe if unset
+    pub name: Option<String>,
+
+    // Rebuild even if the cache has an artifact for the current inputs
+    #[arg(long)]
+    pub force: bool,
+
+    // Number of canisters built at once, the number of CPUs if unset
+    #[arg(long, short)]
+    pub jobs: Option<NonZeroUsize>,
+
+    // Keep building canisters that do not depend on a failed one
+    #[arg(long)]
+    pub keep_going: bool,
+
+    // Environment whose canister IDs are passed to dependent builds
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl Validate for BuildArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // recipes are declared in the manifest
+        match mode {
+            Mode::Global => Err(ValidateError::Unexpected(anyhow::format_err!(
+                "Building is only available in project mode."
+            ))),
+            Mode::Project(_) => Ok(()),
+        }
+    }
 }
 
-pub async fn build(ctx: &Context, args: &BuildArgs) -> Result<(), Error> {
-    // let cid = match &ctx.mode {
-    //     //
-    //     Mode::Project(dir) => {
-    //         todo!()
-    //     }
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("canisters can only be built in project mode")]
+    ProjectRequired,
+
+    #[error("canister `{0}` is not declared in the manifest")]
+    UnknownCanister(String),
+
+    #[error("canister `{0}` has no build recipe")]
+    NoRecipe(String),
+
+    #[error("failed to build canister `{name}`")]
+    Build {
+        name: String,
+
+        #[source]
+        err: BuildError,
+    },
+
+    #[error("failed to post-process canister `{name}`")]
+    Process {
+        name: String,
+
+        #[source]
+        err: ProcessError,
+    },
+
+    #[error("failed to use the build cache for canister `{name}`")]
+    Cache {
+        name: String,
+
+        #[source]
+        err: CacheError,
+    },
+
+    #[error("failed to read `{0}`")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("failed to write `{0}`")]
+    Write(PathBuf, #[source] std::io::Error),
+
+    #[error("failed to build {}", .0.join(", "))]
+    Incomplete(Vec<String>),
+
+    #[error(transparent)]
+    Graph(#[from] GraphError),
+
+    #[error("failed to record the build artifacts")]
+    Artifacts(#[from] ArtifactsError),
+
+    #[error(transparent)]
+    Load(#[from] LoadError),
+
+    #[error(transparent)]
+    List(#[from] ListError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn build(ctx: &Context, args: &BuildArgs) -> Result<(), CommandError> {
+    let Mode::Project(dir) = &ctx.mode else {
+        return Err(CommandError::ProjectRequired);
+    };
+
+    let m = (ctx.ops.store.manifest)(dir).load().await?;
+    let g = graph(&m)?;
+
+    let selected = match &args.name {
+        Some(name) => {
+            let c = m
+                .canisters
+                .get(name)
+                .ok_or_else(|| CommandError::UnknownCanister(name.to_owned()))?;
+
+            if c.build.is_none() {
+                return Err(CommandError::NoRecipe(name.to_owned()));
+            }
+
+            closure(&g, [name])
+        }
+
+        None => m.canisters.keys().cloned().collect(),
+    };
+
+    // Remote canisters are built elsewhere, dependencies without a recipe are taken as they are
+    let targets: BTreeMap<&String, (&Canister, &Recipe)> = m
+        .canisters
+        .iter()
+        .filter(|(name, c)| selected.contains(*name) && c.remote.is_none())
+        .filter_map(|(name, c)| Some((name, (c, c.build.as_ref()?))))
+        .collect();
+
+    let environment = resolve::environment(&None, &args.environment);
+    let deployed = (ctx.ops.store.ids)(dir).list(environment).await?;
+
+    let tools = Tools {
+        builder: (ctx.ops.build.build)(dir),
+        processor: (ctx.ops.build.process)(dir),
+        cache: (ctx.ops.build.cache)(dir),
+        artifacts: (ctx.ops.store.artifacts)(dir),
+        environment: environment.to_owned(),
+    };
+
+    let jobs = args
+        .jobs
+        .or(std::thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get);
+
+    // Dependencies of each canister still to be built
+    let mut waiting: BTreeMap<&String, BTreeSet<&String>> = targets
+        .keys()
+        .map(|name| {
+            let deps = g[*name].iter().filter(|d| targets.contains_key(d));
+            (*name, deps.collect())
+        })
+        .collect();
+
+    let mut running = FuturesUnordered::new();
+    let mut failed = vec![];
+
+    loop {
+        let ready: Vec<&String> = waiting
+            .iter()
+            .filter(|(_, deps)| deps.is_empty())
+            .map(|(name, _)| *name)
+            .take(jobs - running.len())
+            .collect();
+
+        for name in ready {
+            waiting.remove(name);
+
+            let (c, recipe) = targets[name];
+            let env = exports(&m, name, environment, &deployed);
+
+            let tools = &tools;
+            running.push(async move {
+                let out = tools.run(dir, name, c, recipe, &env, args.force).await;
+                (name, out)
+            });
+        }
+
+        let Some((name, out)) = running.next().await else {
+            break;
+        };
+
+        match out {
+            Ok(()) => {
+                for deps in waiting.values_mut() {
+                    deps.remove(name);
+                }
+            }
+
+            // Dropping the builds still running stops their tools
+            Err(err) if !args.keep_going => return Err(err),
+
+            Err(err) => {
+                eprintln!("{name} | {:#}", anyhow::Error::from(err));
+                failed.push(name.to_owned());
+
+                // Anything depending on it, directly or not, can no longer be built
+                let mut blocked = BTreeSet::from([name]);
+                loop {
+                    let dependents: Vec<&String> = waiting
+                        .iter()
+                        .filter(|(_, deps)| !deps.is_disjoint(&blocked))
+                        .map(|(name, _)| *name)
+                        .collect();
+
+                    if dependents.is_empty() {
+                        break;
+                    }
+
+                    for name in dependents {
+                        println!("{name} | skipped, a dependency failed");
+                        waiting.remove(name);
+                        blocked.insert(name);
+                    }
+                }
+            }
+        }
+    }
+
+    match failed.is_empty() {
+        true => Ok(()),
+        false => Err(CommandError::Incomplete(failed)),
+    }
+}
+
+/// Operations shared by the builds of every canister.
+struct Tools {
+    builder: Arc<dyn Build>,
+    processor: Arc<dyn Process>,
+    cache: Arc<dyn Cache>,
+    artifacts: Arc<dyn Record>,
+
+    /// Environment whose dependency IDs are linked into the modules.
+    environment: String,
+}
+
+impl Tools {
+    /// Builds a canister, or takes its artifact from the cache.
+    async fn run(
+        &self,
+        dir: &Path,
+        name: &str,
+        c: &Canister,
+        recipe: &Recipe,
+        env: &BTreeMap<String, String>,
+        force: bool,
+    ) -> Result<(), CommandError> {
+        let cache_err = |err| CommandError::Cache {
+            name: name.to_owned(),
+            err,
+        };
+
+        let key = self.cache.key(name, c, env).await.map_err(cache_err)?;
+
+        if !force
+            && let Some(key) = &key
+            && let Some(module) = self.cache.get(key).await.map_err(cache_err)?
+        {
+            let out = self.write(dir, name, c, recipe, &module).await?;
+            println!("{name} | cached {}", out.display());
+            return Ok(());
+        }
+
+        println!("{name} | building");
+
+        let module =
+            self.builder
+                .build(name, recipe, env)
+                .await
+                .map_err(|err| CommandError::Build {
+                    name: name.to_owned(),
+                    err,
+                })?;
+
+        let steps = match &c.wasm {
+            Some(w) => steps(dir, w).await.map_err(|err| CommandError::Process {
+                name: name.to_owned(),
+                err,
+            })?,
+            None => vec![],
+        };
+
+        let module = tokio::fs::read(&module)
+            .await
+            .map_err(|err| CommandError::Read(module.to_owned(), err))?;
+
+        // What is written here is exactly what gets installed
+        let module = match steps.is_empty() {
+            true => module,
+            false => self
+                .processor
+                .process(name, module, &steps)
+                .await
+                .map_err(|err| CommandError::Process {
+                    name: name.to_owned(),
+                    err,
+                })?,
+        };
+
+        if let Some(key) = &key {
+            self.cache.put(key, &module).await.map_err(cache_err)?;
+        }
+
+        let out = self.write(dir, name, c, recipe, &module).await?;
+        println!("{name} | built {}", out.display());
+
+        Ok(())
+    }
+
+    /// Leaves the module where it is installed from and records it in the artifacts file.
+    async fn write(
+        &self,
+        dir: &Path,
+        name: &str,
+        c: &Canister,
+        recipe: &Recipe,
+        module: &[u8],
+    ) -> Result<PathBuf, CommandError> {
+        let out = wasm(dir, name);
+
+        tokio::fs::create_dir_all(dir.join(OUTPUT))
+            .await
+            .map_err(|err| CommandError::Write(out.to_owned(), err))?;
+
+        tokio::fs::write(&out, module)
+            .await
+            .map_err(|err| CommandError::Write(out.to_owned(), err))?;
+
+        let artifact = Artifact {
+            wasm: Path::new(OUTPUT).join(format!("{name}.wasm")),
+            sha256: hex::encode(Sha256::digest(module)),
+            candid: c.wasm.as_ref().and_then(|w| w.candid.to_owned()),
+            recipe: recipe.to_owned(),
+            environment: self.environment.to_owned(),
+            built_at: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .map_err(anyhow::Error::from)?,
+        };
+
+        self.artifacts.record(name, &artifact).await?;
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        path::Path,
+        sync::{Arc, Mutex},
+    };
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::{always, eq, function};
+    use sha2::{Digest, Sha256};
+
+    use crate::{
+        commands::{
+            Context, Mode,
+            build::{BuildArgs, CommandError, build, wasm},
+        },
+        operations::{
+            self,
+            build::{BuildError, MockBuild, MockCache, MockProcess, Step},
+            store::{
+                self, Artifact, Canister, Manifest, MockLoad, MockRecord, MockStore, Recipe, Wasm,
+            },
+        },
+    };
+
+    #[tokio::test]
+    async fn build_named_canister() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+
+        // Module left by the recipe
+        let module = dir.path().join("vendor.wasm");
+        std::fs::write(&module, b"\0asm")?;
+
+        // Mode (Project)
+        let mode = Mode::Project(dir.path().to_owned());
+
+        // Operations
+        let ops = operations::Initializers {
+            build: operations::build::Initializers {
+                build: Box::new(move |_| {
+                    let mut m = MockBuild::new();
+                    let module = module.clone();
+                    m.expect_build()
+                        .with(eq("backend"), always(), always())
+                        .once()
+                        .returning(move |_, _, _| Ok(module.clone()));
+                    Arc::new(m)
+                }),
+                cache: Box::new(|_| {
+                    let mut m = MockCache::new();
+                    m.expect_key()
+                        .returning(|_, _, _| Ok(Some("k".to_string())));
+                    m.expect_get().never();
+                    m.expect_put()
+                        .with(eq("k"), function(|m: &[u8]| m == b"\x1f\x8b"))
+                        .once()
+                        .returning(|_, _| Ok(()));
+                    Arc::new(m)
+                }),
+                process: Box::new(|_| {
+                    let mut m = MockProcess::new();
+                    m.expect_process()
+                        .with(eq("backend"), eq(b"\0asm".to_vec()), eq([Step::Gzip]))
+                        .once()
+                        .returning(|_, _, _| Ok(b"\x1f\x8b".to_vec()));
+                    Arc::new(m)
+                }),
+            },
+            store: store::Initializers {
+                artifacts: Box::new(|_| {
+                    let mut m = MockRecord::new();
+                    m.expect_record()
+                        .with(
+                            eq("backend"),
+                            function(|a: &Artifact| {
+                                a.wasm == Path::new(".icp/build/backend.wasm")
+                                    && a.environment == "local"
+                                    && a.sha256 == hex::encode(Sha256::digest(b"\x1f\x8b"))
+                            }),
+                        )
+                        .once()
+                        .returning(|_, _| Ok(()));
+                    Arc::new(m)
+                }),
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_list().returning(|_| Ok(BTreeMap::new()));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| {
+                        let prebuilt = Canister {
+                            build: Some(Recipe::Prebuilt {
+                                path: "vendor.wasm".into(),
+                            }),
+                            wasm: Some(Wasm {
+                                gzip: true,
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        };
+
+                        Ok(Manifest {
+                            canisters: BTreeMap::from([
+                                ("backend".to_string(), prebuilt.clone()),
+                                ("frontend".to_string(), prebuilt),
+                            ]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = BuildArgs {
+            name: Some("backend".to_string()),
+            force: true,
+            jobs: None,
+            keep_going: false,
+            environment: None,
+        };
+
+        build(&ctx, &args).await?;
+
+        assert_eq!(std::fs::read(wasm(dir.path(), "backend"))?, b"\x1f\x8b");
+        assert!(!wasm(dir.path(), "frontend").exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn build_served_from_cache() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+
+        // Mode (Project)
+        let mode = Mode::Project(dir.path().to_owned());
+
+        // Operations
+        let ops = operations::Initializers {
+            build: operations::build::Initializers {
+                build: Box::new(|_| {
+                    let mut m = MockBuild::new();
+                    m.expect_build().never();
+                    Arc::new(m)
+                }),
+                cache: Box::new(|_| {
+                    let mut m = MockCache::new();
+                    m.expect_key()
+                        .returning(|_, _, _| Ok(Some("k".to_string())));
+                    m.expect_get()
+                        .with(eq("k"))
+                        .returning(|_| Ok(Some(b"\0asm".to_vec())));
+                    Arc::new(m)
+                }),
+                process: Box::new(|_| Arc::new(MockProcess::new())),
+            },
+            store: store::Initializers {
+                artifacts: Box::new(|_| {
+                    let mut m = MockRecord::new();
+                    m.expect_record()
+                        .with(eq("backend"), always())
+                        .once()
+                        .returning(|_, _| Ok(()));
+                    Arc::new(m)
+                }),
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_list().returning(|_| Ok(BTreeMap::new()));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| {
+                        Ok(Manifest {
+                            canisters: BTreeMap::from([(
+                                "backend".to_string(),
+                                Canister {
+                                    build: Some(Recipe::Script {
+                                        command: "./build.sh".to_string(),
+                                        output: "out.wasm".into(),
+                                    }),
+                                    inputs: vec!["src".into()],
+                                    ..Default::default()
+                                },
+                            )]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = BuildArgs {
+            name: None,
+            force: false,
+            jobs: None,
+            keep_going: false,
+            environment: None,
+        };
+
+        build(&ctx, &args).await?;
+
+        assert_eq!(std::fs::read(wasm(dir.path(), "backend"))?, b"\0asm");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keep_going_skips_dependents_of_failures() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+
+        // Module left by every successful recipe
+        let module = dir.path().join("out.wasm");
+        std::fs::write(&module, b"\0asm")?;
+
+        let ledger = Principal::from_slice(&[1]);
+        let built = Arc::new(Mutex::new(vec![]));
+
+        // Mode (Project)
+        let mode = Mode::Project(dir.path().to_owned());
+
+        // Operations
+        let ops = operations::Initializers {
+            build: operations::build::Initializers {
+                build: Box::new({
+                    let built = built.clone();
+                    move |_| {
+                        let mut m = MockBuild::new();
+                        let (built, module) = (built.clone(), module.clone());
+                        m.expect_build().returning(move |name, _, env| {
+                            if name == "broken" {
+                                return Err(BuildError::MissingOutput("broken.wasm".into()));
+                            }
+
+                            built.lock().unwrap().push((name.to_string(), env.clone()));
+                            Ok(module.clone())
+                        });
+                        Arc::new(m)
+                    }
+                }),
+                cache: Box::new(|_| {
+                    let mut m = MockCache::new();
+                    m.expect_key().returning(|_, _, _| Ok(None));
+                    Arc::new(m)
+                }),
+                process: Box::new(|_| Arc::new(MockProcess::new())),
+            },
+            store: store::Initializers {
+                artifacts: Box::new(|_| {
+                    let mut m = MockRecord::new();
+                    m.expect_record().times(2).returning(|_, _| Ok(()));
+                    Arc::new(m)
+                }),
+                ids: Box::new(move |_| {
+                    let mut m = MockStore::new();
+                    m.expect_list()
+                        .with(eq("local"))
+                        .returning(move |_| Ok(BTreeMap::from([("ledger".to_string(), ledger)])));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| {
+                        let canister = |deps: &[&str]| Canister {
+                            build: Some(Recipe::Script {
+                                command: "./build.sh".to_string(),
+                                output: "out.wasm".into(),
+                            }),
+                            dependencies: deps.iter().map(|d| d.to_string()).collect(),
+                            ..Default::default()
+                        };
+
+                        Ok(Manifest {
+                            canisters: BTreeMap::from([
+                                ("ledger".to_string(), canister(&[])),
+                                ("backend".to_string(), canister(&["ledger"])),
+                                ("broken".to_string(), canister(&[])),
+                                ("frontend".to_string(), canister(&["backend", "broken"])),
+                            ]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = BuildArgs {
+            name: None,
+            force: false,
+            jobs: Some(1.try_into()?),
+            keep_going: true,
+            environment: None,
+        };
+
+        match build(&ctx, &args).await {
+            Err(CommandError::Incomplete(failed)) => assert_eq!(failed, ["broken"]),
+            out => panic!("expected the build to be incomplete: {out:?}"),
+        }
 
-    //     //
-    //     Mode::Global => {
-    //         todo!()
-    //     }
-    // };
+        // Dependencies first, with their IDs passed along
+        assert_eq!(
+            *built.lock().unwrap(),
+            [
+                ("ledger".to_string(), BTreeMap::new()),
+                (
+                    "backend".to_string(),
+                    BTreeMap::from([("CANISTER_ID_LEDGER".to_string(), ledger.to_string())])
+                ),
+            ]
+        );
+        assert!(!wasm(dir.path(), "frontend").exists());
 
-    Ok(())
+        Ok(())
+    }
 }
@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{Validate, ValidateError},
+    },
+    operations::canister::{self, CompatError},
+};
+
+#[derive(Args)]
+pub struct DiffArgs {
+    // Interface clients were built against
+    pub old: PathBuf,
+
+    // Interface to check
+    pub new: PathBuf,
+}
+
+impl Validate for DiffArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // old interface must exist
+            |args: &DiffArgs, _: &Mode| {
+                (!args.old.is_file())
+                    .then(|| format!("Interface not found at `{}`.", args.old.display()))
+            },
+            //
+            // new interface must exist
+            |args: &DiffArgs, _: &Mode| {
+                (!args.new.is_file())
+                    .then(|| format!("Interface not found at `{}`.", args.new.display()))
+            },
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("failed to read `{0}`")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error(transparent)]
+    Compat(#[from] CompatError),
+
+    #[error("found {0} breaking change(s)")]
+    Breaking(usize),
+}
+
+pub async fn diff(_: &Context, args: &DiffArgs) -> Result<(), CommandError> {
+    let read = async |path: &PathBuf| {
+        tokio::fs::read_to_string(path)
+            .await
+            .map_err(|err| CommandError::Read(path.to_owned(), err))
+    };
+
+    let (old, new) = (read(&args.old).await?, read(&args.new).await?);
+
+    let changes = canister::breaking_changes(&old, &new)?;
+
+    if changes.is_empty() {
+        println!(
+            "Compatible, `{}` can replace `{}`",
+            args.new.display(),
+            args.old.display()
+        );
+        return Ok(());
+    }
+
+    print!("{}", canister::report(&changes));
+
+    Err(CommandError::Breaking(changes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Error;
+
+    use crate::{
+        commands::{
+            Context, Mode,
+            candid::{DiffArgs, diff},
+        },
+        operations,
+    };
+
+    #[tokio::test]
+    async fn diff_reports_breaking_changes() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+
+        let (old, new) = (dir.path().join("old.did"), dir.path().join("new.did"));
+        std::fs::write(&old, "service : { greet : (text) -> (text) query }")?;
+        std::fs::write(&new, "service : { greet : (nat) -> (text) query }")?;
+
+        let ctx = Context {
+            mode: Mode::Global,
+            ops: operations::Initializers::default(),
+        };
+
+        let out = diff(&ctx, &DiffArgs { old, new }).await;
+
+        assert!(matches!(out, Err(super::CommandError::Breaking(1))));
+
+        Ok(())
+    }
+}
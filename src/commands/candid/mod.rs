@@ -0,0 +1,15 @@
+use clap::{Parser, Subcommand};
+
+mod diff;
+pub use diff::*;
+
+#[derive(Parser)]
+pub struct Command {
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    Diff(DiffArgs),
+}
@@ -0,0 +1,71 @@
+use std::{error::Error, future::Future};
+
+use candid::Principal;
+use futures::{StreamExt, stream};
+
+/// Outcome of an operation on one canister of a batch.
+pub struct Outcome<E> {
+    pub name: String,
+    pub cid: Principal,
+    pub result: Result<(), E>,
+}
+
+/// Runs an operation over every target, at most `concurrency` at once.
+///
+/// Outcomes are returned in the order of the targets.
+pub async fn each<F, Fut, E>(
+    targets: Vec<(String, Principal)>,
+    concurrency: usize,
+    f: F,
+) -> Vec<Outcome<E>>
+where
+    F: Fn(Principal) -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+{
+    stream::iter(targets)
+        .map(|(name, cid)| {
+            let fut = f(cid);
+            async move {
+                Outcome {
+                    name,
+                    cid,
+                    result: fut.await,
+                }
+            }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Prints a line per canister and returns how many failed.
+pub fn summarize<E: Error>(done: &str, outcomes: &[Outcome<E>]) -> usize {
+    let mut failed = 0;
+
+    for Outcome { name, cid, result } in outcomes {
+        let label = match name == &cid.to_text() {
+            true => name.to_owned(),
+            false => format!("{name} ({cid})"),
+        };
+
+        match result {
+            Ok(()) => println!("{label}: {done}"),
+
+            Err(err) => {
+                failed += 1;
+
+                // Errors are wrapped, so include their causes
+                let mut msg = err.to_string();
+                let mut source = err.source();
+                while let Some(err) = source {
+                    msg.push_str(&format!(": {err}"));
+                    source = err.source();
+                }
+
+                println!("{label}: failed: {msg}");
+            }
+        }
+    }
+
+    failed
+}
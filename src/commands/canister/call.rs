@@ -0,0 +1,546 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use candid::{IDLArgs, Principal, types::FuncMode};
+use candid_parser::configs::Configs;
+use clap::Args;
+use ic_agent::{
+    AgentError,
+    agent::{RejectCode, RejectResponse},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{
+        self,
+        canister::{Call, CallError, Interface, InterfaceError},
+    },
+};
+
+/// Bytes of entropy used to generate a single set of random arguments.
+const SEED_SIZE: usize = 2048;
+
+#[derive(Args)]
+pub struct CallArgs {
+    pub canister: args::Canister,
+
+    pub method: String,
+
+    // Textual Candid arguments, e.g. `("hello", 42 : nat)`
+    pub argument: Option<String>,
+
+    // Read the Candid arguments from a file
+    #[arg(long, conflicts_with = "argument")]
+    pub argument_file: Option<PathBuf>,
+
+    // Generate random arguments, configured by inline TOML or a TOML file
+    #[arg(long, conflicts_with_all = ["argument", "argument_file"])]
+    pub random: Option<String>,
+
+    // Number of calls to make with random arguments
+    #[arg(long, requires = "random", default_value_t = 100)]
+    pub count: u64,
+
+    // Seed for random arguments, a failing run can be replayed with its seed
+    #[arg(long, requires = "random")]
+    pub seed: Option<u64>,
+
+    // Local Candid interface, the canister metadata is used when omitted
+    #[arg(long)]
+    pub candid: Option<PathBuf>,
+
+    // Send a query call even when the interface is unknown
+    #[arg(long)]
+    pub query: bool,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(CallArgs, canister: args::Canister);
+impl_from_args!(CallArgs, network: Option<args::Network>);
+impl_from_args!(CallArgs, environment: Option<String>);
+impl_from_args!(CallArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for CallArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // argument file must exist
+            |args: &CallArgs, _: &Mode| {
+                args.argument_file
+                    .as_ref()
+                    .filter(|p| !p.is_file())
+                    .map(|p| format!("argument file not found at `{}`", p.display()))
+            },
+            //
+            // candid interface must exist
+            |args: &CallArgs, _: &Mode| {
+                args.candid
+                    .as_ref()
+                    .filter(|p| !p.is_file())
+                    .map(|p| format!("candid interface not found at `{}`", p.display()))
+            },
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("failed to read `{0}`")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error(transparent)]
+    Interface(#[from] InterfaceError),
+
+    #[error("failed to parse candid arguments")]
+    Parse(#[source] candid_parser::Error),
+
+    #[error("arguments do not match the method signature")]
+    Encode(#[source] candid::Error),
+
+    #[error("failed to decode the reply")]
+    Decode(#[source] candid::Error),
+
+    #[error("random arguments require a candid interface")]
+    InterfaceRequired,
+
+    #[error("failed to parse random config")]
+    Config(#[source] candid_parser::Error),
+
+    #[error("failed to generate random arguments")]
+    Generate(#[source] candid_parser::Error),
+
+    #[error("{0} of {1} calls were rejected")]
+    Rejected(u64, u64),
+
+    #[error("failed to call canister")]
+    Call(#[from] operations::canister::CallError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn call(ctx: &Context, args: &CallArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let caller = (ctx.ops.canister.call)(&agent);
+
+    // Interface
+    let interface = match &args.candid {
+        Some(p) => Some(read(p).await?),
+        None => match caller.interface(&cid).await {
+            Ok(interface) => interface,
+
+            // Metadata may be private to controllers, which is no reason not to call
+            Err(err) => {
+                eprintln!(
+                    "Failed to read the interface of {cid}, calling untyped: {:#}",
+                    anyhow::Error::from(err)
+                );
+                None
+            }
+        },
+    };
+
+    let interface = interface.as_deref().map(Interface::parse).transpose()?;
+
+    if let Some(config) = &args.random {
+        let iface = interface.as_ref().ok_or(CommandError::InterfaceRequired)?;
+        return fuzz(caller.as_ref(), &cid, iface, args, config).await;
+    }
+
+    // Arguments
+    let argument = match &args.argument_file {
+        Some(p) => read(p).await?,
+        None => args.argument.to_owned().unwrap_or("()".to_string()),
+    };
+
+    let argument = candid_parser::parse_idl_args(&argument).map_err(CommandError::Parse)?;
+
+    let reply = match &interface {
+        // Typed call
+        Some(iface) => {
+            let method = iface.method(&args.method)?;
+
+            let arg = argument
+                .to_bytes_with_types(&iface.env, &method.args)
+                .map_err(CommandError::Encode)?;
+
+            let query = args.query || is_query(&method.modes);
+            let reply = send(caller.as_ref(), &cid, &args.method, query, &arg).await?;
+
+            IDLArgs::from_bytes_with_types(&reply, &iface.env, &method.rets)
+                .map_err(CommandError::Decode)?
+        }
+
+        // Untyped call, record fields are shown as hashes
+        None => {
+            let arg = argument.to_bytes().map_err(CommandError::Encode)?;
+            let reply = send(caller.as_ref(), &cid, &args.method, args.query, &arg).await?;
+
+            IDLArgs::from_bytes(&reply).map_err(CommandError::Decode)?
+        }
+    };
+
+    println!("{reply}");
+
+    Ok(())
+}
+
+/// Calls the method repeatedly with well-typed random arguments, reporting
+/// every reject along with the arguments that caused it.
+async fn fuzz(
+    caller: &dyn Call,
+    cid: &Principal,
+    iface: &Interface,
+    args: &CallArgs,
+    config: &str,
+) -> Result<(), CommandError> {
+    let method = iface.method(&args.method)?;
+    let query = args.query || is_query(&method.modes);
+
+    let config = match Path::new(config).is_file() {
+        true => read(Path::new(config)).await?,
+        false => config.to_owned(),
+    };
+
+    let configs: Configs = config.parse().map_err(CommandError::Config)?;
+
+    let seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+
+    println!("Seed: {seed}");
+
+    let mut rejected = 0;
+
+    for i in 0..args.count {
+        let argument = candid_parser::random::any(
+            &entropy(seed, i),
+            configs.to_owned(),
+            &iface.env,
+            &method.args,
+            &None,
+        )
+        .map_err(CommandError::Generate)?;
+
+        let arg = argument
+            .to_bytes_with_types(&iface.env, &method.args)
+            .map_err(CommandError::Encode)?;
+
+        match send(caller, cid, &args.method, query, &arg).await {
+            Ok(_) => {}
+
+            Err(CallError::Agent(
+                AgentError::CertifiedReject { reject, .. }
+                | AgentError::UncertifiedReject { reject, .. },
+            )) => {
+                rejected += 1;
+
+                println!("#{i} {}: {}", outcome(&reject), reject.reject_message);
+                println!("  {argument}");
+            }
+
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    println!("{} calls, {rejected} rejected", args.count);
+
+    match rejected {
+        0 => Ok(()),
+        n => Err(CommandError::Rejected(n, args.count)),
+    }
+}
+
+fn is_query(modes: &[FuncMode]) -> bool {
+    modes
+        .iter()
+        .any(|m| matches!(m, FuncMode::Query | FuncMode::CompositeQuery))
+}
+
+async fn send(
+    caller: &dyn Call,
+    cid: &Principal,
+    method: &str,
+    query: bool,
+    arg: &[u8],
+) -> Result<Vec<u8>, CallError> {
+    match query {
+        true => caller.query(cid, method, arg).await,
+        false => caller.update(cid, method, arg).await,
+    }
+}
+
+fn outcome(reject: &RejectResponse) -> &'static str {
+    match reject.reject_code {
+        RejectCode::CanisterError => "trapped",
+        _ => "rejected",
+    }
+}
+
+/// Deterministic entropy for the i-th call of a run.
+fn entropy(seed: u64, i: u64) -> Vec<u8> {
+    (0u64..)
+        .flat_map(|block| {
+            Sha256::new()
+                .chain_update(seed.to_le_bytes())
+                .chain_update(i.to_le_bytes())
+                .chain_update(block.to_le_bytes())
+                .finalize()
+        })
+        .take(SEED_SIZE)
+        .collect()
+}
+
+async fn read(p: &Path) -> Result<String, CommandError> {
+    tokio::fs::read_to_string(p)
+        .await
+        .map_err(|err| CommandError::Read(p.to_owned(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Error;
+    use candid::{Decode, Encode, Principal};
+    use ic_agent::{
+        AgentError,
+        agent::{RejectCode, RejectResponse},
+    };
+    use mockall::predicate::{always, eq, function};
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::{CallArgs, call},
+        },
+        operations::{
+            self,
+            canister::{self, CallError, MockCall},
+        },
+    };
+
+    #[tokio::test]
+    async fn call_query_with_metadata_interface() -> Result<(), Error> {
+        // Mode (Global)
+        let mode = Mode::Global;
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                call: Box::new(|_| {
+                    let mut m = MockCall::new();
+
+                    m.expect_interface()
+                        .with(eq(Principal::anonymous()))
+                        .once()
+                        .returning(|_| {
+                            Ok(Some(
+                                "service : { greet : (text, nat) -> (text) query }".to_string(),
+                            ))
+                        });
+
+                    // Arguments are encoded with the declared types
+                    m.expect_query()
+                        .with(
+                            eq(Principal::anonymous()),
+                            eq("greet"),
+                            function(|arg: &[u8]| {
+                                arg == Encode!(&"world", &candid::Nat::from(3u8)).unwrap()
+                            }),
+                        )
+                        .once()
+                        .returning(|_, _, _| Ok(Encode!(&"hello, world").unwrap()));
+
+                    m.expect_update().with(always(), always(), always()).never();
+
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = CallArgs {
+            canister: args::Canister::Principal(Principal::anonymous()),
+            method: "greet".to_string(),
+            argument: Some(r#"("world", 3)"#.to_string()),
+            argument_file: None,
+            random: None,
+            count: 100,
+            seed: None,
+            candid: None,
+            query: false,
+            network: Some(args::Network::Url("https://icp-api.io".to_string())),
+            environment: None,
+        };
+
+        call(&ctx, &args).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn call_untyped_when_interface_is_unreadable() -> Result<(), Error> {
+        // Mode (Global)
+        let mode = Mode::Global;
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                call: Box::new(|_| {
+                    let mut m = MockCall::new();
+
+                    m.expect_interface().once().returning(|_| {
+                        Err(CallError::Agent(AgentError::MessageError(
+                            "metadata is private".to_string(),
+                        )))
+                    });
+
+                    m.expect_update()
+                        .with(
+                            eq(Principal::anonymous()),
+                            eq("greet"),
+                            function(|arg: &[u8]| arg == Encode!(&"world").unwrap()),
+                        )
+                        .once()
+                        .returning(|_, _, _| Ok(Encode!(&"hello, world").unwrap()));
+
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = CallArgs {
+            canister: args::Canister::Principal(Principal::anonymous()),
+            method: "greet".to_string(),
+            argument: Some(r#"("world")"#.to_string()),
+            argument_file: None,
+            random: None,
+            count: 100,
+            seed: None,
+            candid: None,
+            query: false,
+            network: Some(args::Network::Url("https://icp-api.io".to_string())),
+            environment: None,
+        };
+
+        call(&ctx, &args).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn call_random_reports_traps() -> Result<(), Error> {
+        // Mode (Global)
+        let mode = Mode::Global;
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                call: Box::new(|_| {
+                    let mut m = MockCall::new();
+
+                    m.expect_interface().returning(|_| {
+                        Ok(Some("service : { set : (nat8, text) -> () }".to_string()))
+                    });
+
+                    // Every generated argument is well-typed
+                    m.expect_update()
+                        .with(
+                            eq(Principal::anonymous()),
+                            eq("set"),
+                            function(|arg: &[u8]| Decode!(arg, u8, String).is_ok()),
+                        )
+                        .times(3)
+                        .returning(|_, _, _| {
+                            Err(canister::CallError::Agent(AgentError::CertifiedReject {
+                                reject: RejectResponse {
+                                    reject_code: RejectCode::CanisterError,
+                                    reject_message: "trapped".to_string(),
+                                    error_code: None,
+                                },
+                                operation: None,
+                            }))
+                        });
+
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = CallArgs {
+            canister: args::Canister::Principal(Principal::anonymous()),
+            method: "set".to_string(),
+            argument: None,
+            argument_file: None,
+            random: Some("text = \"ascii\"".to_string()),
+            count: 3,
+            seed: Some(42),
+            candid: None,
+            query: false,
+            network: Some(args::Network::Url("https://icp-api.io".to_string())),
+            environment: None,
+        };
+
+        let out = call(&ctx, &args).await;
+        assert!(matches!(out, Err(super::CommandError::Rejected(3, 3))));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,494 @@
+use candid::Principal;
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{self, canister::WithdrawTarget},
+};
+
+#[derive(Args)]
+pub struct DeleteArgs {
+    pub canister: args::Canister,
+
+    // Withdraw remaining cycles to a canister
+    #[arg(long, conflicts_with = "withdraw_to_account")]
+    pub withdraw_to_canister: Option<Principal>,
+
+    // Withdraw remaining cycles to a cycles ledger account
+    #[arg(long)]
+    pub withdraw_to_account: Option<Principal>,
+
+    // Subaccount of the cycles ledger account (hex)
+    #[arg(long, requires = "withdraw_to_account")]
+    pub subaccount: Option<String>,
+
+    // Skip confirmation on non-local networks
+    #[arg(long)]
+    pub yes: bool,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(DeleteArgs, canister: args::Canister);
+impl_from_args!(DeleteArgs, network: Option<args::Network>);
+impl_from_args!(DeleteArgs, environment: Option<String>);
+impl_from_args!(DeleteArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl DeleteArgs {
+    /// Where to send the remaining cycles, fails on a malformed subaccount.
+    fn withdraw_target(&self) -> Result<Option<WithdrawTarget>, String> {
+        if let Some(cid) = self.withdraw_to_canister {
+            return Ok(Some(WithdrawTarget::Canister(cid)));
+        }
+
+        let Some(owner) = self.withdraw_to_account else {
+            return Ok(None);
+        };
+
+        let subaccount = self
+            .subaccount
+            .as_ref()
+            .map(|v| {
+                hex::decode(v)
+                    .ok()
+                    .and_then(|v| <[u8; 32]>::try_from(v).ok())
+                    .ok_or_else(|| v.to_owned())
+            })
+            .transpose()?;
+
+        Ok(Some(WithdrawTarget::Account { owner, subaccount }))
+    }
+}
+
+impl Validate for DeleteArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // subaccount must be 32 bytes of hex
+            |args: &DeleteArgs, _: &Mode| {
+                args.withdraw_target()
+                    .err()
+                    .map(|v| format!("Invalid subaccount `{v}`, expected 32 bytes of hex."))
+            },
+            //
+            // dummy case to shush linter
+            |_: &DeleteArgs, _: &Mode| None,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("invalid subaccount `{0}`, expected 32 bytes of hex")]
+    Subaccount(String),
+
+    #[error("refusing to delete a canister on non-local network `{0}` without `--yes`")]
+    ConfirmationRequired(String),
+
+    #[error("failed to stop canister")]
+    Stop(#[from] operations::canister::StopError),
+
+    #[error("failed to withdraw cycles")]
+    Withdraw(#[from] operations::canister::WithdrawError),
+
+    #[error("failed to delete canister")]
+    Delete(#[from] operations::canister::DeleteError),
+
+    #[error("failed to remove canister from the ID store")]
+    Unregister(#[from] operations::store::UnregisterError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn delete(ctx: &Context, args: &DeleteArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::owned(ctx, &args.canister, environment).await?;
+    let target = args.withdraw_target().map_err(CommandError::Subaccount)?;
+
+    // Summary
+    println!("The following will be permanently destroyed:");
+    match &args.canister {
+        args::Canister::Principal(_) => println!("  canister:    {cid}"),
+        canister => println!("  canister:    {canister} ({cid})"),
+    }
+    println!("  network:     {} ({})", network.name, network.url);
+    if let Mode::Project(_) = ctx.mode {
+        println!("  environment: {environment}");
+    }
+    match &target {
+        Some(WithdrawTarget::Canister(to)) => {
+            println!("  cycles:      withdrawn to canister {to}")
+        }
+        Some(WithdrawTarget::Account { owner, subaccount }) => match subaccount {
+            Some(sub) => println!(
+                "  cycles:      withdrawn to account {owner} (subaccount {})",
+                hex::encode(sub)
+            ),
+            None => println!("  cycles:      withdrawn to account {owner}"),
+        },
+        None => println!("  cycles:      all remaining cycles are lost"),
+    }
+
+    if !network.local && !args.yes {
+        return Err(CommandError::ConfirmationRequired(network.name));
+    }
+
+    let agent = resolve::agent(&network).await?;
+
+    (ctx.ops.canister.stop)(&agent).stop(&cid).await?;
+
+    if let Some(target) = &target {
+        let amount = (ctx.ops.canister.withdraw)(&agent)
+            .withdraw(&cid, target)
+            .await?;
+
+        println!("Withdrew {amount} cycles");
+    }
+
+    (ctx.ops.canister.delete)(&agent).delete(&cid).await?;
+
+    // Forget the canister
+    if let Some(at) = resolve::locate(ctx, &args.canister, environment).await? {
+        (ctx.ops.store.ids)(&at.dir)
+            .unregister(&at.environment, &at.name)
+            .await?;
+    }
+
+    println!("Deleted canister {cid}");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+    };
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::{DeleteArgs, delete, delete::CommandError},
+            resolve::ResolveError,
+        },
+        operations::{
+            self,
+            canister::{self, MockDelete, MockStop, MockWithdraw, WithdrawTarget},
+            store::{self, Environment, LookupError, Manifest, MockLoad, MockStore},
+        },
+    };
+
+    const CID: &str = "ntyui-iatoh-pfi3f-27wnk-vgdqt-mq3cl-ld7jh-743kl-sde6i-tbm7g-tqe";
+
+    #[tokio::test]
+    async fn delete_in_project() -> Result<(), Error> {
+        let cid = Principal::from_text(CID)?;
+        let unregistered = Arc::new(AtomicBool::new(false));
+
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                stop: Box::new(move |_| {
+                    let mut m = MockStop::new();
+                    m.expect_stop().with(eq(cid)).once().returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                withdraw: Box::new(move |_| {
+                    let mut m = MockWithdraw::new();
+                    m.expect_withdraw()
+                        .with(
+                            eq(cid),
+                            eq(WithdrawTarget::Canister(Principal::anonymous())),
+                        )
+                        .once()
+                        .returning(|_, _| Ok(1_000));
+                    Arc::new(m)
+                }),
+                delete: Box::new(move |_| {
+                    let mut m = MockDelete::new();
+                    m.expect_delete().with(eq(cid)).once().returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new({
+                    let unregistered = unregistered.clone();
+                    move |_| {
+                        let mut m = MockStore::new();
+                        m.expect_lookup()
+                            .with(eq("prod"), eq("my-canister"))
+                            .returning(move |_, _| Ok(cid));
+                        m.expect_unregister()
+                            .with(eq("prod"), eq("my-canister"))
+                            .returning({
+                                let unregistered = unregistered.clone();
+                                move |_, _| {
+                                    unregistered.store(true, Ordering::SeqCst);
+                                    Ok(())
+                                }
+                            });
+                        Arc::new(m)
+                    }
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    // Prod is deployed to the IC
+                    m.expect_load().returning(|| {
+                        Ok(Manifest {
+                            environments: BTreeMap::from([(
+                                "prod".to_string(),
+                                Environment {
+                                    network: Some("ic".to_string()),
+                                    ..Default::default()
+                                },
+                            )]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = DeleteArgs {
+            canister: args::Canister::Name("my-canister".to_string()),
+            withdraw_to_canister: Some(Principal::anonymous()),
+            withdraw_to_account: None,
+            subaccount: None,
+            yes: true,
+            network: None,
+            environment: Some("prod".to_string()),
+        };
+
+        delete(&ctx, &args).await?;
+
+        assert!(unregistered.load(Ordering::SeqCst));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_by_name_resolves_in_the_network_environment() -> Result<(), Error> {
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations, nothing is stopped or deleted
+        let ops = operations::Initializers {
+            store: store::Initializers {
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .with(eq("ic"), eq("my-canister"))
+                        .returning(|environment, name| {
+                            Err(LookupError::NotFound {
+                                environment: environment.to_string(),
+                                name: name.to_string(),
+                            })
+                        });
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        // The canister only has an ID in the default environment
+        let args = DeleteArgs {
+            canister: args::Canister::Name("my-canister".to_string()),
+            withdraw_to_canister: None,
+            withdraw_to_account: None,
+            subaccount: None,
+            yes: true,
+            network: Some(args::Network::Name("ic".to_string())),
+            environment: None,
+        };
+
+        assert!(matches!(
+            delete(&ctx, &args).await,
+            Err(CommandError::Resolve(ResolveError::Lookup(
+                LookupError::NotFound { .. }
+            )))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_requires_confirmation_on_non_local_networks() -> Result<(), Error> {
+        // Mode (Global)
+        let mode = Mode::Global;
+
+        // Operations
+        let ops = operations::Initializers::default();
+
+        let ctx = Context { mode, ops };
+
+        let args = DeleteArgs {
+            canister: args::Canister::Principal(Principal::from_text(CID)?),
+            withdraw_to_canister: None,
+            withdraw_to_account: None,
+            subaccount: None,
+            yes: false,
+            network: Some(args::Network::Url("https://icp-api.io".to_string())),
+            environment: None,
+        };
+
+        match delete(&ctx, &args).await {
+            Err(CommandError::ConfirmationRequired(_)) => {}
+            out => panic!("expected confirmation to be required: {out:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_refuses_qualified_canister() -> Result<(), Error> {
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations, nothing is looked up, stopped or deleted
+        let ops = operations::Initializers {
+            store: store::Initializers {
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = DeleteArgs {
+            canister: "staging:backend".parse().map_err(Error::msg)?,
+            withdraw_to_canister: None,
+            withdraw_to_account: None,
+            subaccount: None,
+            yes: true,
+            network: None,
+            environment: None,
+        };
+
+        match delete(&ctx, &args).await {
+            Err(CommandError::Resolve(ResolveError::Qualified(name))) => {
+                assert_eq!(name, "staging:backend")
+            }
+            out => panic!("expected the qualified canister to be refused: {out:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_refuses_remote_canister() -> Result<(), Error> {
+        let ledger = Principal::from_text(CID)?;
+
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            store: store::Initializers {
+                manifest: Box::new(move |_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(move || {
+                        Ok(Manifest {
+                            canisters: BTreeMap::from([(
+                                "ledger".to_string(),
+                                store::Canister {
+                                    remote: Some(BTreeMap::from([("ic".to_string(), ledger)])),
+                                    ..Default::default()
+                                },
+                            )]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        // Referenced by its ID rather than its name
+        let args = DeleteArgs {
+            canister: args::Canister::Principal(ledger),
+            withdraw_to_canister: None,
+            withdraw_to_account: None,
+            subaccount: None,
+            yes: true,
+            network: None,
+            environment: Some("ic".to_string()),
+        };
+
+        match delete(&ctx, &args).await {
+            Err(CommandError::Resolve(ResolveError::Remote(name))) => assert_eq!(name, "ledger"),
+            out => panic!("expected the remote canister to be refused: {out:?}"),
+        }
+
+        Ok(())
+    }
+}
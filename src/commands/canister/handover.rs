@@ -0,0 +1,187 @@
+use candid::Principal;
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::canister::{Handover, HandoverError},
+};
+
+#[derive(Args)]
+pub struct HandoverArgs {
+    pub canister: args::Canister,
+
+    // Principal taking over control, e.g. another team or a DAO
+    #[arg(long)]
+    pub to: Principal,
+
+    // Current controller to keep as a backup, may be repeated
+    #[arg(long)]
+    pub keep: Vec<Principal>,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(HandoverArgs, canister: args::Canister);
+impl_from_args!(HandoverArgs, network: Option<args::Network>);
+impl_from_args!(HandoverArgs, environment: Option<String>);
+impl_from_args!(HandoverArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for HandoverArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("failed to hand over canister, run the command again to resume")]
+    Handover(#[from] HandoverError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn handover(ctx: &Context, args: &HandoverArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let caller = agent
+        .get_principal()
+        .map_err(|err| anyhow::format_err!(err))?;
+
+    let handover = Handover {
+        settings: (ctx.ops.canister.settings)(&agent),
+        info: (ctx.ops.canister.info)(&agent),
+    };
+
+    let controllers = handover
+        .run(&cid, &args.to, &args.keep, &caller, &|step| {
+            println!("{cid}: {step}")
+        })
+        .await?;
+
+    println!(
+        "{cid}: controlled by {}",
+        controllers
+            .iter()
+            .map(Principal::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::{always, function};
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::{HandoverArgs, handover},
+        },
+        operations::{
+            self,
+            canister::{self, CanisterSettings, DefiniteCanisterSettings, MockInfo, MockSettings},
+        },
+    };
+
+    #[tokio::test]
+    async fn handover_keeps_backup() -> Result<(), Error> {
+        // Mode (Global)
+        let mode = Mode::Global;
+
+        // The anonymous caller controls the canister with a backup
+        let (backup, dao) = (Principal::from_slice(&[1]), Principal::from_slice(&[2]));
+        let current = Arc::new(Mutex::new(vec![Principal::anonymous(), backup]));
+
+        // Operations
+        let (c, ci) = (current.clone(), current.clone());
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                settings: Box::new(move |_| {
+                    let mut m = MockSettings::new();
+                    let g = c.clone();
+                    m.expect_get().times(2).returning(move |_| {
+                        Ok(DefiniteCanisterSettings {
+                            controllers: g.lock().unwrap().clone(),
+                            ..Default::default()
+                        })
+                    });
+                    let u = c.clone();
+                    m.expect_update()
+                        .with(
+                            always(),
+                            function(|s: &CanisterSettings| s.controllers.is_some()),
+                        )
+                        .times(2)
+                        .returning(move |_, s| {
+                            *u.lock().unwrap() = s.controllers.unwrap();
+                            Ok(())
+                        });
+                    Arc::new(m)
+                }),
+                info: Box::new(move |_| {
+                    let mut m = MockInfo::new();
+                    let c = ci.clone();
+                    m.expect_controllers()
+                        .once()
+                        .returning(move |_| Ok(c.lock().unwrap().clone()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = HandoverArgs {
+            canister: args::Canister::Principal(Principal::anonymous()),
+            to: dao,
+            keep: vec![backup],
+            network: Some(args::Network::Url("https://icp-api.io".to_string())),
+            environment: None,
+        };
+
+        handover(&ctx, &args).await?;
+
+        assert_eq!(*current.lock().unwrap(), [dao, backup]);
+
+        Ok(())
+    }
+}
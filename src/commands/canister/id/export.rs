@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{Validate, ValidateError},
+    },
+    operations::store::{Ids, ListError},
+};
+
+#[derive(Args)]
+pub struct ExportArgs {
+    // Only export this environment
+    #[arg(long)]
+    pub environment: Option<String>,
+
+    // File to write, stdout if unset
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+impl Validate for ExportArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // IDs are stored with the project
+        match mode {
+            Mode::Global => Err(ValidateError::Unexpected(anyhow::format_err!(
+                "Canister IDs are only available in project mode."
+            ))),
+            Mode::Project(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("canister IDs are only available in project mode")]
+    ProjectRequired,
+
+    #[error(transparent)]
+    List(#[from] ListError),
+
+    #[error("failed to write `{0}`")]
+    Write(PathBuf, #[source] std::io::Error),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Writes the ID store as JSON, `{ environment: { canister: principal } }`.
+pub async fn export(ctx: &Context, args: &ExportArgs) -> Result<(), CommandError> {
+    let Mode::Project(dir) = &ctx.mode else {
+        return Err(CommandError::ProjectRequired);
+    };
+
+    let ids = (ctx.ops.store.ids)(dir);
+
+    let ids: Ids = match &args.environment {
+        Some(environment) => Ids::from([(environment.to_owned(), ids.list(environment).await?)]),
+        None => ids.all().await?,
+    };
+
+    let out = serde_json::to_string_pretty(&ids).map_err(anyhow::Error::from)?;
+
+    match &args.output {
+        Some(path) => tokio::fs::write(path, out + "\n")
+            .await
+            .map_err(|err| CommandError::Write(path.to_owned(), err))?,
+
+        None => println!("{out}"),
+    }
+
+    Ok(())
+}
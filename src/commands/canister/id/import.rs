@@ -0,0 +1,209 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use tokio::io::AsyncReadExt;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{Validate, ValidateError},
+    },
+    operations::store::{Ids, ListError, RegisterError},
+};
+
+#[derive(Args)]
+pub struct ImportArgs {
+    // JSON file as written by `canister id export`, `-` for stdin
+    pub file: PathBuf,
+
+    // Overwrite IDs already recorded for a canister
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl Validate for ImportArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // IDs are stored with the project
+        match mode {
+            Mode::Global => Err(ValidateError::Unexpected(anyhow::format_err!(
+                "Canister IDs are only available in project mode."
+            ))),
+            Mode::Project(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("canister IDs are only available in project mode")]
+    ProjectRequired,
+
+    #[error("failed to read `{0}`")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("failed to parse `{0}`")]
+    Parse(PathBuf, #[source] serde_json::Error),
+
+    #[error(
+        "{} canister(s) already have a different ID, use `--force` to overwrite: {}",
+        .0.len(),
+        .0.join(", ")
+    )]
+    Conflicts(Vec<String>),
+
+    #[error(transparent)]
+    List(#[from] ListError),
+
+    #[error(transparent)]
+    Register(#[from] RegisterError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn import(ctx: &Context, args: &ImportArgs) -> Result<(), CommandError> {
+    let Mode::Project(dir) = &ctx.mode else {
+        return Err(CommandError::ProjectRequired);
+    };
+
+    let bs = match args.file.to_str() {
+        Some("-") => {
+            let mut bs = vec![];
+            tokio::io::stdin().read_to_end(&mut bs).await.map(|_| bs)
+        }
+        _ => tokio::fs::read(&args.file).await,
+    }
+    .map_err(|err| CommandError::Read(args.file.to_owned(), err))?;
+
+    let incoming: Ids = serde_json::from_slice(&bs)
+        .map_err(|err| CommandError::Parse(args.file.to_owned(), err))?;
+
+    let store = (ctx.ops.store.ids)(dir);
+    let current = store.all().await?;
+
+    // Mappings that would change, checked before anything is written
+    let mut conflicts: Vec<String> = vec![];
+
+    for (environment, ids) in &incoming {
+        for (name, cid) in ids {
+            if let Some(existing) = current.get(environment).and_then(|ids| ids.get(name))
+                && existing != cid
+            {
+                conflicts.push(format!("{environment}/{name} ({existing} -> {cid})"));
+            }
+        }
+    }
+
+    if !conflicts.is_empty() && !args.force {
+        return Err(CommandError::Conflicts(conflicts));
+    }
+
+    let mut changed = Ids::default();
+    let mut imported = 0;
+
+    for (environment, ids) in incoming {
+        for (name, cid) in ids {
+            let unchanged = current.get(&environment).and_then(|ids| ids.get(&name)) == Some(&cid);
+
+            if !unchanged {
+                changed
+                    .entry(environment.to_owned())
+                    .or_default()
+                    .insert(name, cid);
+                imported += 1;
+            }
+        }
+    }
+
+    // All or nothing
+    if !changed.is_empty() {
+        store.register_all(&changed).await?;
+    }
+
+    println!(
+        "Imported {imported} canister ID(s), {} overwritten",
+        conflicts.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode,
+            canister::id::{ImportArgs, import},
+        },
+        operations::{
+            self,
+            store::{self, Ids, MockStore},
+        },
+    };
+
+    fn ids(backend: Principal) -> Ids {
+        BTreeMap::from([(
+            "ic".to_string(),
+            BTreeMap::from([("backend".to_string(), backend)]),
+        )])
+    }
+
+    fn ctx(dir: &std::path::Path, force: bool) -> Context {
+        let ops = operations::Initializers {
+            store: store::Initializers {
+                ids: Box::new(move |_| {
+                    let mut m = MockStore::new();
+                    m.expect_all()
+                        .returning(|| Ok(ids(Principal::from_slice(&[1]))));
+
+                    // Only a forced import overwrites the existing ID
+                    m.expect_register_all()
+                        .with(eq(ids(Principal::from_slice(&[2]))))
+                        .times(force as usize)
+                        .returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        Context {
+            mode: Mode::Project(dir.to_owned()),
+            ops,
+        }
+    }
+
+    #[tokio::test]
+    async fn import_refuses_conflicts_without_force() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+
+        let file = dir.path().join("ids.json");
+        std::fs::write(
+            &file,
+            serde_json::to_vec(&ids(Principal::from_slice(&[2])))?,
+        )?;
+
+        let args = ImportArgs {
+            file: file.to_owned(),
+            force: false,
+        };
+
+        assert!(matches!(
+            import(&ctx(dir.path(), false), &args).await,
+            Err(super::CommandError::Conflicts(cs)) if cs.len() == 1
+        ));
+
+        let args = ImportArgs { file, force: true };
+
+        import(&ctx(dir.path(), true), &args).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use clap::Args;
+
+use crate::commands::{
+    Context, Mode,
+    args::{self, ValidateError},
+    resolve::{self, ResolveError},
+};
+
+#[derive(Args)]
+pub struct LookupArgs {
+    // Canister name
+    #[arg(required = true)]
+    pub name: Option<String>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl args::Validate for LookupArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // IDs are stored with the project
+        match mode {
+            Mode::Global => Err(ValidateError::Unexpected(anyhow::format_err!(
+                "Canister IDs are only available in project mode."
+            ))),
+            Mode::Project(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn lookup(ctx: &Context, args: &LookupArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&None, &args.environment);
+
+    let name = args
+        .name
+        .to_owned()
+        .expect("clap requires a name without a subcommand");
+
+    let cid = resolve::canister(ctx, &args::Canister::Name(name), environment).await?;
+
+    println!("{cid}");
+
+    Ok(())
+}
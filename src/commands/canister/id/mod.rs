@@ -0,0 +1,26 @@
+use clap::{Parser, Subcommand};
+
+mod export;
+pub use export::*;
+
+mod import;
+pub use import::*;
+
+mod lookup;
+pub use lookup::*;
+
+#[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct Command {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    #[command(flatten)]
+    pub lookup: LookupArgs,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    Export(ExportArgs),
+    Import(ImportArgs),
+}
@@ -0,0 +1,170 @@
+use candid::Principal;
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::canister::{CANDID_SERVICE, InfoError},
+};
+
+#[derive(Args)]
+pub struct InfoArgs {
+    pub canister: args::Canister,
+
+    // Additional metadata sections to read, e.g. `git_commit`
+    #[arg(long)]
+    pub metadata: Vec<String>,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(InfoArgs, canister: args::Canister);
+impl_from_args!(InfoArgs, network: Option<args::Network>);
+impl_from_args!(InfoArgs, environment: Option<String>);
+impl_from_args!(InfoArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for InfoArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("failed to read canister info")]
+    Info(#[from] InfoError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn info(ctx: &Context, args: &InfoArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let info = (ctx.ops.canister.info)(&agent);
+
+    let controllers = info.controllers(&cid).await?;
+    let module_hash = info.module_hash(&cid).await?;
+
+    println!("{:<23}{cid}", "Canister:");
+    println!("{:<23}{}", "Controllers:", principals(&controllers));
+    println!(
+        "{:<23}{}",
+        "Module hash:",
+        module_hash
+            .map(|h| format!("0x{}", hex::encode(h)))
+            .unwrap_or("none".to_string())
+    );
+
+    for name in std::iter::once(CANDID_SERVICE).chain(args.metadata.iter().map(String::as_str)) {
+        // Private sections are only readable by controllers
+        match info.metadata(&cid, name).await? {
+            Some(bs) => match String::from_utf8(bs) {
+                Ok(s) => println!("\nMetadata `{name}`:\n{}", s.trim_end()),
+                Err(err) => println!("\nMetadata `{name}`: 0x{}", hex::encode(err.as_bytes())),
+            },
+
+            None => println!("\nMetadata `{name}`: none"),
+        }
+    }
+
+    Ok(())
+}
+
+fn principals(ps: &[Principal]) -> String {
+    ps.iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::{InfoArgs, info},
+        },
+        operations::{
+            self,
+            canister::{self, MockInfo},
+        },
+    };
+
+    #[tokio::test]
+    async fn info_reads_requested_metadata() -> Result<(), Error> {
+        // Mode (Global)
+        let mode = Mode::Global;
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                info: Box::new(|_| {
+                    let mut m = MockInfo::new();
+                    m.expect_controllers()
+                        .returning(|_| Ok(vec![Principal::management_canister()]));
+                    m.expect_module_hash().returning(|_| Ok(Some(vec![0xab])));
+                    m.expect_metadata()
+                        .with(eq(Principal::anonymous()), eq("candid:service"))
+                        .once()
+                        .returning(|_, _| Ok(Some(b"service : {}".to_vec())));
+                    m.expect_metadata()
+                        .with(eq(Principal::anonymous()), eq("git_commit"))
+                        .once()
+                        .returning(|_, _| Ok(None));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = InfoArgs {
+            canister: args::Canister::Principal(Principal::anonymous()),
+            metadata: vec!["git_commit".to_string()],
+            network: Some(args::Network::Url("https://icp-api.io".to_string())),
+            environment: None,
+        };
+
+        info(&ctx, &args).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,592 @@
+use std::path::{Path, PathBuf};
+
+use candid::{Encode, Principal};
+use clap::{Args, ValueEnum};
+use ic_agent::Agent;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{
+        self,
+        canister::{self, CanisterInstallMode, Code, Orchestrator},
+        store::ArtifactsError,
+    },
+};
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum InstallMode {
+    Install,
+    Reinstall,
+    Upgrade,
+}
+
+impl From<&InstallMode> for CanisterInstallMode {
+    fn from(v: &InstallMode) -> Self {
+        match v {
+            InstallMode::Install => CanisterInstallMode::Install,
+            InstallMode::Reinstall => CanisterInstallMode::Reinstall,
+            InstallMode::Upgrade => CanisterInstallMode::Upgrade(None),
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct InstallArgs {
+    pub canister: args::Canister,
+
+    // Wasm module, the last build of the canister if unset
+    #[arg(long)]
+    pub wasm: Option<PathBuf>,
+
+    // Install mode
+    #[arg(long, value_enum, default_value_t = InstallMode::Install)]
+    pub mode: InstallMode,
+
+    // Candid interface of the new module, checked against the deployed one on upgrade
+    #[arg(long)]
+    pub candid: Option<PathBuf>,
+
+    // Upgrade even if the new interface breaks existing clients
+    #[arg(long)]
+    pub allow_breaking: bool,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(InstallArgs, canister: args::Canister);
+impl_from_args!(InstallArgs, network: Option<args::Network>);
+impl_from_args!(InstallArgs, environment: Option<String>);
+impl_from_args!(InstallArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for InstallArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // wasm module must exist
+            |args: &InstallArgs, _: &Mode| {
+                args.wasm
+                    .as_ref()
+                    .filter(|path| !path.is_file())
+                    .map(|path| format!("wasm module not found at `{}`", path.display()))
+            },
+            //
+            // there are no builds to install outside a project
+            |args: &InstallArgs, mode: &Mode| {
+                (args.wasm.is_none() && matches!(mode, Mode::Global))
+                    .then_some("A wasm module is required in global mode.".to_string())
+            },
+            //
+            // interfaces are only compared on upgrade
+            |args: &InstallArgs, _: &Mode| {
+                (args.candid.is_some() && !matches!(args.mode, InstallMode::Upgrade)).then_some(
+                    "An interface can only be checked with `--mode upgrade`.".to_string(),
+                )
+            },
+            //
+            // candid interface must exist
+            |args: &InstallArgs, _: &Mode| {
+                args.candid
+                    .as_ref()
+                    .filter(|path| !path.is_file())
+                    .map(|path| format!("candid interface not found at `{}`", path.display()))
+            },
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("failed to read wasm module")]
+    Read(#[from] std::io::Error),
+
+    #[error("no wasm module given for `{0}`, see `--wasm`")]
+    WasmRequired(String),
+
+    #[error("canister `{0}` has not been built yet")]
+    NotBuilt(String),
+
+    #[error("the module of canister `{0}` changed since it was built, build it again")]
+    Stale(String),
+
+    #[error("canister `{name}` was built for environment `{built}`, build it for `{environment}`")]
+    OtherEnvironment {
+        name: String,
+        built: String,
+        environment: String,
+    },
+
+    #[error("failed to read the build artifacts")]
+    Artifacts(#[from] ArtifactsError),
+
+    #[error("failed to install canister")]
+    Install(#[from] operations::canister::InstallError),
+
+    #[error(transparent)]
+    Orchestrate(#[from] operations::canister::OrchestrateError),
+
+    #[error("failed to read deployed interface")]
+    Call(#[from] operations::canister::CallError),
+
+    #[error(transparent)]
+    Compat(#[from] operations::canister::CompatError),
+
+    #[error("refusing an upgrade with {0} breaking interface change(s), see `--allow-breaking`")]
+    Breaking(usize),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn install(ctx: &Context, args: &InstallArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::owned(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let (wasm, candid) = module(ctx, args, environment).await?;
+
+    // Without an interface there is nothing to compare, let alone allow
+    if args.allow_breaking && candid.is_none() {
+        println!("{cid}: no interface to check, `--allow-breaking` has no effect");
+    }
+
+    // Init arguments
+    let arg = Encode!().map_err(anyhow::Error::from)?;
+
+    let installer = (ctx.ops.canister.install)(&agent);
+
+    match args.mode {
+        // A fresh canister has nothing running to protect
+        InstallMode::Install => {
+            installer
+                .install(&cid, (&args.mode).into(), &wasm, &arg)
+                .await?
+        }
+
+        // Replacing code happens on a stopped canister, which is started again either way
+        InstallMode::Reinstall | InstallMode::Upgrade => {
+            if let Some(path) = &candid {
+                check_interface(ctx, &agent, &cid, path, args.allow_breaking).await?;
+            }
+
+            // Checks are declared by canister name
+            let checks = match &args.canister {
+                args::Canister::Name(name) => {
+                    resolve::health(ctx, &[(name.to_owned(), cid)]).await?
+                }
+                _ => Default::default(),
+            };
+
+            let orchestrator = Orchestrator {
+                stop: (ctx.ops.canister.stop)(&agent),
+                start: (ctx.ops.canister.start)(&agent),
+                health: (ctx.ops.canister.health)(&agent),
+            };
+
+            orchestrator
+                .install(
+                    installer.as_ref(),
+                    &cid,
+                    Code {
+                        mode: (&args.mode).into(),
+                        wasm: &wasm,
+                        arg: &arg,
+                    },
+                    checks.get(&cid),
+                    &|phase| println!("{cid}: {phase}"),
+                )
+                .await?
+        }
+    }
+
+    println!("Installed code in canister {cid}");
+
+    Ok(())
+}
+
+/// The module to install and the interface to check: as given, or from the last build.
+async fn module(
+    ctx: &Context,
+    args: &InstallArgs,
+    environment: &str,
+) -> Result<(Vec<u8>, Option<PathBuf>), CommandError> {
+    if let Some(path) = &args.wasm {
+        return Ok((tokio::fs::read(path).await?, args.candid.to_owned()));
+    }
+
+    let at = resolve::locate(ctx, &args.canister, environment)
+        .await?
+        .ok_or_else(|| CommandError::WasmRequired(args.canister.to_string()))?;
+
+    let artifact = (ctx.ops.store.artifacts)(&at.dir)
+        .load()
+        .await?
+        .remove(&at.name)
+        .ok_or_else(|| CommandError::NotBuilt(at.name.to_owned()))?;
+
+    // Dependency IDs are linked in at build time
+    if artifact.environment != at.environment {
+        return Err(CommandError::OtherEnvironment {
+            name: at.name,
+            built: artifact.environment,
+            environment: at.environment,
+        });
+    }
+
+    let wasm = tokio::fs::read(at.dir.join(&artifact.wasm)).await?;
+
+    if hex::encode(Sha256::digest(&wasm)) != artifact.sha256 {
+        return Err(CommandError::Stale(at.name));
+    }
+
+    // Interfaces are only compared on upgrade
+    let candid = match args.mode {
+        InstallMode::Upgrade => args
+            .candid
+            .to_owned()
+            .or(artifact.candid.map(|path| at.dir.join(path))),
+        _ => None,
+    };
+
+    Ok((wasm, candid))
+}
+
+/// Compares the deployed interface with the new one, refusing breaking changes unless allowed.
+async fn check_interface(
+    ctx: &Context,
+    agent: &Agent,
+    cid: &Principal,
+    path: &Path,
+    allow_breaking: bool,
+) -> Result<(), CommandError> {
+    let new = tokio::fs::read_to_string(path).await?;
+
+    let Some(old) = (ctx.ops.canister.call)(agent).interface(cid).await? else {
+        println!("{cid}: no deployed interface published, skipping compatibility check");
+        return Ok(());
+    };
+
+    let changes = canister::breaking_changes(&old, &new)?;
+
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    print!("{}", canister::report(&changes));
+
+    match allow_breaking {
+        true => {
+            println!("{cid}: upgrading despite breaking interface changes");
+            Ok(())
+        }
+        false => Err(CommandError::Breaking(changes.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use anyhow::Error;
+    use candid::Principal;
+    use clap::Args;
+    use mockall::predicate::{always, eq, function};
+    use sha2::{Digest, Sha256};
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::{InstallArgs, InstallMode, install},
+        },
+        operations::{
+            self,
+            canister::{
+                self, CanisterInstallMode, MockCall, MockHealth, MockInstall, MockStart, MockStop,
+            },
+            store::{self, Artifact, Manifest, MockLoad, MockRecord, MockStore, Recipe},
+        },
+    };
+
+    #[test]
+    fn allow_breaking_without_candid() -> Result<(), Error> {
+        // The interface may come from the last build instead
+        InstallArgs::augment_args(clap::Command::new("install")).try_get_matches_from([
+            "install",
+            "backend",
+            "--mode",
+            "upgrade",
+            "--allow-breaking",
+        ])?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn install_in_project() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+
+        // Wasm module
+        let wasm = dir.path().join("install_in_project.wasm");
+        std::fs::write(&wasm, b"\0asm")?;
+
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                health: Box::new(|_| Arc::new(MockHealth::new())),
+                install: Box::new(|_| {
+                    let mut m = MockInstall::new();
+                    m.expect_install()
+                        .with(
+                            eq(Principal::anonymous()),
+                            eq(CanisterInstallMode::Upgrade(None)),
+                            function(|wasm: &[u8]| wasm == b"\0asm"),
+                            always(),
+                        )
+                        .once()
+                        .returning(|_, _, _, _| Ok(()));
+
+                    Arc::new(m)
+                }),
+                stop: Box::new(|_| {
+                    let mut m = MockStop::new();
+                    m.expect_stop().once().returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                start: Box::new(|_| {
+                    let mut m = MockStart::new();
+                    m.expect_start().once().returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .with(eq("ic"), eq("my-canister"))
+                        .returning(|_, _| Ok(Principal::anonymous()));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = InstallArgs {
+            canister: args::Canister::Name("my-canister".to_string()),
+            wasm: Some(wasm),
+            mode: InstallMode::Upgrade,
+            candid: None,
+            allow_breaking: false,
+            network: Some(args::Network::Name("ic".to_string())),
+            environment: None,
+        };
+
+        install(&ctx, &args).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn upgrade_refuses_breaking_interface() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+
+        let wasm = dir.path().join("backend.wasm");
+        std::fs::write(&wasm, b"\0asm")?;
+
+        let candid = dir.path().join("backend.did");
+        std::fs::write(&candid, "service : { greet : (nat) -> (text) query }")?;
+
+        // Mode (Global)
+        let mode = Mode::Global;
+
+        // Operations, nothing is stopped or installed
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                call: Box::new(|_| {
+                    let mut m = MockCall::new();
+                    m.expect_interface().once().returning(|_| {
+                        Ok(Some(
+                            "service : { greet : (text) -> (text) query }".to_string(),
+                        ))
+                    });
+                    Arc::new(m)
+                }),
+                install: Box::new(|_| Arc::new(MockInstall::new())),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = InstallArgs {
+            canister: args::Canister::Principal(Principal::anonymous()),
+            wasm: Some(wasm),
+            mode: InstallMode::Upgrade,
+            candid: Some(candid),
+            allow_breaking: false,
+            network: Some(args::Network::Url("https://icp-api.io".to_string())),
+            environment: None,
+        };
+
+        assert!(matches!(
+            install(&ctx, &args).await,
+            Err(super::CommandError::Breaking(1))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn install_last_build() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+
+        // Last build of the backend, changed since
+        let wasm = dir.path().join(".icp/build/backend.wasm");
+        std::fs::create_dir_all(wasm.parent().unwrap())?;
+        std::fs::write(&wasm, b"\0asm")?;
+
+        // Mode (Project)
+        let mode = Mode::Project(dir.path().to_owned());
+
+        // Operations
+        let ops = |sha256: String, environment: &'static str| operations::Initializers {
+            canister: canister::Initializers {
+                install: Box::new(|_| {
+                    let mut m = MockInstall::new();
+                    m.expect_install()
+                        .with(
+                            always(),
+                            eq(CanisterInstallMode::Install),
+                            function(|wasm: &[u8]| wasm == b"\0asm"),
+                            always(),
+                        )
+                        .returning(|_, _, _, _| Ok(()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                artifacts: Box::new(move |_| {
+                    let mut m = MockRecord::new();
+                    let sha256 = sha256.clone();
+                    m.expect_load().returning(move || {
+                        let artifact = Artifact {
+                            wasm: ".icp/build/backend.wasm".into(),
+                            sha256: sha256.clone(),
+                            candid: None,
+                            recipe: Recipe::Prebuilt {
+                                path: "backend.wasm".into(),
+                            },
+                            environment: environment.to_string(),
+                            built_at: "2025-01-01T00:00:00Z".to_string(),
+                        };
+                        Ok(BTreeMap::from([("backend".to_string(), artifact)]))
+                    });
+                    Arc::new(m)
+                }),
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .returning(|_, _| Ok(Principal::anonymous()));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let args = InstallArgs {
+            canister: args::Canister::Name("backend".to_string()),
+            wasm: None,
+            mode: InstallMode::Install,
+            candid: None,
+            allow_breaking: false,
+            network: Some(args::Network::Name("ic".to_string())),
+            environment: None,
+        };
+
+        let ctx = Context {
+            mode,
+            ops: ops(hex::encode(Sha256::digest(b"\0asm")), "ic"),
+        };
+
+        install(&ctx, &args).await?;
+
+        // Built with the dependency IDs of another environment
+        let ctx = Context {
+            mode: Mode::Project(dir.path().to_owned()),
+            ops: ops(hex::encode(Sha256::digest(b"\0asm")), "staging"),
+        };
+
+        assert!(matches!(
+            install(&ctx, &args).await,
+            Err(super::CommandError::OtherEnvironment { built, environment, .. })
+                if built == "staging" && environment == "ic"
+        ));
+
+        // The recorded hash no longer matches the module
+        let ctx = Context {
+            mode: Mode::Project(dir.path().to_owned()),
+            ops: ops(hex::encode(Sha256::digest(b"\0asm\x01")), "ic"),
+        };
+
+        assert!(matches!(
+            install(&ctx, &args).await,
+            Err(super::CommandError::Stale(name)) if name == "backend"
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,434 @@
+use std::collections::BTreeSet;
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{Validate, ValidateError},
+        resolve::{self, DEFAULT_ENVIRONMENT, ResolveError},
+    },
+    operations::{
+        canister::CanisterStatusType,
+        store::{Artifacts, ArtifactsError, ListError, LoadError},
+    },
+};
+
+#[derive(Args)]
+pub struct ListArgs {
+    // Only list canisters in this environment
+    #[arg(long)]
+    pub environment: Option<String>,
+
+    // Query the network for status and deployed module hashes
+    #[arg(long)]
+    pub status: bool,
+
+    // Print JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl Validate for ListArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // canisters are listed from the project
+        match mode {
+            Mode::Global => Err(ValidateError::Unexpected(anyhow::format_err!(
+                "Listing is only available in project mode."
+            ))),
+            Mode::Project(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("canisters can only be listed in project mode")]
+    ProjectRequired,
+
+    #[error(transparent)]
+    Load(#[from] LoadError),
+
+    #[error(transparent)]
+    List(#[from] ListError),
+
+    #[error("failed to read the build artifacts")]
+    Artifacts(#[from] ArtifactsError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// A canister in one environment, as printed with `--json`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Row {
+    pub canister: String,
+    pub environment: String,
+    pub id: Option<String>,
+    pub created: bool,
+
+    /// Deployed by someone else, with an ID fixed in the manifest.
+    pub remote: bool,
+
+    /// `running`, `stopping`, `stopped`, or `unknown` if it could not be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module_hash: Option<String>,
+
+    /// Hash of the locally built module, as recorded by the last build.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_hash: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches_build: Option<bool>,
+}
+
+pub async fn list(ctx: &Context, args: &ListArgs) -> Result<(), CommandError> {
+    let rows = rows(ctx, args).await?;
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&rows).map_err(anyhow::Error::from)?
+        );
+        return Ok(());
+    }
+
+    let width = |f: fn(&Row) -> usize| rows.iter().map(f).max().unwrap_or_default();
+    let (wc, we) = (width(|r| r.canister.len()), width(|r| r.environment.len()));
+
+    for r in &rows {
+        let mut line = format!(
+            "{:<wc$}  {:<we$}  {}",
+            r.canister,
+            r.environment,
+            r.id.as_deref().unwrap_or("not created")
+        );
+
+        if r.remote {
+            line += "  remote";
+        }
+
+        if let Some(status) = &r.status {
+            line += &format!("  {status}");
+        }
+
+        if let Some(hash) = &r.module_hash {
+            line += &format!("  0x{hash}");
+        }
+
+        match r.matches_build {
+            Some(true) => line += "  (matches build)",
+            Some(false) => line += "  (differs from build)",
+            None => {}
+        }
+
+        println!("{}", line.trim_end());
+    }
+
+    Ok(())
+}
+
+/// Every manifest canister in every environment selected.
+async fn rows(ctx: &Context, args: &ListArgs) -> Result<Vec<Row>, CommandError> {
+    let Mode::Project(dir) = &ctx.mode else {
+        return Err(CommandError::ProjectRequired);
+    };
+
+    let m = (ctx.ops.store.manifest)(dir).load().await?;
+    let ids = (ctx.ops.store.ids)(dir);
+
+    // Deployed modules are compared with the last build
+    let artifacts = match args.status {
+        true => (ctx.ops.store.artifacts)(dir).load().await?,
+        false => Artifacts::default(),
+    };
+
+    let environments: BTreeSet<String> = match &args.environment {
+        Some(environment) => BTreeSet::from([environment.to_owned()]),
+        None => m
+            .environments
+            .keys()
+            .cloned()
+            .chain([DEFAULT_ENVIRONMENT.to_string()])
+            .collect(),
+    };
+
+    let mut rows = vec![];
+
+    for environment in environments {
+        let deployed = ids.list(&environment).await?;
+
+        let any = m.canisters.keys().any(|name| {
+            deployed.contains_key(name) || matches!(m.remote(name, &environment), Some(Some(_)))
+        });
+
+        // Live details are read with the environment's own network, if there is anything to
+        // read, and an unreachable network leaves them unknown
+        let agent = match args.status && any {
+            true => {
+                let environment = Some(environment.to_owned());
+                match resolve::network(ctx, &None, &environment).await {
+                    Ok(network) => resolve::agent(&network).await.ok(),
+                    Err(_) => None,
+                }
+            }
+            false => None,
+        };
+
+        for name in m.canisters.keys() {
+            let remote = m.remote(name, &environment);
+            let cid = match remote {
+                Some(cid) => cid,
+                None => deployed.get(name).copied(),
+            };
+            let cid = cid.as_ref();
+
+            let mut row = Row {
+                canister: name.to_owned(),
+                environment: environment.to_owned(),
+                id: cid.map(|cid| cid.to_string()),
+                created: cid.is_some(),
+                remote: remote.is_some(),
+                status: None,
+                module_hash: None,
+                build_hash: None,
+                matches_build: None,
+            };
+
+            if let (Some(agent), Some(cid)) = (&agent, cid) {
+                // Only controllers can read the status, anyone the module hash
+                row.status = Some(
+                    match (ctx.ops.canister.status)(agent).status(cid).await {
+                        Ok(CanisterStatusType::Running) => "running",
+                        Ok(CanisterStatusType::Stopping) => "stopping",
+                        Ok(CanisterStatusType::Stopped) => "stopped",
+                        Err(_) => "unknown",
+                    }
+                    .to_string(),
+                );
+
+                row.module_hash = (ctx.ops.canister.info)(agent)
+                    .module_hash(cid)
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(hex::encode);
+
+                // Builds for another environment link other dependency IDs
+                row.build_hash = artifacts
+                    .get(name)
+                    .filter(|a| a.environment == environment)
+                    .map(|a| a.sha256.to_owned());
+
+                row.matches_build = match (&row.module_hash, &row.build_hash) {
+                    (Some(deployed), Some(built)) => Some(deployed == built),
+                    _ => None,
+                };
+            } else if args.status && cid.is_some() {
+                row.status = Some("unknown".to_string());
+            }
+
+            rows.push(row);
+        }
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::eq;
+    use sha2::{Digest, Sha256};
+
+    use super::rows;
+    use crate::{
+        commands::{Context, Mode, canister::ListArgs},
+        operations::{
+            self,
+            canister::{self, CanisterStatusType, MockInfo, MockStatus},
+            store::{
+                self, Artifact, Canister, Manifest, MockLoad, MockRecord, MockStore, Network,
+                Recipe,
+            },
+        },
+    };
+
+    #[tokio::test]
+    async fn list_compares_deployed_module_with_build() -> Result<(), Error> {
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        let cid = Principal::from_slice(&[1]);
+        let hash = Sha256::digest(b"\0asm").to_vec();
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                status: Box::new(|_| {
+                    let mut m = MockStatus::new();
+                    m.expect_status()
+                        .with(eq(Principal::from_slice(&[1])))
+                        .returning(|_| Ok(CanisterStatusType::Running));
+                    Arc::new(m)
+                }),
+                info: Box::new(move |_| {
+                    let mut m = MockInfo::new();
+                    let hash = hash.clone();
+                    m.expect_module_hash()
+                        .returning(move |_| Ok(Some(hash.clone())));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                // Last build of the backend
+                artifacts: Box::new(|_| {
+                    let mut m = MockRecord::new();
+                    m.expect_load().returning(|| {
+                        let artifact = Artifact {
+                            wasm: ".icp/build/backend.wasm".into(),
+                            sha256: hex::encode(Sha256::digest(b"\0asm")),
+                            candid: None,
+                            recipe: Recipe::Prebuilt {
+                                path: "backend.wasm".into(),
+                            },
+                            environment: "ic".to_string(),
+                            built_at: "2025-01-01T00:00:00Z".to_string(),
+                        };
+                        Ok(BTreeMap::from([("backend".to_string(), artifact)]))
+                    });
+                    Arc::new(m)
+                }),
+                ids: Box::new(move |_| {
+                    let mut m = MockStore::new();
+                    m.expect_list()
+                        .with(eq("ic"))
+                        .returning(move |_| Ok(BTreeMap::from([("backend".to_string(), cid)])));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| {
+                        Ok(Manifest {
+                            canisters: BTreeMap::from([
+                                ("backend".to_string(), Canister::default()),
+                                ("frontend".to_string(), Canister::default()),
+                            ]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = ListArgs {
+            environment: Some("ic".to_string()),
+            status: true,
+            json: true,
+        };
+
+        let rows = rows(&ctx, &args).await?;
+
+        assert_eq!(
+            rows.iter()
+                .map(|r| (r.canister.as_str(), r.created, r.matches_build))
+                .collect::<Vec<_>>(),
+            [("backend", true, Some(true)), ("frontend", false, None)]
+        );
+        assert_eq!(rows[0].status.as_deref(), Some("running"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_status_without_network() -> Result<(), Error> {
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            store: store::Initializers {
+                artifacts: Box::new(|_| {
+                    let mut m = MockRecord::new();
+                    m.expect_load().returning(|| Ok(BTreeMap::new()));
+                    Arc::new(m)
+                }),
+                // Deployed locally only
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_list().with(eq("local")).returning(|_| {
+                        Ok(BTreeMap::from([(
+                            "backend".to_string(),
+                            Principal::from_slice(&[1]),
+                        )]))
+                    });
+                    m.expect_list()
+                        .with(eq("staging"))
+                        .returning(|_| Ok(BTreeMap::new()));
+                    Arc::new(m)
+                }),
+                // Nothing listens locally, and staging has no network at all
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| {
+                        Ok(Manifest {
+                            canisters: BTreeMap::from([(
+                                "backend".to_string(),
+                                Canister::default(),
+                            )]),
+                            networks: BTreeMap::from([(
+                                "local".to_string(),
+                                Network {
+                                    url: "http://127.0.0.1:9".to_string(),
+                                },
+                            )]),
+                            environments: BTreeMap::from([(
+                                "staging".to_string(),
+                                Default::default(),
+                            )]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = ListArgs {
+            environment: None,
+            status: true,
+            json: true,
+        };
+
+        let rows = rows(&ctx, &args).await?;
+
+        assert_eq!(
+            rows.iter()
+                .map(|r| (r.environment.as_str(), r.status.as_deref()))
+                .collect::<Vec<_>>(),
+            [("local", Some("unknown")), ("staging", None)]
+        );
+
+        Ok(())
+    }
+}
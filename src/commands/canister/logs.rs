@@ -0,0 +1,443 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use candid::Principal;
+use clap::Args;
+use serde::Serialize;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{
+        self,
+        canister::{CanisterLogRecord, Logs},
+        store::ListError,
+    },
+};
+
+#[derive(Args)]
+pub struct LogsArgs {
+    // Canister, all canisters of the environment when omitted in project mode
+    pub canister: Option<args::Canister>,
+
+    // Keep polling and print new records as they arrive
+    #[arg(long)]
+    pub follow: bool,
+
+    // Polling interval in seconds
+    #[arg(long, requires = "follow", default_value_t = 2)]
+    pub interval: u64,
+
+    // Only records newer than the given age, e.g. `30s`, `15m`, `2h` or `1d`
+    #[arg(long, value_parser = parse_age)]
+    pub since: Option<Duration>,
+
+    // Only records containing the given text
+    #[arg(long)]
+    pub grep: Option<String>,
+
+    // Print one JSON object per record
+    #[arg(long)]
+    pub json: bool,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(LogsArgs, network: Option<args::Network>);
+impl_from_args!(LogsArgs, environment: Option<String>);
+impl_from_args!(LogsArgs, network: Option<args::Network>, environment: Option<String>);
+
+fn parse_age(v: &str) -> Result<Duration, String> {
+    let unit = v.trim_start_matches(|c: char| c.is_ascii_digit());
+    let n: u64 = v[..v.len() - unit.len()]
+        .parse()
+        .map_err(|_| format!("expected an age like `15m`, got `{v}`"))?;
+
+    let scale: u64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => return Err(format!("unknown unit `{unit}`, expected one of s, m, h, d")),
+    };
+
+    n.checked_mul(scale)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("age `{v}` is too large"))
+}
+
+impl Validate for LogsArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // a canister is required in global mode
+            |args: &LogsArgs, m: &Mode| {
+                (matches!(m, Mode::Global) && args.canister.is_none())
+                    .then_some("Please provide a canister ID in global mode.".to_string())
+            },
+            //
+            // names are only known in project mode
+            |args: &LogsArgs, m: &Mode| {
+                (matches!(m, Mode::Global)
+                    && matches!(&args.canister, Some(c) if !matches!(c, args::Canister::Principal(_))))
+                .then_some("Canisters can only be referenced by name in project mode.".to_string())
+            },
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("a canister is required in global mode")]
+    CanisterRequired,
+
+    #[error(transparent)]
+    List(#[from] ListError),
+
+    #[error("failed to fetch canister logs")]
+    Logs(#[from] operations::canister::LogsError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// A log record as printed with `--json`.
+#[derive(Serialize)]
+struct Entry<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    canister: Option<&'a str>,
+    id: String,
+    idx: u64,
+    timestamp_nanos: u64,
+    timestamp: String,
+    content: String,
+}
+
+pub async fn logs(ctx: &Context, args: &LogsArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+
+    // Canisters to read from, named when interleaving
+    let targets: BTreeMap<Option<String>, Principal> = match (&ctx.mode, &args.canister) {
+        (_, Some(canister)) => {
+            let cid = resolve::canister(ctx, canister, environment).await?;
+            BTreeMap::from([(None, cid)])
+        }
+
+        (Mode::Project(dir), None) => (ctx.ops.store.ids)(dir)
+            .list(environment)
+            .await?
+            .into_iter()
+            .map(|(name, cid)| (Some(name), cid))
+            .collect(),
+
+        (Mode::Global, None) => return Err(CommandError::CanisterRequired),
+    };
+
+    let agent = resolve::agent(&network).await?;
+    let reader = (ctx.ops.canister.logs)(&agent);
+
+    let since = args.since.map(|age| {
+        SystemTime::now()
+            .checked_sub(age)
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+
+    // Index of the last record printed per canister
+    let mut seen: BTreeMap<Principal, u64> = BTreeMap::new();
+
+    loop {
+        for row in poll(reader.as_ref(), &targets, &mut seen, since, args).await? {
+            println!("{row}");
+        }
+
+        if !args.follow {
+            break;
+        }
+
+        tokio::time::sleep(Duration::from_secs(args.interval)).await;
+    }
+
+    Ok(())
+}
+
+/// Rows for the records not yet seen, interleaving canisters by time.
+async fn poll(
+    reader: &dyn Logs,
+    targets: &BTreeMap<Option<String>, Principal>,
+    seen: &mut BTreeMap<Principal, u64>,
+    since: Option<u64>,
+    args: &LogsArgs,
+) -> Result<Vec<String>, CommandError> {
+    let mut batch = vec![];
+
+    for (name, cid) in targets {
+        let records = reader.fetch(cid).await?;
+
+        for r in records {
+            if seen.get(cid).is_some_and(|idx| r.idx <= *idx) {
+                continue;
+            }
+
+            seen.insert(*cid, r.idx);
+
+            if matches(&r, since, args.grep.as_deref()) {
+                batch.push((name.as_deref(), cid, r));
+            }
+        }
+    }
+
+    batch.sort_by_key(|(_, _, r)| r.timestamp_nanos);
+
+    let rows = batch
+        .into_iter()
+        .map(|(name, cid, r)| render(name, cid, &r, args.json))
+        .collect::<Result<_, _>>()?;
+
+    Ok(rows)
+}
+
+fn matches(r: &CanisterLogRecord, since: Option<u64>, grep: Option<&str>) -> bool {
+    since.is_none_or(|t| r.timestamp_nanos >= t)
+        && grep.is_none_or(|s| String::from_utf8_lossy(&r.content).contains(s))
+}
+
+fn render(
+    name: Option<&str>,
+    cid: &Principal,
+    r: &CanisterLogRecord,
+    json: bool,
+) -> Result<String, anyhow::Error> {
+    let timestamp =
+        OffsetDateTime::from_unix_timestamp_nanos(r.timestamp_nanos as i128)?.format(&Rfc3339)?;
+
+    let content = String::from_utf8_lossy(&r.content).to_string();
+
+    if json {
+        return Ok(serde_json::to_string(&Entry {
+            canister: name,
+            id: cid.to_string(),
+            idx: r.idx,
+            timestamp_nanos: r.timestamp_nanos,
+            timestamp,
+            content,
+        })?);
+    }
+
+    let line = format!("[{}. {timestamp}]: {content}", r.idx);
+
+    Ok(match name {
+        Some(name) => format!("{name} | {line}"),
+        None => line,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode,
+            canister::{LogsArgs, logs},
+        },
+        operations::{
+            self,
+            canister::{self, CanisterLogRecord, MockLogs},
+            store::{self, Manifest, MockLoad, MockStore},
+        },
+    };
+
+    use super::{matches, parse_age, poll, render};
+
+    fn record(idx: u64, timestamp_nanos: u64, content: &str) -> CanisterLogRecord {
+        CanisterLogRecord {
+            idx,
+            timestamp_nanos,
+            content: content.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn filter_and_render() {
+        let r = record(7, 1_700_000_000_000_000_000, "hello world");
+
+        assert!(matches(&r, None, None));
+        assert!(matches(&r, Some(1_700_000_000_000_000_000), Some("world")));
+        assert!(!matches(&r, Some(1_700_000_000_000_000_001), None));
+        assert!(!matches(&r, None, Some("bye")));
+
+        assert_eq!(
+            render(Some("backend"), &Principal::anonymous(), &r, false).unwrap(),
+            "backend | [7. 2023-11-14T22:13:20Z]: hello world"
+        );
+
+        assert_eq!(parse_age("15m"), Ok(std::time::Duration::from_secs(900)));
+        assert!(parse_age("15w").is_err());
+        assert!(parse_age("999999999999999999d").is_err());
+    }
+
+    #[tokio::test]
+    async fn logs_in_project_reads_every_canister() -> Result<(), Error> {
+        let backend = Principal::from_slice(&[1]);
+        let frontend = Principal::from_slice(&[2]);
+
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                logs: Box::new(move |_| {
+                    let mut m = MockLogs::new();
+
+                    m.expect_fetch()
+                        .with(eq(backend))
+                        .once()
+                        .returning(|_| Ok(vec![record(0, 1, "a"), record(1, 3, "c")]));
+
+                    m.expect_fetch()
+                        .with(eq(frontend))
+                        .once()
+                        .returning(|_| Ok(vec![record(0, 2, "b")]));
+
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(move |_| {
+                    let mut m = MockStore::new();
+                    m.expect_list().with(eq("ic")).once().returning(move |_| {
+                        Ok(BTreeMap::from([
+                            ("backend".to_string(), backend),
+                            ("frontend".to_string(), frontend),
+                        ]))
+                    });
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = LogsArgs {
+            canister: None,
+            follow: false,
+            interval: 2,
+            since: None,
+            grep: None,
+            json: false,
+            network: None,
+            environment: Some("ic".to_string()),
+        };
+
+        logs(&ctx, &args).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn poll_interleaves_canisters() -> Result<(), Error> {
+        let backend = Principal::from_slice(&[1]);
+        let frontend = Principal::from_slice(&[2]);
+
+        let t = 1_700_000_000_000_000_000;
+
+        let mut reader = MockLogs::new();
+
+        reader
+            .expect_fetch()
+            .with(eq(backend))
+            .returning(move |_| Ok(vec![record(0, t, "a"), record(1, t + 2_000_000_000, "c")]));
+
+        reader
+            .expect_fetch()
+            .with(eq(frontend))
+            .returning(move |_| Ok(vec![record(0, t + 1_000_000_000, "b")]));
+
+        let targets = BTreeMap::from([
+            (Some("backend".to_string()), backend),
+            (Some("frontend".to_string()), frontend),
+        ]);
+
+        let args = LogsArgs {
+            canister: None,
+            follow: false,
+            interval: 2,
+            since: None,
+            grep: None,
+            json: false,
+            network: None,
+            environment: Some("ic".to_string()),
+        };
+
+        let mut seen = BTreeMap::new();
+
+        assert_eq!(
+            poll(&reader, &targets, &mut seen, None, &args).await?,
+            vec![
+                "backend | [0. 2023-11-14T22:13:20Z]: a",
+                "frontend | [0. 2023-11-14T22:13:21Z]: b",
+                "backend | [1. 2023-11-14T22:13:22Z]: c",
+            ]
+        );
+
+        // Records already printed are skipped
+        assert!(
+            poll(&reader, &targets, &mut seen, None, &args)
+                .await?
+                .is_empty()
+        );
+
+        Ok(())
+    }
+}
@@ -1,11 +1,49 @@
 use clap::{Parser, Subcommand};
 
+pub mod batch;
+
+mod call;
+pub use call::*;
+
+mod delete;
+pub use delete::*;
+
+mod handover;
+pub use handover::*;
+
+pub mod id;
+
+mod info;
+pub use info::*;
+
+mod install;
+pub use install::*;
+
+mod list;
+pub use list::*;
+
+mod logs;
+pub use logs::*;
+
+mod restart;
+pub use restart::*;
+
+pub mod settings;
+
+pub mod snapshot;
+
 mod start;
 pub use start::*;
 
 mod stop;
 pub use stop::*;
 
+mod topup;
+pub use topup::*;
+
+mod verify;
+pub use verify::*;
+
 #[derive(Parser)]
 pub struct Command {
     #[command(subcommand)]
@@ -14,6 +52,19 @@ pub struct Command {
 
 #[derive(Subcommand)]
 pub enum Commands {
+    Call(CallArgs),
+    Delete(DeleteArgs),
+    Handover(HandoverArgs),
+    Id(id::Command),
+    Info(InfoArgs),
+    Install(InstallArgs),
+    List(ListArgs),
+    Logs(LogsArgs),
+    Restart(RestartArgs),
+    Settings(settings::Command),
+    Snapshot(snapshot::Command),
     Start(StartArgs),
     Stop(StopArgs),
+    TopUp(TopUpArgs),
+    Verify(VerifyArgs),
 }
@@ -0,0 +1,198 @@
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        canister::batch,
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::canister::Orchestrator,
+};
+
+#[derive(Args)]
+pub struct RestartArgs {
+    // Canisters to restart
+    #[arg(required_unless_present_any = ["all", "tag"])]
+    pub canisters: Vec<args::Canister>,
+
+    // Restart every canister in the environment
+    #[arg(long, conflicts_with_all = ["canisters", "tag"])]
+    pub all: bool,
+
+    // Restart the canisters carrying a tag, may be repeated
+    #[arg(long)]
+    pub tag: Vec<String>,
+
+    // Maximum number of canisters restarted at once
+    #[arg(long, default_value_t = 8)]
+    pub concurrency: usize,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(RestartArgs, canisters: Vec<args::Canister>, all: bool, tag: Vec<String>);
+impl_from_args!(RestartArgs, network: Option<args::Network>);
+impl_from_args!(RestartArgs, environment: Option<String>);
+impl_from_args!(RestartArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for RestartArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // at least one canister at a time
+            |args: &RestartArgs, _: &Mode| {
+                (args.concurrency == 0).then_some("Concurrency must be at least 1.")
+            },
+            //
+            // dummy case to shush linter
+            |_: &RestartArgs, _: &Mode| None,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::canister_principals_are_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("failed to restart {failed} of {total} canisters")]
+    Failed { failed: usize, total: usize },
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn restart(ctx: &Context, args: &RestartArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let targets =
+        resolve::canisters(ctx, &args.canisters, args.all, &args.tag, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let checks = resolve::health(ctx, &targets).await?;
+
+    let orchestrator = Orchestrator {
+        stop: (ctx.ops.canister.stop)(&agent),
+        start: (ctx.ops.canister.start)(&agent),
+        health: (ctx.ops.canister.health)(&agent),
+    };
+
+    let outcomes = batch::each(targets, args.concurrency, |cid| {
+        let (orchestrator, check) = (&orchestrator, checks.get(&cid));
+        async move {
+            orchestrator
+                .restart(&cid, check, &|phase| println!("{cid}: {phase}"))
+                .await
+        }
+    })
+    .await;
+
+    match batch::summarize("restarted", &outcomes) {
+        0 => Ok(()),
+        failed => Err(CommandError::Failed {
+            failed,
+            total: outcomes.len(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::{RestartArgs, restart},
+        },
+        operations::{
+            self,
+            canister::{self, MockHealth, MockStart, MockStop, StopError},
+        },
+    };
+
+    #[tokio::test]
+    async fn restart_starts_canister_after_failed_stop() -> Result<(), Error> {
+        // Mode (Global)
+        let mode = Mode::Global;
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                health: Box::new(|_| Arc::new(MockHealth::new())),
+                stop: Box::new(|_| {
+                    let mut m = MockStop::new();
+                    m.expect_stop()
+                        .with(eq(Principal::anonymous()))
+                        .once()
+                        .returning(|_| Err(StopError::Unexpected(anyhow::anyhow!("timed out"))));
+                    Arc::new(m)
+                }),
+                start: Box::new(|_| {
+                    let mut m = MockStart::new();
+                    m.expect_start()
+                        .with(eq(Principal::anonymous()))
+                        .once()
+                        .returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = RestartArgs {
+            canisters: vec![args::Canister::Principal(Principal::anonymous())],
+            all: false,
+            tag: vec![],
+            concurrency: 8,
+            network: Some(args::Network::Url("https://icp-api.io".to_string())),
+            environment: None,
+        };
+
+        // The restart is reported as failed, but the canister was started
+        assert!(matches!(
+            restart(&ctx, &args).await,
+            Err(super::CommandError::Failed {
+                failed: 1,
+                total: 1
+            })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use clap::{Parser, Subcommand};
+
+mod show;
+pub use show::*;
+
+mod sync;
+pub use sync::*;
+
+mod update;
+pub use update::*;
+
+#[derive(Parser)]
+pub struct Command {
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    Show(ShowArgs),
+    Sync(SyncArgs),
+    Update(Box<UpdateArgs>),
+}
@@ -0,0 +1,191 @@
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{
+        self,
+        canister::{DefiniteCanisterSettings, LogVisibility},
+    },
+};
+
+#[derive(Args)]
+pub struct ShowArgs {
+    pub canister: args::Canister,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(ShowArgs, canister: args::Canister);
+impl_from_args!(ShowArgs, network: Option<args::Network>);
+impl_from_args!(ShowArgs, environment: Option<String>);
+impl_from_args!(ShowArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for ShowArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("failed to get canister settings")]
+    Settings(#[from] operations::canister::SettingsError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn show(ctx: &Context, args: &ShowArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let settings = (ctx.ops.canister.settings)(&agent).get(&cid).await?;
+
+    print!("{}", describe(&settings));
+
+    Ok(())
+}
+
+pub fn describe(settings: &DefiniteCanisterSettings) -> String {
+    let controllers = settings
+        .controllers
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let environment_variables = settings
+        .environment_variables
+        .iter()
+        .map(|v| format!("{}={}", v.name, v.value))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    [
+        ("Controllers", controllers),
+        (
+            "Compute allocation",
+            settings.compute_allocation.to_string(),
+        ),
+        ("Memory allocation", settings.memory_allocation.to_string()),
+        (
+            "Freezing threshold",
+            settings.freezing_threshold.to_string(),
+        ),
+        (
+            "Reserved cycles limit",
+            settings.reserved_cycles_limit.to_string(),
+        ),
+        ("Wasm memory limit", settings.wasm_memory_limit.to_string()),
+        (
+            "Wasm memory threshold",
+            settings.wasm_memory_threshold.to_string(),
+        ),
+        (
+            "Log visibility",
+            describe_log_visibility(&settings.log_visibility),
+        ),
+        ("Environment variables", environment_variables),
+    ]
+    .into_iter()
+    .map(|(k, v)| format!("{:<23}{v}\n", format!("{k}:")))
+    .collect()
+}
+
+pub fn describe_log_visibility(v: &LogVisibility) -> String {
+    match v {
+        LogVisibility::Controllers => "controllers".to_string(),
+        LogVisibility::Public => "public".to_string(),
+        LogVisibility::AllowedViewers(vs) => format!(
+            "allowed viewers ({})",
+            vs.iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::settings::{ShowArgs, show},
+        },
+        operations::{
+            self,
+            canister::{self, DefiniteCanisterSettings, MockSettings},
+        },
+    };
+
+    #[tokio::test]
+    async fn show_in_global() -> Result<(), Error> {
+        // Mode (Global)
+        let mode = Mode::Global;
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                settings: Box::new(|_| {
+                    let mut m = MockSettings::new();
+                    m.expect_get()
+                        .with(eq(Principal::anonymous()))
+                        .once()
+                        .returning(|_| Ok(DefiniteCanisterSettings::default()));
+
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = ShowArgs {
+            canister: args::Canister::Principal(Principal::anonymous()),
+            network: Some(args::Network::Url("https://icp-api.io".to_string())),
+            environment: None,
+        };
+
+        show(&ctx, &args).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,649 @@
+use std::collections::BTreeMap;
+
+use candid::{Nat, Principal};
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode, args,
+        args::{Validate, ValidateError, validations},
+        canister::settings::describe_log_visibility,
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{
+        self,
+        canister::{
+            CanisterSettings, DefiniteCanisterSettings, EnvironmentVariable, LogVisibility,
+        },
+        store::{self, LoadError},
+    },
+};
+
+#[derive(Args)]
+pub struct SyncArgs {
+    // Canister to sync, all canisters with declared settings when omitted
+    pub canister: Option<String>,
+
+    // Only print the changes
+    #[arg(long)]
+    pub dry_run: bool,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(SyncArgs, network: Option<args::Network>);
+impl_from_args!(SyncArgs, environment: Option<String>);
+impl_from_args!(SyncArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for SyncArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // settings are declared in the project manifest
+            |_: &SyncArgs, m: &Mode| {
+                matches!(m, Mode::Global)
+                    .then_some("Declared settings are only available in project mode.".to_string())
+            },
+            //
+            // dummy case to shush linter
+            |_: &SyncArgs, _: &Mode| None,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_network_name_is_required_in_project_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("declared settings are only available in project mode")]
+    ProjectRequired,
+
+    #[error("no settings declared for canister `{canister}` in environment `{environment}`")]
+    NotDeclared {
+        environment: String,
+        canister: String,
+    },
+
+    #[error("failed to load project manifest")]
+    Load(#[from] LoadError),
+
+    #[error("failed to sync canister settings")]
+    Settings(#[from] operations::canister::SettingsError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// A setting whose declared value differs from the one in effect.
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub setting: &'static str,
+    pub current: String,
+    pub declared: String,
+}
+
+pub async fn sync(ctx: &Context, args: &SyncArgs) -> Result<(), CommandError> {
+    let Mode::Project(dir) = &ctx.mode else {
+        return Err(CommandError::ProjectRequired);
+    };
+
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+
+    let m = (ctx.ops.store.manifest)(dir).load().await?;
+
+    let declared = m
+        .environments
+        .get(environment)
+        .map(|env| env.settings.to_owned())
+        .unwrap_or_default();
+
+    let declared: Vec<(String, store::Settings)> = match &args.canister {
+        Some(name) => {
+            let settings =
+                declared
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| CommandError::NotDeclared {
+                        environment: environment.to_owned(),
+                        canister: name.to_owned(),
+                    })?;
+
+            vec![(name.to_owned(), settings)]
+        }
+
+        None => declared.into_iter().collect(),
+    };
+
+    let agent = resolve::agent(&network).await?;
+    let settings = (ctx.ops.canister.settings)(&agent);
+    let ids = (ctx.ops.store.ids)(dir);
+
+    for (name, declared) in declared {
+        // Remote canisters resolve to their declared ID
+        let cid = resolve::lookup(ids.as_ref(), &m, &name, environment).await?;
+
+        let current = settings.get(&cid).await?;
+        let (changes, update) = diff(&declared, &current);
+
+        if changes.is_empty() {
+            println!("{name} ({cid}): up to date");
+            continue;
+        }
+
+        println!("{name} ({cid}):");
+        for c in &changes {
+            println!("  {}: {} -> {}", c.setting, c.current, c.declared);
+        }
+
+        if !args.dry_run {
+            settings.update(&cid, update).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Compares declared settings to the ones in effect, returning the differences
+/// and an update that only touches the differing settings.
+pub fn diff(
+    declared: &store::Settings,
+    current: &DefiniteCanisterSettings,
+) -> (Vec<Change>, CanisterSettings) {
+    let mut changes = vec![];
+    let mut update = CanisterSettings::default();
+
+    // Controllers (order is irrelevant)
+    if let Some(controllers) = &declared.controllers {
+        let sorted = |ps: &[Principal]| {
+            let mut ps = ps.to_vec();
+            ps.sort();
+            ps.dedup();
+            ps
+        };
+
+        if sorted(controllers) != sorted(&current.controllers) {
+            let join = |ps: &[Principal]| {
+                ps.iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+
+            changes.push(Change {
+                setting: "controllers",
+                current: join(&current.controllers),
+                declared: join(controllers),
+            });
+
+            update.controllers = Some(controllers.to_owned());
+        }
+    }
+
+    // Numeric settings
+    for (setting, declared, current, field) in [
+        (
+            "compute_allocation",
+            declared.compute_allocation.map(Nat::from),
+            &current.compute_allocation,
+            &mut update.compute_allocation,
+        ),
+        (
+            "memory_allocation",
+            declared.memory_allocation.map(Nat::from),
+            &current.memory_allocation,
+            &mut update.memory_allocation,
+        ),
+        (
+            "freezing_threshold",
+            declared.freezing_threshold.map(Nat::from),
+            &current.freezing_threshold,
+            &mut update.freezing_threshold,
+        ),
+        (
+            "reserved_cycles_limit",
+            declared.reserved_cycles_limit.map(Nat::from),
+            &current.reserved_cycles_limit,
+            &mut update.reserved_cycles_limit,
+        ),
+        (
+            "wasm_memory_limit",
+            declared.wasm_memory_limit.map(Nat::from),
+            &current.wasm_memory_limit,
+            &mut update.wasm_memory_limit,
+        ),
+    ] {
+        if let Some(declared) = declared.filter(|v| v != current) {
+            changes.push(Change {
+                setting,
+                current: current.to_string(),
+                declared: declared.to_string(),
+            });
+
+            *field = Some(declared);
+        }
+    }
+
+    // Log visibility
+    if let Some(v) = &declared.log_visibility {
+        let declared = match v {
+            store::LogVisibility::Controllers => LogVisibility::Controllers,
+            store::LogVisibility::Public => LogVisibility::Public,
+            store::LogVisibility::AllowedViewers(vs) => {
+                LogVisibility::AllowedViewers(vs.to_owned())
+            }
+        };
+
+        if declared != current.log_visibility {
+            changes.push(Change {
+                setting: "log_visibility",
+                current: describe_log_visibility(&current.log_visibility),
+                declared: describe_log_visibility(&declared),
+            });
+
+            update.log_visibility = Some(declared);
+        }
+    }
+
+    // Environment variables (order is irrelevant)
+    if let Some(vars) = &declared.environment_variables {
+        let current: BTreeMap<String, String> = current
+            .environment_variables
+            .iter()
+            .map(|v| (v.name.to_owned(), v.value.to_owned()))
+            .collect();
+
+        if vars != &current {
+            let join = |vs: &BTreeMap<String, String>| {
+                vs.iter()
+                    .map(|(k, v)| format!("{k}={v}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+
+            changes.push(Change {
+                setting: "environment_variables",
+                current: join(&current),
+                declared: join(vars),
+            });
+
+            update.environment_variables = Some(
+                vars.iter()
+                    .map(|(name, value)| EnvironmentVariable {
+                        name: name.to_owned(),
+                        value: value.to_owned(),
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    (changes, update)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use anyhow::Error;
+    use candid::{Nat, Principal};
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode,
+            canister::settings::{Change, SyncArgs, diff, sync},
+        },
+        operations::{
+            self,
+            canister::{
+                self, CanisterSettings, DefiniteCanisterSettings, EnvironmentVariable,
+                LogVisibility, MockSettings,
+            },
+            store::{self, Canister, Environment, Manifest, MockLoad, MockStore, Settings},
+        },
+    };
+
+    #[tokio::test]
+    async fn sync_in_project() -> Result<(), Error> {
+        let alice = Principal::from_slice(&[1]);
+
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                settings: Box::new(move |_| {
+                    let mut m = MockSettings::new();
+
+                    m.expect_get()
+                        .with(eq(Principal::anonymous()))
+                        .once()
+                        .returning(move |_| {
+                            Ok(DefiniteCanisterSettings {
+                                controllers: vec![alice],
+                                freezing_threshold: Nat::from(2_592_000u64),
+                                ..Default::default()
+                            })
+                        });
+
+                    // Only the drifted setting is applied
+                    m.expect_update()
+                        .with(
+                            eq(Principal::anonymous()),
+                            eq(CanisterSettings {
+                                freezing_threshold: Some(Nat::from(7_776_000u64)),
+                                ..Default::default()
+                            }),
+                        )
+                        .once()
+                        .returning(|_, _| Ok(()));
+
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .with(eq("prod"), eq("backend"))
+                        .returning(|_, _| Ok(Principal::anonymous()));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(move |_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(move || {
+                        Ok(Manifest {
+                            environments: BTreeMap::from([(
+                                "prod".to_string(),
+                                Environment {
+                                    network: Some("ic".to_string()),
+                                    settings: BTreeMap::from([(
+                                        "backend".to_string(),
+                                        Settings {
+                                            controllers: Some(vec![alice]),
+                                            freezing_threshold: Some(7_776_000),
+                                            ..Default::default()
+                                        },
+                                    )]),
+                                },
+                            )]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = SyncArgs {
+            canister: None,
+            dry_run: false,
+            network: None,
+            environment: Some("prod".to_string()),
+        };
+
+        sync(&ctx, &args).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sync_remote_canister() -> Result<(), Error> {
+        let ledger = Principal::from_slice(&[2]);
+
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                settings: Box::new(move |_| {
+                    let mut m = MockSettings::new();
+                    m.expect_get().with(eq(ledger)).once().returning(|_| {
+                        Ok(DefiniteCanisterSettings {
+                            freezing_threshold: Nat::from(7_776_000u64),
+                            ..Default::default()
+                        })
+                    });
+                    m.expect_update().never();
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup().never();
+                    Arc::new(m)
+                }),
+                manifest: Box::new(move |_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(move || {
+                        Ok(Manifest {
+                            canisters: BTreeMap::from([(
+                                "ledger".to_string(),
+                                Canister {
+                                    remote: Some(BTreeMap::from([("ic".to_string(), ledger)])),
+                                    ..Default::default()
+                                },
+                            )]),
+                            environments: BTreeMap::from([(
+                                "prod".to_string(),
+                                Environment {
+                                    network: Some("ic".to_string()),
+                                    settings: BTreeMap::from([(
+                                        "ledger".to_string(),
+                                        Settings {
+                                            freezing_threshold: Some(7_776_000),
+                                            ..Default::default()
+                                        },
+                                    )]),
+                                },
+                            )]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = SyncArgs {
+            canister: Some("ledger".to_string()),
+            dry_run: false,
+            network: None,
+            environment: Some("prod".to_string()),
+        };
+
+        sync(&ctx, &args).await?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_without_changes() {
+        let alice = Principal::from_slice(&[1]);
+
+        let current = DefiniteCanisterSettings {
+            controllers: vec![alice],
+            freezing_threshold: Nat::from(2_592_000u64),
+            ..Default::default()
+        };
+
+        // Nothing declared
+        assert_eq!(
+            diff(&Settings::default(), &current),
+            (vec![], CanisterSettings::default())
+        );
+
+        // Declared as in effect
+        let declared = Settings {
+            controllers: Some(vec![alice]),
+            freezing_threshold: Some(2_592_000),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            diff(&declared, &current),
+            (vec![], CanisterSettings::default())
+        );
+    }
+
+    #[test]
+    fn diff_controllers() {
+        let alice = Principal::from_slice(&[1]);
+        let bob = Principal::from_slice(&[2]);
+
+        let current = DefiniteCanisterSettings {
+            controllers: vec![alice, bob],
+            ..Default::default()
+        };
+
+        // Order and duplicates are irrelevant
+        let declared = Settings {
+            controllers: Some(vec![bob, alice, bob]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            diff(&declared, &current),
+            (vec![], CanisterSettings::default())
+        );
+
+        // Replaced as declared
+        let declared = Settings {
+            controllers: Some(vec![bob]),
+            ..Default::default()
+        };
+
+        let (changes, update) = diff(&declared, &current);
+
+        assert_eq!(
+            changes,
+            vec![Change {
+                setting: "controllers",
+                current: format!("{alice}, {bob}"),
+                declared: bob.to_string(),
+            }]
+        );
+        assert_eq!(update.controllers, Some(vec![bob]));
+    }
+
+    #[test]
+    fn diff_log_visibility() {
+        let alice = Principal::from_slice(&[1]);
+
+        let current = DefiniteCanisterSettings {
+            log_visibility: LogVisibility::Controllers,
+            ..Default::default()
+        };
+
+        let declared = Settings {
+            log_visibility: Some(store::LogVisibility::Controllers),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            diff(&declared, &current),
+            (vec![], CanisterSettings::default())
+        );
+
+        let declared = Settings {
+            log_visibility: Some(store::LogVisibility::AllowedViewers(vec![alice])),
+            ..Default::default()
+        };
+
+        let (changes, update) = diff(&declared, &current);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].setting, "log_visibility");
+        assert_eq!(
+            update,
+            CanisterSettings {
+                log_visibility: Some(LogVisibility::AllowedViewers(vec![alice])),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn diff_environment_variables() {
+        let var = |name: &str, value: &str| EnvironmentVariable {
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+
+        let current = DefiniteCanisterSettings {
+            environment_variables: vec![var("B", "2"), var("A", "1")],
+            ..Default::default()
+        };
+
+        // Order is irrelevant
+        let declared = Settings {
+            environment_variables: Some(BTreeMap::from([
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "2".to_string()),
+            ])),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            diff(&declared, &current),
+            (vec![], CanisterSettings::default())
+        );
+
+        // The declared map replaces the one in effect
+        let declared = Settings {
+            environment_variables: Some(BTreeMap::from([("A".to_string(), "3".to_string())])),
+            ..Default::default()
+        };
+
+        let (changes, update) = diff(&declared, &current);
+
+        assert_eq!(
+            changes,
+            vec![Change {
+                setting: "environment_variables",
+                current: "A=1, B=2".to_string(),
+                declared: "A=3".to_string(),
+            }]
+        );
+        assert_eq!(update.environment_variables, Some(vec![var("A", "3")]));
+    }
+}
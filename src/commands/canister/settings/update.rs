@@ -0,0 +1,430 @@
+use candid::{Nat, Principal};
+use clap::{Args, ValueEnum};
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{
+        self,
+        canister::{
+            CanisterSettings, DefiniteCanisterSettings, EnvironmentVariable, LogVisibility,
+        },
+    },
+};
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum LogVisibilityArg {
+    Controllers,
+    Public,
+}
+
+#[derive(Args)]
+pub struct UpdateArgs {
+    pub canister: args::Canister,
+
+    // Controllers, principals or canisters such as `staging:frontend`
+    #[arg(long, conflicts_with = "set_controller")]
+    pub add_controller: Vec<args::Canister>,
+
+    #[arg(long, conflicts_with = "set_controller")]
+    pub remove_controller: Vec<args::Canister>,
+
+    #[arg(long)]
+    pub set_controller: Vec<args::Canister>,
+
+    // Allocations
+    #[arg(long)]
+    pub compute_allocation: Option<u8>,
+
+    #[arg(long)]
+    pub memory_allocation: Option<u64>,
+
+    // Cycles
+    #[arg(long)]
+    pub freezing_threshold: Option<u64>,
+
+    #[arg(long)]
+    pub reserved_cycles_limit: Option<u128>,
+
+    // Memory
+    #[arg(long)]
+    pub wasm_memory_limit: Option<u64>,
+
+    // Logs
+    #[arg(long, value_enum, conflicts_with = "log_viewer")]
+    pub log_visibility: Option<LogVisibilityArg>,
+
+    #[arg(long)]
+    pub log_viewer: Vec<Principal>,
+
+    // Environment variables
+    #[arg(long, value_parser = parse_environment_variable)]
+    pub set_env: Vec<(String, String)>,
+
+    #[arg(long)]
+    pub unset_env: Vec<String>,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(UpdateArgs, canister: args::Canister);
+impl_from_args!(UpdateArgs, network: Option<args::Network>);
+impl_from_args!(UpdateArgs, environment: Option<String>);
+impl_from_args!(UpdateArgs, network: Option<args::Network>, environment: Option<String>);
+
+fn parse_environment_variable(v: &str) -> Result<(String, String), String> {
+    v.split_once('=')
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .ok_or_else(|| format!("expected `NAME=VALUE`, got `{v}`"))
+}
+
+/// Controller changes, with canister references resolved to principals.
+#[derive(Debug, Default)]
+pub struct Controllers {
+    pub add: Vec<Principal>,
+    pub remove: Vec<Principal>,
+    pub set: Vec<Principal>,
+}
+
+impl UpdateArgs {
+    /// Whether the update is relative to the settings currently in effect.
+    fn is_relative(&self) -> bool {
+        !self.add_controller.is_empty()
+            || !self.remove_controller.is_empty()
+            || !self.set_env.is_empty()
+            || !self.unset_env.is_empty()
+    }
+
+    /// Settings to apply, given the settings currently in effect for relative updates.
+    pub fn settings(
+        &self,
+        current: Option<&DefiniteCanisterSettings>,
+        controllers: &Controllers,
+    ) -> CanisterSettings {
+        let controllers = match (&controllers.set[..], current) {
+            (set @ [_, ..], _) => Some(set.to_vec()),
+
+            (_, Some(current)) if !controllers.add.is_empty() || !controllers.remove.is_empty() => {
+                let mut out: Vec<Principal> = current
+                    .controllers
+                    .iter()
+                    .filter(|p| !controllers.remove.contains(p))
+                    .copied()
+                    .collect();
+
+                for p in &controllers.add {
+                    if !out.contains(p) {
+                        out.push(*p);
+                    }
+                }
+
+                Some(out)
+            }
+
+            _ => None,
+        };
+
+        let log_visibility = match (&self.log_visibility, &self.log_viewer[..]) {
+            (Some(LogVisibilityArg::Controllers), _) => Some(LogVisibility::Controllers),
+            (Some(LogVisibilityArg::Public), _) => Some(LogVisibility::Public),
+            (None, vs @ [_, ..]) => Some(LogVisibility::AllowedViewers(vs.to_vec())),
+            (None, []) => None,
+        };
+
+        let environment_variables = match current {
+            Some(current) if !self.set_env.is_empty() || !self.unset_env.is_empty() => {
+                let mut out: Vec<EnvironmentVariable> = current
+                    .environment_variables
+                    .iter()
+                    .filter(|v| !self.unset_env.contains(&v.name))
+                    .filter(|v| !self.set_env.iter().any(|(name, _)| name == &v.name))
+                    .cloned()
+                    .collect();
+
+                out.extend(
+                    self.set_env
+                        .iter()
+                        .map(|(name, value)| EnvironmentVariable {
+                            name: name.to_owned(),
+                            value: value.to_owned(),
+                        }),
+                );
+
+                Some(out)
+            }
+
+            _ => None,
+        };
+
+        CanisterSettings {
+            controllers,
+            compute_allocation: self.compute_allocation.map(Nat::from),
+            memory_allocation: self.memory_allocation.map(Nat::from),
+            freezing_threshold: self.freezing_threshold.map(Nat::from),
+            reserved_cycles_limit: self.reserved_cycles_limit.map(Nat::from),
+            log_visibility,
+            wasm_memory_limit: self.wasm_memory_limit.map(Nat::from),
+            environment_variables,
+            ..Default::default()
+        }
+    }
+}
+
+impl Validate for UpdateArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // something has to change
+            |args: &UpdateArgs, _: &Mode| {
+                (!args.is_relative()
+                    && args.set_controller.is_empty()
+                    && args.settings(None, &Controllers::default()) == CanisterSettings::default())
+                .then_some("Please provide at least one setting to update.".to_string())
+            },
+            //
+            // controllers are referenced by principal in global mode
+            |args: &UpdateArgs, m: &Mode| {
+                (matches!(m, Mode::Global)
+                    && args
+                        .add_controller
+                        .iter()
+                        .chain(&args.remove_controller)
+                        .chain(&args.set_controller)
+                        .any(|c| !matches!(c, args::Canister::Principal(_))))
+                .then_some("Please provide controller principals in global mode.".to_string())
+            },
+            //
+            // compute allocation is a percentage
+            |args: &UpdateArgs, _: &Mode| {
+                args.compute_allocation
+                    .is_some_and(|v| v > 100)
+                    .then_some("Compute allocation must be between 0 and 100.".to_string())
+            },
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("failed to update canister settings")]
+    Settings(#[from] operations::canister::SettingsError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn update(ctx: &Context, args: &UpdateArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    // Controllers may be canisters of other environments or projects
+    let mut controllers = Controllers::default();
+    for (refs, out) in [
+        (&args.add_controller, &mut controllers.add),
+        (&args.remove_controller, &mut controllers.remove),
+        (&args.set_controller, &mut controllers.set),
+    ] {
+        for c in refs {
+            out.push(resolve::canister(ctx, c, environment).await?);
+        }
+    }
+
+    let settings = (ctx.ops.canister.settings)(&agent);
+
+    // Relative updates are applied on top of the settings currently in effect
+    let current = match args.is_relative() {
+        true => Some(settings.get(&cid).await?),
+        false => None,
+    };
+
+    settings
+        .update(&cid, args.settings(current.as_ref(), &controllers))
+        .await?;
+
+    println!("Updated settings of canister {cid}");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use anyhow::Error;
+    use candid::{Nat, Principal};
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::settings::{UpdateArgs, update},
+        },
+        operations::{
+            self,
+            canister::{
+                self, CanisterSettings, DefiniteCanisterSettings, EnvironmentVariable, MockSettings,
+            },
+            store::{self, Environment, Manifest, MockLoad, MockStore},
+        },
+    };
+
+    #[tokio::test]
+    async fn update_in_project() -> Result<(), Error> {
+        let alice = Principal::from_slice(&[1]);
+        let bob = Principal::from_slice(&[2]);
+        let carol = Principal::from_slice(&[3]);
+
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                settings: Box::new(move |_| {
+                    let mut m = MockSettings::new();
+
+                    m.expect_get()
+                        .with(eq(Principal::anonymous()))
+                        .once()
+                        .returning(move |_| {
+                            Ok(DefiniteCanisterSettings {
+                                controllers: vec![alice, bob],
+                                environment_variables: vec![
+                                    EnvironmentVariable {
+                                        name: "A".to_string(),
+                                        value: "1".to_string(),
+                                    },
+                                    EnvironmentVariable {
+                                        name: "B".to_string(),
+                                        value: "2".to_string(),
+                                    },
+                                ],
+                                ..Default::default()
+                            })
+                        });
+
+                    m.expect_update()
+                        .with(
+                            eq(Principal::anonymous()),
+                            eq(CanisterSettings {
+                                controllers: Some(vec![alice, carol]),
+                                freezing_threshold: Some(Nat::from(86_400u64)),
+                                environment_variables: Some(vec![
+                                    EnvironmentVariable {
+                                        name: "A".to_string(),
+                                        value: "1".to_string(),
+                                    },
+                                    EnvironmentVariable {
+                                        name: "B".to_string(),
+                                        value: "3".to_string(),
+                                    },
+                                ]),
+                                ..Default::default()
+                            }),
+                        )
+                        .once()
+                        .returning(|_, _| Ok(()));
+
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(move |_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .with(eq("prod"), eq("my-canister"))
+                        .returning(|_, _| Ok(Principal::anonymous()));
+                    m.expect_lookup()
+                        .with(eq("staging"), eq("frontend"))
+                        .returning(move |_, _| Ok(carol));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| {
+                        Ok(Manifest {
+                            canisters: BTreeMap::from([(
+                                "frontend".to_string(),
+                                Default::default(),
+                            )]),
+                            environments: BTreeMap::from([
+                                ("staging".to_string(), Default::default()),
+                                (
+                                    "prod".to_string(),
+                                    Environment {
+                                        network: Some("ic".to_string()),
+                                        ..Default::default()
+                                    },
+                                ),
+                            ]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = UpdateArgs {
+            canister: args::Canister::Name("my-canister".to_string()),
+            add_controller: vec!["staging:frontend".parse().map_err(Error::msg)?],
+            remove_controller: vec![args::Canister::Principal(bob)],
+            set_controller: vec![],
+            compute_allocation: None,
+            memory_allocation: None,
+            freezing_threshold: Some(86_400),
+            reserved_cycles_limit: None,
+            wasm_memory_limit: None,
+            log_visibility: None,
+            log_viewer: vec![],
+            set_env: vec![("B".to_string(), "3".to_string())],
+            unset_env: vec![],
+            network: None,
+            environment: Some("prod".to_string()),
+        };
+
+        update(&ctx, &args).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,505 @@
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{self, canister::CanisterStatusType, store::Labeled},
+};
+
+#[derive(Args)]
+pub struct CreateArgs {
+    pub canister: args::Canister,
+
+    // Label to refer to the snapshot by
+    #[arg(long)]
+    pub label: Option<String>,
+
+    // ID of an existing snapshot to replace
+    #[arg(long)]
+    pub replace: Option<String>,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(CreateArgs, canister: args::Canister);
+impl_from_args!(CreateArgs, network: Option<args::Network>);
+impl_from_args!(CreateArgs, environment: Option<String>);
+impl_from_args!(CreateArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for CreateArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // labels are recorded in the project
+            |args: &CreateArgs, m: &Mode| {
+                (matches!(m, Mode::Global) && args.label.is_some())
+                    .then_some("Snapshot labels are only available in project mode.".to_string())
+            },
+            //
+            // snapshot IDs are hex
+            |args: &CreateArgs, _: &Mode| {
+                args.replace
+                    .as_ref()
+                    .filter(|id| hex::decode(id).is_err())
+                    .map(|id| format!("Invalid snapshot ID `{id}`, expected hex."))
+            },
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("invalid snapshot ID `{0}`, expected hex")]
+    InvalidId(String),
+
+    #[error("failed to read canister status")]
+    Status(#[from] operations::canister::StatusError),
+
+    #[error("failed to stop canister")]
+    Stop(#[from] operations::canister::StopError),
+
+    #[error("failed to create snapshot")]
+    Snapshot(#[from] operations::canister::SnapshotError),
+
+    #[error("failed to restart canister")]
+    Start(#[from] operations::canister::StartError),
+
+    #[error("failed to record snapshot")]
+    Labels(#[from] operations::store::LabelError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn create(ctx: &Context, args: &CreateArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let replace = args
+        .replace
+        .as_ref()
+        .map(|id| hex::decode(id).map_err(|_| CommandError::InvalidId(id.to_owned())))
+        .transpose()?;
+    let replaced = replace.as_ref().map(hex::encode);
+
+    // Refuse a taken label before touching the canister
+    let labels = match &ctx.mode {
+        Mode::Project(dir) => Some((ctx.ops.store.labels)(dir)),
+        Mode::Global => None,
+    };
+
+    if let (Some(labels), Some(label)) = (&labels, &args.label)
+        && labels
+            .list(&cid)
+            .await?
+            .iter()
+            // The snapshot being replaced gives up its label
+            .any(|s| s.label.as_ref() == Some(label) && Some(&s.id) != replaced.as_ref())
+    {
+        return Err(operations::store::LabelError::Taken {
+            cid,
+            label: label.to_owned(),
+        }
+        .into());
+    }
+
+    let running = (ctx.ops.canister.status)(&agent).status(&cid).await?;
+
+    // The canister is stopped for the snapshot and restarted regardless of the outcome,
+    // unless it was not running to begin with
+    (ctx.ops.canister.stop)(&agent).stop(&cid).await?;
+
+    let snapshot = (ctx.ops.canister.snapshot)(&agent)
+        .create(&cid, replace.to_owned())
+        .await;
+
+    let started = match running {
+        CanisterStatusType::Running => (ctx.ops.canister.start)(&agent).start(&cid).await,
+        _ => Ok(()),
+    };
+
+    let snapshot = match snapshot {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            started?;
+            return Err(err.into());
+        }
+    };
+
+    let id = hex::encode(&snapshot.id);
+
+    if let Some(labels) = &labels {
+        if let Some(replaced) = &replaced {
+            labels.remove(&cid, replaced).await?;
+        }
+
+        labels
+            .record(
+                &cid,
+                Labeled {
+                    id: id.to_owned(),
+                    label: args.label.to_owned(),
+                    taken_at: snapshot.taken_at_timestamp,
+                },
+            )
+            .await?;
+    }
+
+    println!("Created snapshot {id} of canister {cid}");
+
+    // The snapshot is kept, and recorded, whether or not the canister starts
+    started?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::snapshot::{CreateArgs, create, create::CommandError},
+        },
+        operations::{
+            self,
+            canister::{
+                self, CanisterStatusType, MockSnapshots, MockStart, MockStatus, MockStop, Snapshot,
+                StartError,
+            },
+            store::{self, Environment, Labeled, Manifest, MockLabels, MockLoad, MockStore},
+        },
+    };
+
+    #[tokio::test]
+    async fn create_in_project_records_label() -> Result<(), Error> {
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                status: Box::new(|_| {
+                    let mut m = MockStatus::new();
+                    m.expect_status()
+                        .returning(|_| Ok(CanisterStatusType::Running));
+                    Arc::new(m)
+                }),
+                stop: Box::new(|_| {
+                    let mut m = MockStop::new();
+                    m.expect_stop()
+                        .with(eq(Principal::anonymous()))
+                        .once()
+                        .returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                snapshot: Box::new(|_| {
+                    let mut m = MockSnapshots::new();
+                    m.expect_create()
+                        .with(eq(Principal::anonymous()), eq(None))
+                        .once()
+                        .returning(|_, _| {
+                            Ok(Snapshot {
+                                id: vec![0xab, 0xcd],
+                                taken_at_timestamp: 42,
+                                total_size: 1024,
+                            })
+                        });
+                    Arc::new(m)
+                }),
+                start: Box::new(|_| {
+                    let mut m = MockStart::new();
+                    m.expect_start()
+                        .with(eq(Principal::anonymous()))
+                        .once()
+                        .returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .with(eq("prod"), eq("backend"))
+                        .returning(|_, _| Ok(Principal::anonymous()));
+                    Arc::new(m)
+                }),
+                labels: Box::new(|_| {
+                    let mut m = MockLabels::new();
+                    m.expect_list().returning(|_| Ok(vec![]));
+                    m.expect_record()
+                        .with(
+                            eq(Principal::anonymous()),
+                            eq(Labeled {
+                                id: "abcd".to_string(),
+                                label: Some("before-migration".to_string()),
+                                taken_at: 42,
+                            }),
+                        )
+                        .once()
+                        .returning(|_, _| Ok(()));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    // Prod is deployed to the IC
+                    m.expect_load().returning(|| {
+                        Ok(Manifest {
+                            environments: BTreeMap::from([(
+                                "prod".to_string(),
+                                Environment {
+                                    network: Some("ic".to_string()),
+                                    ..Default::default()
+                                },
+                            )]),
+                            ..Default::default()
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = CreateArgs {
+            canister: args::Canister::Name("backend".to_string()),
+            label: Some("before-migration".to_string()),
+            replace: None,
+            network: None,
+            environment: Some("prod".to_string()),
+        };
+
+        create(&ctx, &args).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn create_restores_original_state() -> Result<(), Error> {
+        // Operations
+        let ops = |status: CanisterStatusType| operations::Initializers {
+            canister: canister::Initializers {
+                status: Box::new(move |_| {
+                    let mut m = MockStatus::new();
+                    m.expect_status().returning(move |_| Ok(status));
+                    Arc::new(m)
+                }),
+                stop: Box::new(|_| {
+                    let mut m = MockStop::new();
+                    m.expect_stop().once().returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                snapshot: Box::new(|_| {
+                    let mut m = MockSnapshots::new();
+                    m.expect_create().once().returning(|_, _| {
+                        Ok(Snapshot {
+                            id: vec![0xab, 0xcd],
+                            taken_at_timestamp: 42,
+                            total_size: 1024,
+                        })
+                    });
+                    Arc::new(m)
+                }),
+                // Only a running canister is started again, and here fails to
+                start: Box::new(|_| {
+                    let mut m = MockStart::new();
+                    m.expect_start()
+                        .once()
+                        .returning(|_| Err(StartError::Unexpected(anyhow::anyhow!("boom"))));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .with(eq("ic"), eq("backend"))
+                        .returning(|_, _| Ok(Principal::anonymous()));
+                    Arc::new(m)
+                }),
+                labels: Box::new(|_| {
+                    let mut m = MockLabels::new();
+                    m.expect_list().returning(|_| Ok(vec![]));
+                    m.expect_record().once().returning(|_, _| Ok(()));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let args = CreateArgs {
+            canister: args::Canister::Name("backend".to_string()),
+            label: None,
+            replace: None,
+            network: Some(args::Network::Name("ic".to_string())),
+            environment: None,
+        };
+
+        // A stopped canister is left stopped
+        let ctx = Context {
+            mode: Mode::Project("path".into()),
+            ops: ops(CanisterStatusType::Stopped),
+        };
+
+        create(&ctx, &args).await?;
+
+        // The snapshot is recorded before the failure to start is reported
+        let ctx = Context {
+            mode: Mode::Project("path".into()),
+            ops: ops(CanisterStatusType::Running),
+        };
+
+        assert!(matches!(
+            create(&ctx, &args).await,
+            Err(CommandError::Start(_))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn create_replaces_labeled_snapshot() -> Result<(), Error> {
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                status: Box::new(|_| {
+                    let mut m = MockStatus::new();
+                    m.expect_status()
+                        .returning(|_| Ok(CanisterStatusType::Stopped));
+                    Arc::new(m)
+                }),
+                stop: Box::new(|_| {
+                    let mut m = MockStop::new();
+                    m.expect_stop().once().returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                snapshot: Box::new(|_| {
+                    let mut m = MockSnapshots::new();
+                    m.expect_create()
+                        .with(eq(Principal::anonymous()), eq(Some(vec![0x01, 0x02])))
+                        .once()
+                        .returning(|_, _| {
+                            Ok(Snapshot {
+                                id: vec![0xab, 0xcd],
+                                taken_at_timestamp: 42,
+                                total_size: 1024,
+                            })
+                        });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .with(eq("ic"), eq("backend"))
+                        .returning(|_, _| Ok(Principal::anonymous()));
+                    Arc::new(m)
+                }),
+                labels: Box::new(|_| {
+                    let mut m = MockLabels::new();
+                    // The label is carried by the snapshot being replaced
+                    m.expect_list().returning(|_| {
+                        Ok(vec![Labeled {
+                            id: "0102".to_string(),
+                            label: Some("nightly".to_string()),
+                            taken_at: 1,
+                        }])
+                    });
+                    m.expect_remove()
+                        .with(eq(Principal::anonymous()), eq("0102"))
+                        .once()
+                        .returning(|_, _| Ok(()));
+                    m.expect_record()
+                        .with(
+                            eq(Principal::anonymous()),
+                            eq(Labeled {
+                                id: "abcd".to_string(),
+                                label: Some("nightly".to_string()),
+                                taken_at: 42,
+                            }),
+                        )
+                        .once()
+                        .returning(|_, _| Ok(()));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = CreateArgs {
+            canister: args::Canister::Name("backend".to_string()),
+            label: Some("nightly".to_string()),
+            replace: Some("0102".to_string()),
+            network: Some(args::Network::Name("ic".to_string())),
+            environment: None,
+        };
+
+        create(&ctx, &args).await?;
+
+        Ok(())
+    }
+}
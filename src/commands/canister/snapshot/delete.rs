@@ -0,0 +1,115 @@
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        canister::snapshot::{ReferenceError, reference},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args, operations,
+};
+
+#[derive(Args)]
+pub struct DeleteArgs {
+    pub canister: args::Canister,
+
+    // Snapshot ID
+    #[arg(required_unless_present = "label")]
+    pub id: Option<String>,
+
+    // Label of a snapshot recorded in the project
+    #[arg(long, conflicts_with = "id")]
+    pub label: Option<String>,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(DeleteArgs, canister: args::Canister);
+impl_from_args!(DeleteArgs, network: Option<args::Network>);
+impl_from_args!(DeleteArgs, environment: Option<String>);
+impl_from_args!(DeleteArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for DeleteArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // labels are recorded in the project
+            |args: &DeleteArgs, m: &Mode| {
+                (matches!(m, Mode::Global) && args.label.is_some())
+                    .then_some("Snapshot labels are only available in project mode.".to_string())
+            },
+            //
+            // dummy case to shush linter
+            |_: &DeleteArgs, _: &Mode| None,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error(transparent)]
+    Reference(#[from] ReferenceError),
+
+    #[error("failed to delete snapshot")]
+    Snapshot(#[from] operations::canister::SnapshotError),
+
+    #[error("failed to remove snapshot record")]
+    Labels(#[from] operations::store::LabelError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn delete(ctx: &Context, args: &DeleteArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let id = reference(ctx, &cid, &args.id, &args.label).await?;
+
+    (ctx.ops.canister.snapshot)(&agent)
+        .delete(&cid, &id)
+        .await?;
+
+    // Forget the local record as well
+    if let Mode::Project(dir) = &ctx.mode {
+        (ctx.ops.store.labels)(dir)
+            .remove(&cid, &hex::encode(&id))
+            .await?;
+    }
+
+    println!("Deleted snapshot {} of canister {cid}", hex::encode(&id));
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        canister::snapshot::{ReferenceError, reference},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args, operations,
+};
+
+#[derive(Args)]
+pub struct DownloadArgs {
+    pub canister: args::Canister,
+
+    // Snapshot ID
+    #[arg(required_unless_present = "label")]
+    pub id: Option<String>,
+
+    // Label of a snapshot recorded in the project
+    #[arg(long, conflicts_with = "id")]
+    pub label: Option<String>,
+
+    // Directory to write the snapshot to
+    #[arg(long)]
+    pub dir: PathBuf,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(DownloadArgs, canister: args::Canister);
+impl_from_args!(DownloadArgs, network: Option<args::Network>);
+impl_from_args!(DownloadArgs, environment: Option<String>);
+impl_from_args!(DownloadArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for DownloadArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // labels are recorded in the project
+            |args: &DownloadArgs, m: &Mode| {
+                (matches!(m, Mode::Global) && args.label.is_some())
+                    .then_some("Snapshot labels are only available in project mode.".to_string())
+            },
+            //
+            // dummy case to shush linter
+            |_: &DownloadArgs, _: &Mode| None,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error(transparent)]
+    Reference(#[from] ReferenceError),
+
+    #[error("directory `{0}` is not empty")]
+    NotEmpty(PathBuf),
+
+    #[error("failed to download snapshot")]
+    Archive(#[from] operations::canister::ArchiveError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn download(ctx: &Context, args: &DownloadArgs) -> Result<(), CommandError> {
+    // Never mix a download into existing files
+    if let Ok(mut entries) = tokio::fs::read_dir(&args.dir).await
+        && entries
+            .next_entry()
+            .await
+            .is_ok_and(|entry| entry.is_some())
+    {
+        return Err(CommandError::NotEmpty(args.dir.to_owned()));
+    }
+
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let id = reference(ctx, &cid, &args.id, &args.label).await?;
+
+    (ctx.ops.canister.archive)(&agent)
+        .download(&cid, &id, &args.dir)
+        .await?;
+
+    println!(
+        "Downloaded snapshot {} of canister {cid} to {}",
+        hex::encode(&id),
+        args.dir.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Error;
+    use candid::Principal;
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::snapshot::{DownloadArgs, download, download::CommandError},
+        },
+        operations,
+    };
+
+    #[tokio::test]
+    async fn download_refuses_non_empty_dir() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("metadata.json"), "{}")?;
+
+        // Mode (Global)
+        let mode = Mode::Global;
+
+        // Operations
+        let ops = operations::Initializers::default();
+
+        let ctx = Context { mode, ops };
+
+        let args = DownloadArgs {
+            canister: args::Canister::Principal(Principal::anonymous()),
+            id: Some("00".to_string()),
+            label: None,
+            dir: dir.path().to_owned(),
+            network: Some(args::Network::Url("https://icp-api.io".to_string())),
+            environment: None,
+        };
+
+        assert!(matches!(
+            download(&ctx, &args).await,
+            Err(CommandError::NotEmpty(path)) if path == dir.path()
+        ));
+
+        Ok(())
+    }
+}
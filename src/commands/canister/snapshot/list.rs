@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+
+use clap::Args;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args, operations,
+};
+
+#[derive(Args)]
+pub struct ListArgs {
+    pub canister: args::Canister,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(ListArgs, canister: args::Canister);
+impl_from_args!(ListArgs, network: Option<args::Network>);
+impl_from_args!(ListArgs, environment: Option<String>);
+impl_from_args!(ListArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for ListArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("failed to list snapshots")]
+    Snapshot(#[from] operations::canister::SnapshotError),
+
+    #[error("failed to read snapshot records")]
+    Labels(#[from] operations::store::LabelError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn list(ctx: &Context, args: &ListArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let snapshots = (ctx.ops.canister.snapshot)(&agent).list(&cid).await?;
+
+    // Local labels by snapshot ID
+    let labels: BTreeMap<String, String> = match &ctx.mode {
+        Mode::Project(dir) => (ctx.ops.store.labels)(dir)
+            .list(&cid)
+            .await?
+            .into_iter()
+            .filter_map(|s| s.label.map(|label| (s.id, label)))
+            .collect(),
+
+        Mode::Global => BTreeMap::new(),
+    };
+
+    if snapshots.is_empty() {
+        println!("No snapshots for canister {cid}");
+        return Ok(());
+    }
+
+    for s in snapshots {
+        let id = hex::encode(&s.id);
+
+        let taken_at = OffsetDateTime::from_unix_timestamp_nanos(s.taken_at_timestamp as i128)
+            .map_err(anyhow::Error::from)?
+            .format(&Rfc3339)
+            .map_err(anyhow::Error::from)?;
+
+        let label = labels.get(&id).map(String::as_str).unwrap_or("-");
+
+        println!("{id}  {taken_at}  {:>12} bytes  {label}", s.total_size);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        canister::snapshot::{ReferenceError, reference},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{self, canister::CanisterStatusType},
+};
+
+#[derive(Args)]
+pub struct LoadArgs {
+    pub canister: args::Canister,
+
+    // Snapshot ID
+    #[arg(required_unless_present = "label")]
+    pub id: Option<String>,
+
+    // Label of a snapshot recorded in the project
+    #[arg(long, conflicts_with = "id")]
+    pub label: Option<String>,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(LoadArgs, canister: args::Canister);
+impl_from_args!(LoadArgs, network: Option<args::Network>);
+impl_from_args!(LoadArgs, environment: Option<String>);
+impl_from_args!(LoadArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for LoadArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // labels are recorded in the project
+            |args: &LoadArgs, m: &Mode| {
+                (matches!(m, Mode::Global) && args.label.is_some())
+                    .then_some("Snapshot labels are only available in project mode.".to_string())
+            },
+            //
+            // dummy case to shush linter
+            |_: &LoadArgs, _: &Mode| None,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error(transparent)]
+    Reference(#[from] ReferenceError),
+
+    #[error("failed to read canister status")]
+    Status(#[from] operations::canister::StatusError),
+
+    #[error("failed to stop canister")]
+    Stop(#[from] operations::canister::StopError),
+
+    #[error("failed to load snapshot")]
+    Snapshot(#[from] operations::canister::SnapshotError),
+
+    #[error("failed to restart canister")]
+    Start(#[from] operations::canister::StartError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn load(ctx: &Context, args: &LoadArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let id = reference(ctx, &cid, &args.id, &args.label).await?;
+
+    let running = (ctx.ops.canister.status)(&agent).status(&cid).await?;
+
+    // The canister is stopped for the load and restarted regardless of the outcome,
+    // unless it was not running to begin with
+    (ctx.ops.canister.stop)(&agent).stop(&cid).await?;
+
+    let out = (ctx.ops.canister.snapshot)(&agent).load(&cid, &id).await;
+
+    if running == CanisterStatusType::Running {
+        (ctx.ops.canister.start)(&agent).start(&cid).await?;
+    }
+
+    out?;
+
+    println!("Loaded snapshot {} into canister {cid}", hex::encode(&id));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::{eq, function};
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::snapshot::{LoadArgs, load},
+        },
+        operations::{
+            self,
+            canister::{
+                self, CanisterStatusType, MockSnapshots, MockStart, MockStatus, MockStop,
+                SnapshotError,
+            },
+            store::{self, Labeled, Manifest, MockLabels, MockLoad, MockStore},
+        },
+    };
+
+    #[tokio::test]
+    async fn load_by_label_restarts_on_failure() -> Result<(), Error> {
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                status: Box::new(|_| {
+                    let mut m = MockStatus::new();
+                    m.expect_status()
+                        .returning(|_| Ok(CanisterStatusType::Running));
+                    Arc::new(m)
+                }),
+                stop: Box::new(|_| {
+                    let mut m = MockStop::new();
+                    m.expect_stop().once().returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                snapshot: Box::new(|_| {
+                    let mut m = MockSnapshots::new();
+                    m.expect_load()
+                        .with(
+                            eq(Principal::anonymous()),
+                            function(|id: &[u8]| id == [0xab, 0xcd]),
+                        )
+                        .once()
+                        .returning(|_, _| Err(SnapshotError::Unexpected(anyhow::anyhow!("boom"))));
+                    Arc::new(m)
+                }),
+                start: Box::new(|_| {
+                    let mut m = MockStart::new();
+                    m.expect_start().once().returning(|_| Ok(()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .returning(|_, _| Ok(Principal::anonymous()));
+                    Arc::new(m)
+                }),
+                labels: Box::new(|_| {
+                    let mut m = MockLabels::new();
+                    m.expect_list().returning(|_| {
+                        Ok(vec![Labeled {
+                            id: "abcd".to_string(),
+                            label: Some("before-migration".to_string()),
+                            taken_at: 42,
+                        }])
+                    });
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = LoadArgs {
+            canister: args::Canister::Name("backend".to_string()),
+            id: None,
+            label: Some("before-migration".to_string()),
+            network: Some(args::Network::Name("ic".to_string())),
+            environment: None,
+        };
+
+        // The load fails, but the canister is still restarted
+        assert!(load(&ctx, &args).await.is_err());
+
+        Ok(())
+    }
+}
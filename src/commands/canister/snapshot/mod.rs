@@ -0,0 +1,89 @@
+use candid::Principal;
+use clap::{Parser, Subcommand};
+
+use crate::{
+    commands::{Context, Mode},
+    operations::store::LabelError,
+};
+
+mod create;
+pub use create::*;
+
+mod delete;
+pub use delete::*;
+
+mod download;
+pub use download::*;
+
+mod list;
+pub use list::*;
+
+mod load;
+pub use load::*;
+
+mod upload;
+pub use upload::*;
+
+#[derive(Parser)]
+pub struct Command {
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    Create(CreateArgs),
+    Delete(DeleteArgs),
+    Download(DownloadArgs),
+    List(ListArgs),
+    Load(LoadArgs),
+    Upload(UploadArgs),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReferenceError {
+    #[error("invalid snapshot ID `{0}`, expected hex")]
+    InvalidId(String),
+
+    #[error("no snapshot labeled `{label}` for canister {cid}")]
+    UnknownLabel { cid: Principal, label: String },
+
+    #[error("snapshot labels are only available in project mode")]
+    ProjectRequired,
+
+    #[error(transparent)]
+    Labels(#[from] LabelError),
+}
+
+/// Resolves a snapshot given either by its hex ID or by a local label.
+pub async fn reference(
+    ctx: &Context,
+    cid: &Principal,
+    id: &Option<String>,
+    label: &Option<String>,
+) -> Result<Vec<u8>, ReferenceError> {
+    let label = match (id, label) {
+        (Some(id), _) => {
+            return hex::decode(id).map_err(|_| ReferenceError::InvalidId(id.to_owned()));
+        }
+        (None, Some(label)) => label,
+        (None, None) => unreachable!("clap requires an ID or a label"),
+    };
+
+    let Mode::Project(dir) = &ctx.mode else {
+        return Err(ReferenceError::ProjectRequired);
+    };
+
+    let id = (ctx.ops.store.labels)(dir)
+        .list(cid)
+        .await?
+        .into_iter()
+        .find(|s| s.label.as_ref() == Some(label))
+        .map(|s| s.id)
+        .ok_or_else(|| ReferenceError::UnknownLabel {
+            cid: *cid,
+            label: label.to_owned(),
+        })?;
+
+    hex::decode(&id).map_err(|_| ReferenceError::InvalidId(id))
+}
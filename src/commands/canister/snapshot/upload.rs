@@ -0,0 +1,276 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{self, store::Labeled},
+};
+
+#[derive(Args)]
+pub struct UploadArgs {
+    pub canister: args::Canister,
+
+    // Directory holding a downloaded snapshot
+    #[arg(long)]
+    pub dir: PathBuf,
+
+    // Label to refer to the snapshot by
+    #[arg(long)]
+    pub label: Option<String>,
+
+    // ID of an existing snapshot to replace
+    #[arg(long)]
+    pub replace: Option<String>,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(UploadArgs, canister: args::Canister);
+impl_from_args!(UploadArgs, network: Option<args::Network>);
+impl_from_args!(UploadArgs, environment: Option<String>);
+impl_from_args!(UploadArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for UploadArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // labels are recorded in the project
+            |args: &UploadArgs, m: &Mode| {
+                (matches!(m, Mode::Global) && args.label.is_some())
+                    .then_some("Snapshot labels are only available in project mode.".to_string())
+            },
+            //
+            // snapshot IDs are hex
+            |args: &UploadArgs, _: &Mode| {
+                args.replace
+                    .as_ref()
+                    .filter(|id| hex::decode(id).is_err())
+                    .map(|id| format!("Invalid snapshot ID `{id}`, expected hex."))
+            },
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_canister_id_is_required_in_global_mode,
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("invalid snapshot ID `{0}`, expected hex")]
+    InvalidId(String),
+
+    #[error("failed to upload snapshot")]
+    Archive(#[from] operations::canister::ArchiveError),
+
+    #[error("failed to list snapshots")]
+    Snapshot(#[from] operations::canister::SnapshotError),
+
+    #[error("failed to record snapshot")]
+    Labels(#[from] operations::store::LabelError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn upload(ctx: &Context, args: &UploadArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let replace = args
+        .replace
+        .as_ref()
+        .map(|id| hex::decode(id).map_err(|_| CommandError::InvalidId(id.to_owned())))
+        .transpose()?;
+
+    // Refuse a taken label before uploading anything
+    let labels = match &ctx.mode {
+        Mode::Project(dir) => Some((ctx.ops.store.labels)(dir)),
+        Mode::Global => None,
+    };
+
+    if let (Some(labels), Some(label)) = (&labels, &args.label)
+        && labels
+            .list(&cid)
+            .await?
+            .iter()
+            .any(|s| s.label.as_ref() == Some(label))
+    {
+        return Err(operations::store::LabelError::Taken {
+            cid,
+            label: label.to_owned(),
+        }
+        .into());
+    }
+
+    let id = (ctx.ops.canister.archive)(&agent)
+        .upload(&cid, &args.dir, replace.to_owned())
+        .await?;
+
+    if let Some(labels) = &labels {
+        if let Some(replaced) = &replace {
+            labels.remove(&cid, &hex::encode(replaced)).await?;
+        }
+
+        // The upload time is only known to the canister
+        let taken_at = (ctx.ops.canister.snapshot)(&agent)
+            .list(&cid)
+            .await?
+            .into_iter()
+            .find(|s| s.id == id)
+            .map(|s| s.taken_at_timestamp)
+            .unwrap_or_default();
+
+        labels
+            .record(
+                &cid,
+                Labeled {
+                    id: hex::encode(&id),
+                    label: args.label.to_owned(),
+                    taken_at,
+                },
+            )
+            .await?;
+    }
+
+    println!("Uploaded snapshot {} to canister {cid}", hex::encode(&id));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, sync::Arc};
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::{eq, function};
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::snapshot::{UploadArgs, upload},
+        },
+        operations::{
+            self,
+            canister::{self, MockArchive, MockSnapshots, Snapshot},
+            store::{self, Labeled, Manifest, MockLabels, MockLoad, MockStore},
+        },
+    };
+
+    #[tokio::test]
+    async fn upload_in_project_records_label() -> Result<(), Error> {
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                archive: Box::new(|_| {
+                    let mut m = MockArchive::new();
+                    m.expect_upload()
+                        .with(
+                            eq(Principal::anonymous()),
+                            function(|dir: &Path| dir == Path::new("backup")),
+                            eq(None),
+                        )
+                        .once()
+                        .returning(|_, _, _| Ok(vec![0xab, 0xcd]));
+                    Arc::new(m)
+                }),
+                snapshot: Box::new(|_| {
+                    let mut m = MockSnapshots::new();
+                    m.expect_list().returning(|_| {
+                        Ok(vec![Snapshot {
+                            id: vec![0xab, 0xcd],
+                            taken_at_timestamp: 42,
+                            total_size: 1024,
+                        }])
+                    });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(|_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .with(eq("ic"), eq("backend"))
+                        .returning(|_, _| Ok(Principal::anonymous()));
+                    Arc::new(m)
+                }),
+                labels: Box::new(|_| {
+                    let mut m = MockLabels::new();
+                    m.expect_list().returning(|_| Ok(vec![]));
+                    m.expect_record()
+                        .with(
+                            eq(Principal::anonymous()),
+                            eq(Labeled {
+                                id: "abcd".to_string(),
+                                label: Some("from-prod".to_string()),
+                                taken_at: 42,
+                            }),
+                        )
+                        .once()
+                        .returning(|_, _| Ok(()));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = UploadArgs {
+            canister: args::Canister::Name("backend".to_string()),
+            dir: "backup".into(),
+            label: Some("from-prod".to_string()),
+            replace: None,
+            network: Some(args::Network::Name("ic".to_string())),
+            environment: None,
+        };
+
+        upload(&ctx, &args).await?;
+
+        Ok(())
+    }
+}
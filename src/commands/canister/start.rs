@@ -1,18 +1,33 @@
-use candid::Principal;
 use clap::Args;
-use ic_agent::{Agent, AgentError};
 
 use crate::{
     commands::{
         Context, Mode,
         args::{self, Validate, ValidateError, validations},
+        canister::batch,
+        resolve::{self, ResolveError},
     },
-    impl_from_args, operations,
+    impl_from_args,
+    operations::canister::Orchestrator,
 };
 
 #[derive(Args, Clone)]
 pub struct StartArgs {
-    pub canister: args::Canister,
+    // Canisters to start
+    #[arg(required_unless_present_any = ["all", "tag"])]
+    pub canisters: Vec<args::Canister>,
+
+    // Start every canister in the environment
+    #[arg(long, conflicts_with_all = ["canisters", "tag"])]
+    pub all: bool,
+
+    // Start the canisters carrying a tag, may be repeated
+    #[arg(long)]
+    pub tag: Vec<String>,
+
+    // Maximum number of canisters started at once
+    #[arg(long, default_value_t = 8)]
+    pub concurrency: usize,
 
     // Network
     #[arg(long)]
@@ -23,7 +38,7 @@ pub struct StartArgs {
     pub environment: Option<String>,
 }
 
-impl_from_args!(StartArgs, canister: args::Canister);
+impl_from_args!(StartArgs, canisters: Vec<args::Canister>, all: bool, tag: Vec<String>);
 impl_from_args!(StartArgs, network: Option<args::Network>);
 impl_from_args!(StartArgs, environment: Option<String>);
 impl_from_args!(StartArgs, network: Option<args::Network>, environment: Option<String>);
@@ -33,11 +48,13 @@ impl Validate for StartArgs {
         // Custom Tests
         for test in [
             //
-            // first custom check
-            |_args, _m| None::<&'static str>,
+            // at least one canister at a time
+            |args: &StartArgs, _: &Mode| {
+                (args.concurrency == 0).then_some("Concurrency must be at least 1.")
+            },
             //
-            // second custom check
-            |_args, _m| None::<&'static str>,
+            // dummy case to shush linter
+            |_: &StartArgs, _: &Mode| None,
         ] {
             test(self, mode)
                 .map(|msg| anyhow::format_err!(msg))
@@ -46,7 +63,7 @@ impl Validate for StartArgs {
 
         // General Tests
         for test in [
-            validations::a_canister_id_is_required_in_global_mode,
+            validations::canister_principals_are_required_in_global_mode,
             validations::a_network_name_is_required_in_project_mode,
             validations::a_network_url_is_required_in_global_mode,
             validations::environments_are_not_available_in_a_global_mode,
@@ -63,47 +80,56 @@ impl Validate for StartArgs {
 
 #[derive(Debug, thiserror::Error)]
 pub enum CommandError {
-    #[error("failed to start canister")]
-    Start(#[from] operations::canister::StartError),
+    #[error("failed to start {failed} of {total} canisters")]
+    Failed { failed: usize, total: usize },
 
     #[error(transparent)]
-    Agent(#[from] AgentError),
+    Resolve(#[from] ResolveError),
 
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
 
 pub async fn start(ctx: &Context, args: &StartArgs) -> Result<(), CommandError> {
-    // let cid = match &ctx.mode {
-    //     //
-    //     Mode::Project(dir) => {
-    //         todo!()
-    //     }
-
-    //     //
-    //     Mode::Global => {
-    //         todo!()
-    //     }
-    // };
-
-    let cid = Principal::anonymous();
-
-    let agent = Agent::builder()
-        .with_url("http://www,example.com")
-        .build()?;
-
-    (ctx.ops.canister.start)(&agent).start(&cid).await?;
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let targets =
+        resolve::canisters(ctx, &args.canisters, args.all, &args.tag, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let checks = resolve::health(ctx, &targets).await?;
+
+    let orchestrator = Orchestrator {
+        stop: (ctx.ops.canister.stop)(&agent),
+        start: (ctx.ops.canister.start)(&agent),
+        health: (ctx.ops.canister.health)(&agent),
+    };
 
-    Ok(())
+    let outcomes = batch::each(targets, args.concurrency, |cid| {
+        let (orchestrator, check) = (&orchestrator, checks.get(&cid));
+        async move {
+            orchestrator
+                .start(&cid, check, &|phase| println!("{cid}: {phase}"))
+                .await
+        }
+    })
+    .await;
+
+    match batch::summarize("started", &outcomes) {
+        0 => Ok(()),
+        failed => Err(CommandError::Failed {
+            failed,
+            total: outcomes.len(),
+        }),
+    }
 }
 
 #[cfg(test)]
 mod tests_start {
-    use std::sync::Arc;
+    use std::{collections::BTreeMap, sync::Arc};
 
     use anyhow::Error;
     use candid::Principal;
-    use mockall::predicate::eq;
 
     use crate::{
         commands::{
@@ -112,25 +138,82 @@ mod tests_start {
         },
         operations::{
             self,
-            canister::{self, MockStart},
+            canister::{self, MockHealth, MockStart, MockStop, StartError},
+            store::{self, Manifest, MockLoad, MockStore},
         },
     };
 
     #[tokio::test]
-    async fn start_in_project() -> Result<(), Error> {
+    async fn start_by_tag_reports_failures() -> Result<(), Error> {
         // Mode (Project)
         let mode = Mode::Project("path".into());
 
+        let (frontend, backend) = (Principal::from_slice(&[1]), Principal::from_slice(&[2]));
+
         // Operations
         let ops = operations::Initializers {
             canister: canister::Initializers {
-                start: Box::new(|_| {
+                stop: Box::new(|_| Arc::new(MockStop::new())),
+                health: Box::new(move |_| {
+                    let mut m = MockHealth::new();
+                    m.expect_check()
+                        .withf(move |cid, check| *cid == frontend && check.method == "health")
+                        .once()
+                        .returning(|_, _| Ok(()));
+                    Arc::new(m)
+                }),
+                start: Box::new(move |_| {
                     let mut m = MockStart::new();
                     m.expect_start()
-                        .with(eq(Principal::anonymous()))
-                        .once()
-                        .returning(|_| Ok(()));
-
+                        .times(2)
+                        .returning(move |cid| match *cid == backend {
+                            true => Err(StartError::Unexpected(anyhow::anyhow!("out of cycles"))),
+                            false => Ok(()),
+                        });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(move |_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup().returning(move |_, name| match name {
+                        "frontend" => Ok(frontend),
+                        _ => Ok(backend),
+                    });
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| {
+                        let tagged = store::Canister {
+                            tags: vec!["web".to_string()],
+                            ..Default::default()
+                        };
+
+                        Ok(Manifest {
+                            canisters: BTreeMap::from([
+                                ("backend".to_string(), tagged.to_owned()),
+                                (
+                                    "frontend".to_string(),
+                                    store::Canister {
+                                        health: Some(store::Health {
+                                            method: "health".to_string(),
+                                            arg: None,
+                                            reply: None,
+                                            predicate: None,
+                                            attempts: 1,
+                                            interval: 0,
+                                            deadline: 1,
+                                        }),
+                                        ..tagged
+                                    },
+                                ),
+                                ("worker".to_string(), store::Canister::default()),
+                            ]),
+                            ..Default::default()
+                        })
+                    });
                     Arc::new(m)
                 }),
                 ..Default::default()
@@ -141,12 +224,23 @@ mod tests_start {
         let ctx = Context { mode, ops };
 
         let args = StartArgs {
-            canister: args::Canister::Name("my-canister".to_string()),
-            network: Some(args::Network::Name("my-network".to_string())),
+            canisters: vec![],
+            all: false,
+            tag: vec!["web".to_string()],
+            concurrency: 2,
+            network: Some(args::Network::Name("ic".to_string())),
             environment: None,
         };
 
-        start(&ctx, &args).await?;
+        // One of the two tagged canisters fails, the other starts healthy
+        let err = start(&ctx, &args).await.unwrap_err();
+        assert!(matches!(
+            err,
+            super::CommandError::Failed {
+                failed: 1,
+                total: 2
+            }
+        ));
 
         Ok(())
     }
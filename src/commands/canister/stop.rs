@@ -1,27 +1,43 @@
-use candid::Principal;
 use clap::Args;
-use ic_agent::{Agent, AgentError};
 
 use crate::{
     commands::{
         Context, Mode,
         args::{self, Validate, ValidateError, validations},
+        canister::batch,
+        resolve::{self, ResolveError},
     },
-    impl_from_args, operations,
+    impl_from_args,
 };
 
 #[derive(Args)]
 pub struct StopArgs {
-    pub canister: args::Canister,
+    // Canisters to stop
+    #[arg(required_unless_present_any = ["all", "tag"])]
+    pub canisters: Vec<args::Canister>,
+
+    // Stop every canister in the environment
+    #[arg(long, conflicts_with_all = ["canisters", "tag"])]
+    pub all: bool,
+
+    // Stop the canisters carrying a tag, may be repeated
+    #[arg(long)]
+    pub tag: Vec<String>,
+
+    // Maximum number of canisters stopped at once
+    #[arg(long, default_value_t = 8)]
+    pub concurrency: usize,
 
     // Network
+    #[arg(long)]
     pub network: Option<args::Network>,
 
     // Environment
+    #[arg(long)]
     pub environment: Option<String>,
 }
 
-impl_from_args!(StopArgs, canister: args::Canister);
+impl_from_args!(StopArgs, canisters: Vec<args::Canister>, all: bool, tag: Vec<String>);
 impl_from_args!(StopArgs, network: Option<args::Network>);
 impl_from_args!(StopArgs, environment: Option<String>);
 impl_from_args!(StopArgs, network: Option<args::Network>, environment: Option<String>);
@@ -31,11 +47,13 @@ impl Validate for StopArgs {
         // Custom Tests
         for test in [
             //
-            // first custom check
-            |_args, _m| None::<String>,
+            // at least one canister at a time
+            |args: &StopArgs, _: &Mode| {
+                (args.concurrency == 0).then_some("Concurrency must be at least 1.")
+            },
             //
-            // second custom check
-            |_args, _m| None::<String>,
+            // dummy case to shush linter
+            |_: &StopArgs, _: &Mode| None,
         ] {
             test(self, mode)
                 .map(|msg| anyhow::format_err!(msg))
@@ -44,7 +62,7 @@ impl Validate for StopArgs {
 
         // General Tests
         for test in [
-            validations::a_canister_id_is_required_in_global_mode,
+            validations::canister_principals_are_required_in_global_mode,
             validations::a_network_name_is_required_in_project_mode,
             validations::a_network_url_is_required_in_global_mode,
             validations::environments_are_not_available_in_a_global_mode,
@@ -61,38 +79,38 @@ impl Validate for StopArgs {
 
 #[derive(Debug, thiserror::Error)]
 pub enum CommandError {
-    #[error("failed to stop canister")]
-    Start(#[from] operations::canister::StopError),
+    #[error("failed to stop {failed} of {total} canisters")]
+    Failed { failed: usize, total: usize },
 
     #[error(transparent)]
-    Agent(#[from] AgentError),
+    Resolve(#[from] ResolveError),
 
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
 
 pub async fn stop(ctx: &Context, args: &StopArgs) -> Result<(), CommandError> {
-    // let cid = match &ctx.mode {
-    //     //
-    //     Mode::Project(dir) => {
-    //         todo!()
-    //     }
-
-    //     //
-    //     Mode::Global => {
-    //         todo!()
-    //     }
-    // };
-
-    let cid = Principal::anonymous();
-
-    let agent = Agent::builder()
-        .with_url("http://www.example.com")
-        .build()?;
-
-    (ctx.ops.canister.stop)(&agent).stop(&cid).await?;
-
-    Ok(())
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let targets =
+        resolve::canisters(ctx, &args.canisters, args.all, &args.tag, environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let stopper = (ctx.ops.canister.stop)(&agent);
+
+    let outcomes = batch::each(targets, args.concurrency, |cid| {
+        let stopper = stopper.clone();
+        async move { stopper.stop(&cid).await }
+    })
+    .await;
+
+    match batch::summarize("stopped", &outcomes) {
+        0 => Ok(()),
+        failed => Err(CommandError::Failed {
+            failed,
+            total: outcomes.len(),
+        }),
+    }
 }
 
 #[cfg(test)]
@@ -111,6 +129,7 @@ mod tests {
         operations::{
             self,
             canister::{self, MockStop},
+            store::{self, Manifest, MockLoad, MockStore},
         },
     };
 
@@ -119,13 +138,19 @@ mod tests {
         // Mode (Project)
         let mode = Mode::Project("path".into());
 
+        let (frontend, backend) = (Principal::from_slice(&[1]), Principal::from_slice(&[2]));
+
         // Operations
         let ops = operations::Initializers {
             canister: canister::Initializers {
-                stop: Box::new(|_| {
+                stop: Box::new(move |_| {
                     let mut m = MockStop::new();
                     m.expect_stop()
-                        .with(eq(Principal::anonymous()))
+                        .with(eq(frontend))
+                        .once()
+                        .returning(|_| Ok(()));
+                    m.expect_stop()
+                        .with(eq(backend))
                         .once()
                         .returning(|_| Ok(()));
 
@@ -133,14 +158,37 @@ mod tests {
                 }),
                 ..Default::default()
             },
+            store: store::Initializers {
+                ids: Box::new(move |_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .with(eq("ic"), eq("frontend"))
+                        .returning(move |_, _| Ok(frontend));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
             ..Default::default()
         };
 
         let ctx = Context { mode, ops };
 
+        // Names and principals can be mixed, duplicates are stopped once
         let args = StopArgs {
-            canister: args::Canister::Name("my-canister".to_string()),
-            network: Some(args::Network::Name("my-network".to_string())),
+            canisters: vec![
+                args::Canister::Name("frontend".to_string()),
+                args::Canister::Principal(backend),
+                args::Canister::Principal(frontend),
+            ],
+            all: false,
+            tag: vec![],
+            concurrency: 8,
+            network: Some(args::Network::Name("ic".to_string())),
             environment: None,
         };
 
@@ -0,0 +1,274 @@
+use candid::Principal;
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Cycles, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::canister::{TopUp, TopUpError},
+};
+
+#[derive(Args)]
+pub struct TopUpArgs {
+    // Canister and amount, or only the amount with `--below`
+    #[arg(num_args = 1..=2, value_names = ["CANISTER", "AMOUNT"], required = true)]
+    pub target: Vec<String>,
+
+    // Top up every canister in the environment holding fewer cycles than this
+    #[arg(long)]
+    pub below: Option<Cycles>,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl TopUpArgs {
+    /// Splits the positional arguments into the canister, if any, and the amount.
+    fn target(&self) -> Result<(Option<args::Canister>, Cycles), String> {
+        match (self.target.as_slice(), &self.below) {
+            ([amount], Some(_)) => Ok((None, amount.parse()?)),
+            ([canister, amount], None) => Ok((Some(canister.parse()?), amount.parse()?)),
+            (_, Some(_)) => Err("With `--below`, only give the amount.".to_string()),
+            (_, None) => Err("Please provide a canister and an amount.".to_string()),
+        }
+    }
+}
+
+impl_from_args!(TopUpArgs, network: Option<args::Network>);
+impl_from_args!(TopUpArgs, environment: Option<String>);
+impl_from_args!(TopUpArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for TopUpArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // a canister or `--below`, and an amount
+            |args: &TopUpArgs, _: &Mode| args.target().err(),
+            //
+            // `--below` selects from the project
+            |args: &TopUpArgs, m: &Mode| {
+                (matches!(m, Mode::Global) && args.below.is_some())
+                    .then_some("`--below` is only available in project mode.".to_string())
+            },
+            //
+            // canisters are referenced by principal in global mode
+            |args: &TopUpArgs, m: &Mode| {
+                (matches!(m, Mode::Global)
+                    && matches!(args.target(), Ok((Some(c), _)) if !matches!(c, args::Canister::Principal(_))))
+                .then_some("Please provide a canister principal in global mode.".to_string())
+            },
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_network_name_is_required_in_project_mode,
+            validations::a_network_url_is_required_in_global_mode,
+            validations::environments_are_not_available_in_a_global_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("{0}")]
+    Target(String),
+
+    #[error("failed to top up canister")]
+    TopUp(#[from] TopUpError),
+
+    #[error("failed to top up {failed} of {total} canisters")]
+    Failed { failed: usize, total: usize },
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+pub async fn top_up(ctx: &Context, args: &TopUpArgs) -> Result<(), CommandError> {
+    let (canister, amount) = args.target().map_err(CommandError::Target)?;
+
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let agent = resolve::agent(&network).await?;
+
+    let topup = (ctx.ops.canister.topup)(&agent);
+
+    // A single canister
+    if let Some(canister) = canister {
+        let cid = resolve::canister(ctx, &canister, environment).await?;
+        return Ok(deposit(topup.as_ref(), &cid.to_text(), &cid, amount, None).await?);
+    }
+
+    // Every canister of the environment running low
+    let targets = resolve::canisters(ctx, &[], true, &[], environment).await?;
+    let mut failed = 0;
+
+    for (name, cid) in &targets {
+        if let Err(err) = deposit(topup.as_ref(), name, cid, amount, args.below).await {
+            println!("{name}: failed: {err}");
+            failed += 1;
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        failed => Err(CommandError::Failed {
+            failed,
+            total: targets.len(),
+        }),
+    }
+}
+
+/// Tops up a canister, unless it holds at least `below`, and prints its balance change.
+async fn deposit(
+    topup: &dyn TopUp,
+    name: &str,
+    cid: &Principal,
+    amount: Cycles,
+    below: Option<Cycles>,
+) -> Result<(), TopUpError> {
+    // Only controllers can read the balance, anyone can top up
+    let before = topup.balance(cid).await;
+
+    if let Some(threshold) = below {
+        // Without a balance there is nothing to compare against
+        let balance = match &before {
+            Ok(balance) => Cycles(*balance),
+            Err(_) => return before.map(|_| ()),
+        };
+
+        if balance >= threshold {
+            println!("{name}: {balance} cycles, not below {threshold}, skipped");
+            return Ok(());
+        }
+    }
+
+    topup.top_up(cid, amount.0).await?;
+
+    match (before, topup.balance(cid).await) {
+        (Ok(before), Ok(after)) => {
+            println!("{name}: {} -> {} cycles", Cycles(before), Cycles(after))
+        }
+        _ => println!("{name}: deposited {amount} cycles"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+    };
+
+    use anyhow::Error;
+    use candid::{Nat, Principal};
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode,
+            args::Cycles,
+            canister::{TopUpArgs, top_up},
+        },
+        operations::{
+            self,
+            canister::{self, MockTopUp},
+            store::{self, Manifest, MockLoad, MockStore},
+        },
+    };
+
+    #[tokio::test]
+    async fn top_up_below_threshold_in_project() -> Result<(), Error> {
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        let (low, high) = (Principal::from_slice(&[1]), Principal::from_slice(&[2]));
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                topup: Box::new(move |_| {
+                    let topped = Arc::new(AtomicBool::new(false));
+
+                    let mut m = MockTopUp::new();
+                    let t = topped.clone();
+                    m.expect_balance().returning(move |cid| {
+                        Ok(match (*cid == low, t.load(Ordering::SeqCst)) {
+                            (true, false) => 1_000_000_000_000,
+                            (true, true) => 3_000_000_000_000,
+                            (false, _) => 10_000_000_000_000,
+                        })
+                    });
+                    m.expect_top_up()
+                        .with(eq(low), eq(2_000_000_000_000))
+                        .once()
+                        .returning(move |_, _| {
+                            topped.store(true, Ordering::SeqCst);
+                            Ok(Nat::from(7u8))
+                        });
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                ids: Box::new(move |_| {
+                    let mut m = MockStore::new();
+                    m.expect_list().with(eq("ic")).returning(move |_| {
+                        Ok(BTreeMap::from([
+                            ("backend".to_string(), low),
+                            ("frontend".to_string(), high),
+                        ]))
+                    });
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = TopUpArgs {
+            target: vec!["2T".to_string()],
+            below: Some(Cycles(5_000_000_000_000)),
+            network: None,
+            environment: Some("ic".to_string()),
+        };
+
+        top_up(&ctx, &args).await?;
+
+        Ok(())
+    }
+}
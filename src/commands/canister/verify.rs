@@ -0,0 +1,250 @@
+use clap::Args;
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, Validate, ValidateError, validations},
+        resolve::{self, ResolveError},
+    },
+    impl_from_args,
+    operations::{canister::InfoError, store::ArtifactsError},
+};
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    pub canister: args::Canister,
+
+    // Network
+    #[arg(long)]
+    pub network: Option<args::Network>,
+
+    // Environment
+    #[arg(long)]
+    pub environment: Option<String>,
+}
+
+impl_from_args!(VerifyArgs, canister: args::Canister);
+impl_from_args!(VerifyArgs, network: Option<args::Network>);
+impl_from_args!(VerifyArgs, environment: Option<String>);
+impl_from_args!(VerifyArgs, network: Option<args::Network>, environment: Option<String>);
+
+impl Validate for VerifyArgs {
+    fn validate(&self, mode: &Mode) -> Result<(), ValidateError> {
+        // Custom Tests
+        for test in [
+            //
+            // builds are recorded by the project
+            |_: &VerifyArgs, mode: &Mode| {
+                matches!(mode, Mode::Global)
+                    .then_some("Verifying is only available in project mode.".to_string())
+            },
+            //
+            // builds are recorded by canister name
+            |args: &VerifyArgs, _: &Mode| {
+                matches!(args.canister, args::Canister::Principal(_))
+                    .then_some("A canister name is required to find its build.".to_string())
+            },
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        // General Tests
+        for test in [
+            validations::a_network_name_is_required_in_project_mode,
+            validations::network_or_environment_not_both,
+        ] {
+            test(self, mode)
+                .map(|msg| anyhow::format_err!(msg))
+                .map_or(Ok(()), Err)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("`{0}` is not a project canister")]
+    NotLocated(String),
+
+    #[error("canister `{0}` has not been built yet")]
+    NotBuilt(String),
+
+    #[error("canister `{name}` was built for environment `{built}`, build it for `{environment}`")]
+    OtherEnvironment {
+        name: String,
+        built: String,
+        environment: String,
+    },
+
+    #[error("canister {0} has no module installed")]
+    Empty(String),
+
+    #[error("canister `{name}` runs module 0x{deployed}, its last build is 0x{built}")]
+    Mismatch {
+        name: String,
+        deployed: String,
+        built: String,
+    },
+
+    #[error("failed to read the deployed module hash")]
+    Info(#[from] InfoError),
+
+    #[error("failed to read the build artifacts")]
+    Artifacts(#[from] ArtifactsError),
+
+    #[error(transparent)]
+    Resolve(#[from] ResolveError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Checks that a canister runs exactly the module of its last build.
+pub async fn verify(ctx: &Context, args: &VerifyArgs) -> Result<(), CommandError> {
+    let environment = resolve::environment(&args.network, &args.environment);
+    let network = resolve::network(ctx, &args.network, &args.environment).await?;
+    let cid = resolve::canister(ctx, &args.canister, environment).await?;
+
+    let at = resolve::locate(ctx, &args.canister, environment)
+        .await?
+        .ok_or_else(|| CommandError::NotLocated(args.canister.to_string()))?;
+
+    let artifact = (ctx.ops.store.artifacts)(&at.dir)
+        .load()
+        .await?
+        .remove(&at.name)
+        .ok_or_else(|| CommandError::NotBuilt(at.name.to_owned()))?;
+
+    // The same sources build to another module in another environment
+    if artifact.environment != at.environment {
+        return Err(CommandError::OtherEnvironment {
+            name: at.name,
+            built: artifact.environment,
+            environment: at.environment,
+        });
+    }
+
+    let agent = resolve::agent(&network).await?;
+
+    let deployed = (ctx.ops.canister.info)(&agent)
+        .module_hash(&cid)
+        .await?
+        .map(hex::encode)
+        .ok_or_else(|| CommandError::Empty(cid.to_string()))?;
+
+    if deployed != artifact.sha256 {
+        return Err(CommandError::Mismatch {
+            name: at.name,
+            deployed,
+            built: artifact.sha256,
+        });
+    }
+
+    println!(
+        "{} ({cid}) runs the module built at {} from {}",
+        at.name,
+        artifact.built_at,
+        artifact.wasm.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use anyhow::Error;
+    use candid::Principal;
+    use mockall::predicate::eq;
+
+    use crate::{
+        commands::{
+            Context, Mode, args,
+            canister::{VerifyArgs, verify, verify::CommandError},
+        },
+        operations::{
+            self,
+            canister::{self, MockInfo},
+            store::{self, Artifact, Manifest, MockLoad, MockRecord, MockStore, Recipe},
+        },
+    };
+
+    #[tokio::test]
+    async fn verify_reports_mismatch() -> Result<(), Error> {
+        let cid = Principal::from_slice(&[1]);
+
+        // Mode (Project)
+        let mode = Mode::Project("path".into());
+
+        // Operations
+        let ops = operations::Initializers {
+            canister: canister::Initializers {
+                info: Box::new(move |_| {
+                    let mut m = MockInfo::new();
+                    m.expect_module_hash()
+                        .with(eq(cid))
+                        .returning(|_| Ok(Some(vec![0xab; 32])));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            store: store::Initializers {
+                artifacts: Box::new(|_| {
+                    let mut m = MockRecord::new();
+                    m.expect_load().returning(|| {
+                        let artifact = Artifact {
+                            wasm: ".icp/build/backend.wasm".into(),
+                            sha256: hex::encode([0xcd; 32]),
+                            candid: None,
+                            recipe: Recipe::Prebuilt {
+                                path: "backend.wasm".into(),
+                            },
+                            environment: "ic".to_string(),
+                            built_at: "2025-01-01T00:00:00Z".to_string(),
+                        };
+                        Ok(BTreeMap::from([("backend".to_string(), artifact)]))
+                    });
+                    Arc::new(m)
+                }),
+                ids: Box::new(move |_| {
+                    let mut m = MockStore::new();
+                    m.expect_lookup()
+                        .with(eq("ic"), eq("backend"))
+                        .returning(move |_, _| Ok(cid));
+                    Arc::new(m)
+                }),
+                manifest: Box::new(|_| {
+                    let mut m = MockLoad::new();
+                    m.expect_load().returning(|| Ok(Manifest::default()));
+                    Arc::new(m)
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = Context { mode, ops };
+
+        let args = VerifyArgs {
+            canister: args::Canister::Name("backend".to_string()),
+            network: Some(args::Network::Name("ic".to_string())),
+            environment: None,
+        };
+
+        match verify(&ctx, &args).await {
+            Err(CommandError::Mismatch {
+                deployed, built, ..
+            }) => {
+                assert_eq!(deployed, hex::encode([0xab; 32]));
+                assert_eq!(built, hex::encode([0xcd; 32]));
+            }
+            out => panic!("expected the modules to differ: {out:?}"),
+        }
+
+        Ok(())
+    }
+}
@@ -6,14 +6,19 @@ use crate::operations::Initializers;
 
 pub mod args;
 pub mod build;
+pub mod candid;
 pub mod canister;
 pub mod macros;
+pub mod resolve;
 pub mod token;
 
 #[derive(Subcommand)]
 pub enum Command {
     // Build
-    Build(build::BuildArgs),
+    Build(build::Command),
+
+    // Candid
+    Candid(candid::Command),
 
     // Canister
     Canister(canister::Command),
@@ -0,0 +1,394 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use candid::Principal;
+use ic_agent::{Agent, AgentError};
+
+use crate::{
+    commands::{
+        Context, Mode,
+        args::{self, validations},
+    },
+    operations::{
+        canister::{Check, HealthError},
+        store::{ListError, LoadError, LookupError, Manifest, Store},
+    },
+};
+
+/// Environment used in project mode when none is given.
+pub const DEFAULT_ENVIRONMENT: &str = "local";
+
+/// Networks known without any further configuration.
+pub const NETWORKS: &[(&str, &str)] = &[
+    ("local", "http://127.0.0.1:4943"),
+    ("ic", "https://icp-api.io"),
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ResolveError {
+    #[error("canister `{0}` can only be referenced by name in project mode")]
+    NameInGlobalMode(String),
+
+    #[error("remote canister `{name}` has no ID in environment `{environment}`")]
+    RemoteUnavailable { name: String, environment: String },
+
+    #[error("canister `{0}` is remote, it is managed outside this project")]
+    Remote(String),
+
+    #[error("canister `{0}` belongs to another environment or project, run the command there")]
+    Qualified(String),
+
+    #[error("canister `{canister}` does not resolve: {}", reason.trim())]
+    Unresolved {
+        canister: String,
+        reason: &'static str,
+    },
+
+    #[error("unknown network `{0}`")]
+    UnknownNetwork(String),
+
+    #[error("environment `{environment}` targets network `{network}`, use `--environment`")]
+    EnvironmentMismatch {
+        environment: String,
+        network: String,
+    },
+
+    #[error("a network url is required in global mode")]
+    UrlRequired,
+
+    #[error("canisters can only be selected by tag or `--all` in project mode")]
+    SelectorInGlobalMode,
+
+    #[error("no canister is tagged `{0}`")]
+    UnknownTag(String),
+
+    #[error(transparent)]
+    Load(#[from] LoadError),
+
+    #[error(transparent)]
+    Lookup(#[from] LookupError),
+
+    #[error(transparent)]
+    List(#[from] ListError),
+
+    #[error("invalid health check for canister `{0}`")]
+    Health(String, #[source] HealthError),
+
+    #[error(transparent)]
+    Agent(#[from] AgentError),
+}
+
+/// A network resolved to its endpoint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Network {
+    pub name: String,
+    pub url: String,
+    pub local: bool,
+}
+
+/// Environment names resolve in: the one given, or else the one named after the network
+/// given, so that a name never resolves to the ID of a canister on another network.
+pub fn environment<'a>(
+    network: &'a Option<args::Network>,
+    environment: &'a Option<String>,
+) -> &'a str {
+    match (environment, network) {
+        (Some(environment), _) => environment,
+        (None, Some(args::Network::Name(name))) => name,
+        (None, Some(args::Network::Url(url))) => url,
+        (None, None) => DEFAULT_ENVIRONMENT,
+    }
+}
+
+pub async fn canister(
+    ctx: &Context,
+    canister: &args::Canister,
+    environment: &str,
+) -> Result<Principal, ResolveError> {
+    if let args::Canister::Principal(cid) = canister {
+        return Ok(*cid);
+    }
+
+    let Some(at) = locate(ctx, canister, environment).await? else {
+        return Err(ResolveError::NameInGlobalMode(canister.to_string()));
+    };
+
+    let m = (ctx.ops.store.manifest)(&at.dir).load().await?;
+    let ids = (ctx.ops.store.ids)(&at.dir);
+    lookup(ids.as_ref(), &m, &at.name, &at.environment).await
+}
+
+/// Where the ID of a named canister is recorded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    /// Root of the project the canister belongs to.
+    pub dir: PathBuf,
+    pub environment: String,
+    pub name: String,
+}
+
+/// Locates a named or qualified canister, None for principals and in global mode.
+pub async fn locate(
+    ctx: &Context,
+    canister: &args::Canister,
+    environment: &str,
+) -> Result<Option<Location>, ResolveError> {
+    let Mode::Project(dir) = &ctx.mode else {
+        return Ok(None);
+    };
+
+    match canister {
+        args::Canister::Principal(_) => Ok(None),
+
+        args::Canister::Name(name) => Ok(Some(Location {
+            dir: dir.to_owned(),
+            environment: environment.to_owned(),
+            name: name.to_owned(),
+        })),
+
+        args::Canister::Qualified(q) => {
+            let m = (ctx.ops.store.manifest)(dir).load().await?;
+
+            // Other projects are declared with their path
+            let project = q.project.as_ref().and_then(|p| m.projects.get(p));
+            let (dir, target) = match project {
+                None => (dir.to_owned(), m.to_owned()),
+                Some(p) => {
+                    let dir = dir.join(&p.path);
+                    let target = (ctx.ops.store.manifest)(&dir).load().await?;
+                    (dir, target)
+                }
+            };
+
+            if let Some(reason) = validations::qualified_canisters_must_resolve(q, &m, &target) {
+                return Err(ResolveError::Unresolved {
+                    canister: canister.to_string(),
+                    reason,
+                });
+            }
+
+            Ok(Some(Location {
+                dir,
+                environment: q.environment.as_deref().unwrap_or(environment).to_owned(),
+                name: q.name.to_owned(),
+            }))
+        }
+    }
+}
+
+/// Like [`canister`], but refuses canisters the project declares as remote, and those of
+/// other environments or projects since changes are made on the current network.
+pub async fn owned(
+    ctx: &Context,
+    canister: &args::Canister,
+    environment: &str,
+) -> Result<Principal, ResolveError> {
+    if let args::Canister::Qualified(_) = canister {
+        return Err(ResolveError::Qualified(canister.to_string()));
+    }
+
+    let cid = self::canister(ctx, canister, environment).await?;
+
+    if let Mode::Project(dir) = &ctx.mode {
+        let m = (ctx.ops.store.manifest)(dir).load().await?;
+
+        // Remote canisters are refused whether named or given by ID
+        let remote = m.canisters.iter().find(|(name, c)| {
+            c.remote.as_ref().is_some_and(|ids| {
+                *canister == args::Canister::Name(name.to_string())
+                    || ids.values().any(|id| *id == cid)
+            })
+        });
+
+        if let Some((name, _)) = remote {
+            return Err(ResolveError::Remote(name.to_owned()));
+        }
+    }
+
+    Ok(cid)
+}
+
+/// Remote canisters resolve to their declared ID, others through the ID store.
+pub async fn lookup(
+    ids: &dyn Store,
+    m: &Manifest,
+    name: &str,
+    environment: &str,
+) -> Result<Principal, ResolveError> {
+    match m.remote(name, environment) {
+        Some(Some(cid)) => Ok(cid),
+
+        Some(None) => Err(ResolveError::RemoteUnavailable {
+            name: name.to_owned(),
+            environment: environment.to_owned(),
+        }),
+
+        None => Ok(ids.lookup(environment, name).await?),
+    }
+}
+
+/// Resolves a selection of canisters to their names (or IDs) and principals.
+///
+/// `--all` selects every canister deployed to the environment, tags select the
+/// manifest canisters carrying any of them. Duplicates are dropped.
+pub async fn canisters(
+    ctx: &Context,
+    canisters: &[args::Canister],
+    all: bool,
+    tags: &[String],
+    environment: &str,
+) -> Result<Vec<(String, Principal)>, ResolveError> {
+    let mut out: Vec<(String, Principal)> = vec![];
+
+    for canister in canisters {
+        out.push((
+            canister.to_string(),
+            self::canister(ctx, canister, environment).await?,
+        ));
+    }
+
+    if all || !tags.is_empty() {
+        let Mode::Project(dir) = &ctx.mode else {
+            return Err(ResolveError::SelectorInGlobalMode);
+        };
+
+        if all {
+            out.extend((ctx.ops.store.ids)(dir).list(environment).await?);
+        }
+
+        if !tags.is_empty() {
+            let m = (ctx.ops.store.manifest)(dir).load().await?;
+            let ids = (ctx.ops.store.ids)(dir);
+
+            for tag in tags {
+                let names: Vec<&String> = m
+                    .canisters
+                    .iter()
+                    .filter(|(_, c)| c.tags.contains(tag))
+                    .map(|(name, _)| name)
+                    .collect();
+
+                if names.is_empty() {
+                    return Err(ResolveError::UnknownTag(tag.to_owned()));
+                }
+
+                for name in names {
+                    let cid = lookup(ids.as_ref(), &m, name, environment).await?;
+                    out.push((name.to_owned(), cid));
+                }
+            }
+        }
+    }
+
+    let mut seen = std::collections::BTreeSet::new();
+    out.retain(|(_, cid)| seen.insert(*cid));
+
+    Ok(out)
+}
+
+/// Health checks declared in the manifest for the given canisters, by principal.
+pub async fn health(
+    ctx: &Context,
+    targets: &[(String, Principal)],
+) -> Result<BTreeMap<Principal, Check>, ResolveError> {
+    let Mode::Project(dir) = &ctx.mode else {
+        return Ok(BTreeMap::new());
+    };
+
+    let m = (ctx.ops.store.manifest)(dir).load().await?;
+
+    targets
+        .iter()
+        .filter_map(|(name, cid)| {
+            let health = m.canisters.get(name)?.health.as_ref()?;
+            Some(
+                Check::parse(health)
+                    .map(|check| (*cid, check))
+                    .map_err(|err| ResolveError::Health(name.to_owned(), err)),
+            )
+        })
+        .collect()
+}
+
+pub async fn network(
+    ctx: &Context,
+    network: &Option<args::Network>,
+    environment: &Option<String>,
+) -> Result<Network, ResolveError> {
+    let dir = match (&ctx.mode, network) {
+        (_, Some(args::Network::Url(url))) => {
+            return Ok(Network {
+                name: url.to_owned(),
+                url: url.to_owned(),
+                local: is_local(url),
+            });
+        }
+
+        (Mode::Global, _) => return Err(ResolveError::UrlRequired),
+
+        (Mode::Project(dir), _) => dir,
+    };
+
+    let m = (ctx.ops.store.manifest)(dir).load().await?;
+
+    let name = match network {
+        // Names then resolve in the environment named after the network, which must target it
+        Some(args::Network::Name(name)) => {
+            let target = m.network_of(name);
+            if environment.is_none() && target != *name {
+                return Err(ResolveError::EnvironmentMismatch {
+                    environment: name.to_owned(),
+                    network: target,
+                });
+            }
+            name.to_owned()
+        }
+
+        // Environments target the network they declare, or the one they are named after
+        _ => m.network_of(self::environment(&None, environment)),
+    };
+
+    match m.networks.get(&name) {
+        Some(network) => Ok(Network {
+            local: is_local(&network.url),
+            url: network.url.to_owned(),
+            name,
+        }),
+
+        None => named(&name),
+    }
+}
+
+pub async fn agent(network: &Network) -> Result<Agent, ResolveError> {
+    let agent = Agent::builder().with_url(&network.url).build()?;
+
+    // Local replicas use a throwaway root key
+    if network.local {
+        agent.fetch_root_key().await?;
+    }
+
+    Ok(agent)
+}
+
+fn named(name: &str) -> Result<Network, ResolveError> {
+    NETWORKS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(n, url)| Network {
+            name: n.to_string(),
+            url: url.to_string(),
+            local: is_local(url),
+        })
+        .ok_or_else(|| ResolveError::UnknownNetwork(name.to_owned()))
+}
+
+fn is_local(url: &str) -> bool {
+    let host = url
+        .split("://")
+        .nth(1)
+        .unwrap_or(url)
+        .split(['/', ':'])
+        .next()
+        .unwrap_or_default();
+
+    matches!(host, "localhost" | "127.0.0.1")
+}
@@ -4,9 +4,14 @@ use anyhow::Error;
 use clap::Parser;
 
 use crate::{
-    commands::{Command, Context, Mode, build, canister, token},
+    commands::{Command, Context, Mode, build, candid, canister, token},
     operations::{
-        canister::{Starter, Stopper},
+        build::{Builder, LocalCache, Processor},
+        canister::{
+            Archiver, Caller, Configurator, Deleter, HealthChecker, Inspector, Installer,
+            LogReader, Snapshotter, Starter, StatusReader, Stopper, TopUpper, Withdrawer,
+        },
+        store::{ArtifactStore, IdStore, LabelStore, MANIFEST, ManifestLoader},
         token::Transmitter,
     },
 };
@@ -36,9 +41,34 @@ async fn main() -> Result<(), Error> {
     };
 
     let ops = operations::Initializers {
+        build: operations::build::Initializers {
+            build: Box::new(Builder::arc),
+            cache: Box::new(LocalCache::arc),
+            process: Box::new(Processor::arc),
+        },
+
         canister: operations::canister::Initializers {
+            archive: Box::new(Archiver::arc),
+            call: Box::new(Caller::arc),
+            delete: Box::new(Deleter::arc),
+            health: Box::new(HealthChecker::arc),
+            info: Box::new(Inspector::arc),
+            install: Box::new(Installer::arc),
+            logs: Box::new(LogReader::arc),
+            settings: Box::new(Configurator::arc),
+            snapshot: Box::new(Snapshotter::arc),
             start: Box::new(Starter::arc),
+            status: Box::new(StatusReader::arc),
             stop: Box::new(Stopper::arc),
+            topup: Box::new(TopUpper::arc),
+            withdraw: Box::new(Withdrawer::arc),
+        },
+
+        store: operations::store::Initializers {
+            artifacts: Box::new(ArtifactStore::arc),
+            ids: Box::new(IdStore::arc),
+            labels: Box::new(LabelStore::arc),
+            manifest: Box::new(ManifestLoader::arc),
         },
 
         token: operations::token::Initializers {
@@ -49,11 +79,70 @@ async fn main() -> Result<(), Error> {
     let ctx = Context { mode, ops };
 
     match cli.command {
-        Command::Build(args) => build::build(&ctx, &args).await?,
+        Command::Build(cmd) => match cmd.command {
+            None => build::build(&ctx, &cmd.build).await?,
+            Some(build::Commands::Cache(cmd)) => match cmd.command {
+                build::cache::Commands::Prune(args) => build::cache::prune(&ctx, &args).await?,
+            },
+        },
+
+        Command::Candid(cmd) => match cmd.command {
+            candid::Commands::Diff(args) => candid::diff(&ctx, &args).await?,
+        },
 
         Command::Canister(cmd) => match cmd.command {
+            canister::Commands::Call(args) => canister::call(&ctx, &args).await?,
+            canister::Commands::Delete(args) => canister::delete(&ctx, &args).await?,
+            canister::Commands::Handover(args) => canister::handover(&ctx, &args).await?,
+            canister::Commands::Id(cmd) => match cmd.command {
+                None => canister::id::lookup(&ctx, &cmd.lookup).await?,
+                Some(canister::id::Commands::Export(args)) => {
+                    canister::id::export(&ctx, &args).await?
+                }
+                Some(canister::id::Commands::Import(args)) => {
+                    canister::id::import(&ctx, &args).await?
+                }
+            },
+            canister::Commands::Info(args) => canister::info(&ctx, &args).await?,
+            canister::Commands::Install(args) => canister::install(&ctx, &args).await?,
+            canister::Commands::List(args) => canister::list(&ctx, &args).await?,
+            canister::Commands::Logs(args) => canister::logs(&ctx, &args).await?,
+            canister::Commands::Settings(cmd) => match cmd.command {
+                canister::settings::Commands::Show(args) => {
+                    canister::settings::show(&ctx, &args).await?
+                }
+                canister::settings::Commands::Sync(args) => {
+                    canister::settings::sync(&ctx, &args).await?
+                }
+                canister::settings::Commands::Update(args) => {
+                    canister::settings::update(&ctx, &args).await?
+                }
+            },
+            canister::Commands::Snapshot(cmd) => match cmd.command {
+                canister::snapshot::Commands::Create(args) => {
+                    canister::snapshot::create(&ctx, &args).await?
+                }
+                canister::snapshot::Commands::Delete(args) => {
+                    canister::snapshot::delete(&ctx, &args).await?
+                }
+                canister::snapshot::Commands::Download(args) => {
+                    canister::snapshot::download(&ctx, &args).await?
+                }
+                canister::snapshot::Commands::List(args) => {
+                    canister::snapshot::list(&ctx, &args).await?
+                }
+                canister::snapshot::Commands::Load(args) => {
+                    canister::snapshot::load(&ctx, &args).await?
+                }
+                canister::snapshot::Commands::Upload(args) => {
+                    canister::snapshot::upload(&ctx, &args).await?
+                }
+            },
+            canister::Commands::Restart(args) => canister::restart(&ctx, &args).await?,
             canister::Commands::Start(args) => canister::start(&ctx, &args).await?,
             canister::Commands::Stop(args) => canister::stop(&ctx, &args).await?,
+            canister::Commands::TopUp(args) => canister::top_up(&ctx, &args).await?,
+            canister::Commands::Verify(args) => canister::verify(&ctx, &args).await?,
         },
 
         Command::Token(cmd) => match cmd.command {
@@ -69,6 +158,12 @@ pub enum LocateError {
     Unexpected(String),
 }
 
+/// Finds the project root by looking for a manifest in the current directory and its ancestors.
 fn locate() -> Result<PathBuf, LocateError> {
-    Err(LocateError::NotFound)
+    let cwd = std::env::current_dir().map_err(|err| LocateError::Unexpected(err.to_string()))?;
+
+    cwd.ancestors()
+        .find(|dir| dir.join(MANIFEST).is_file())
+        .map(PathBuf::from)
+        .ok_or(LocateError::NotFound)
 }
@@ -0,0 +1,411 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use mockall::automock;
+use sha2::{Digest, Sha256};
+use tokio::process::Command;
+
+use crate::operations::{
+    build::BuildError,
+    store::{Canister, Recipe},
+};
+
+/// Directory of cached build artifacts, relative to the project root.
+pub const CACHE: &str = ".icp/cache";
+
+#[derive(Debug, thiserror::Error)]
+pub enum CacheError {
+    #[error("failed to read `{0}`")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("failed to write `{0}`")]
+    Write(PathBuf, #[source] std::io::Error),
+
+    #[error("failed to read the toolchain version")]
+    Toolchain(#[source] BuildError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// What pruning reclaimed.
+#[derive(Debug, Default, PartialEq)]
+pub struct Pruned {
+    pub artifacts: usize,
+    pub bytes: u64,
+}
+
+#[automock]
+#[async_trait]
+pub trait Cache: Sync + Send {
+    /// Hash of everything the build of a canister depends on: its name and recipe, its
+    /// post-processing, the toolchain version, declared environment variables and the content
+    /// of its inputs, along with the variables passed to its recipe.
+    ///
+    /// Canisters without declared inputs have no key and are never cached.
+    async fn key(
+        &self,
+        name: &str,
+        canister: &Canister,
+        env: &BTreeMap<String, String>,
+    ) -> Result<Option<String>, CacheError>;
+
+    /// The artifact built for a key, if there is one.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError>;
+
+    async fn put(&self, key: &str, module: &[u8]) -> Result<(), CacheError>;
+
+    /// Removes every artifact except the kept ones.
+    async fn prune(&self, keep: &BTreeSet<String>) -> Result<Pruned, CacheError>;
+}
+
+pub struct LocalCache {
+    dir: PathBuf,
+}
+
+impl LocalCache {
+    pub fn arc(dir: &Path) -> Arc<dyn Cache> {
+        Arc::new(LocalCache {
+            dir: dir.to_owned(),
+        })
+    }
+
+    fn artifact(&self, key: &str) -> PathBuf {
+        self.dir.join(CACHE).join(format!("{key}.wasm"))
+    }
+
+    /// Version reported by the tool a recipe runs, empty for recipes that run none.
+    async fn toolchain(&self, recipe: &Recipe) -> Result<String, BuildError> {
+        let (tool, dir) = match recipe {
+            // rust-toolchain files apply from where cargo runs
+            Recipe::Rust { path, .. } => (
+                "cargo".into(),
+                match path {
+                    Some(path) => self.dir.join(path),
+                    None => self.dir.to_owned(),
+                },
+            ),
+
+            Recipe::Motoko { .. } => (
+                std::env::var_os("MOC").unwrap_or("moc".into()),
+                self.dir.to_owned(),
+            ),
+
+            Recipe::Script { .. } | Recipe::Prebuilt { .. } => return Ok(String::new()),
+        };
+
+        let name = tool.to_string_lossy().to_string();
+
+        let out = Command::new(&tool)
+            .current_dir(dir)
+            .arg("--version")
+            .output()
+            .await
+            .map_err(|err| BuildError::Spawn(name.to_owned(), err))?;
+
+        match out.status.success() {
+            true => Ok(String::from_utf8_lossy(&out.stdout).trim().to_string()),
+            false => Err(BuildError::Failed {
+                tool: name,
+                status: out.status.to_string(),
+            }),
+        }
+    }
+
+    /// Hashes the relative path and content of a file, or of every file under a directory.
+    async fn digest(&self, h: &mut Sha256, input: &Path) -> Result<(), CacheError> {
+        let mut pending = vec![self.dir.join(input)];
+        let mut files = BTreeSet::new();
+
+        while let Some(path) = pending.pop() {
+            let meta = tokio::fs::metadata(&path)
+                .await
+                .map_err(|err| CacheError::Read(path.to_owned(), err))?;
+
+            if !meta.is_dir() {
+                files.insert(path);
+                continue;
+            }
+
+            let mut entries = tokio::fs::read_dir(&path)
+                .await
+                .map_err(|err| CacheError::Read(path.to_owned(), err))?;
+
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|err| CacheError::Read(path.to_owned(), err))?
+            {
+                pending.push(entry.path());
+            }
+        }
+
+        for path in files {
+            let content = tokio::fs::read(&path)
+                .await
+                .map_err(|err| CacheError::Read(path.to_owned(), err))?;
+
+            let rel = path.strip_prefix(&self.dir).unwrap_or(&path);
+            field(h, rel.to_string_lossy().as_bytes());
+            field(h, &content);
+        }
+
+        Ok(())
+    }
+}
+
+/// Length-prefixed, so that adjacent fields cannot be confused with one another.
+fn field(h: &mut Sha256, bs: &[u8]) {
+    h.update((bs.len() as u64).to_le_bytes());
+    h.update(bs);
+}
+
+#[async_trait]
+impl Cache for LocalCache {
+    async fn key(
+        &self,
+        name: &str,
+        canister: &Canister,
+        env: &BTreeMap<String, String>,
+    ) -> Result<Option<String>, CacheError> {
+        let Some(recipe) = &canister.build else {
+            return Ok(None);
+        };
+
+        // Files read by the recipe or by the post-processing steps
+        let mut inputs = canister.inputs.to_owned();
+
+        if let Recipe::Prebuilt { path } = recipe {
+            inputs.push(path.to_owned());
+        }
+
+        if inputs.is_empty() {
+            return Ok(None);
+        }
+
+        if let Some(wasm) = &canister.wasm {
+            inputs.extend(wasm.candid.to_owned());
+            inputs.extend(wasm.metadata.iter().filter_map(|m| m.file.to_owned()));
+        }
+
+        let mut h = Sha256::new();
+
+        // Recipes see the name of the canister they build
+        field(&mut h, name.as_bytes());
+        field(
+            &mut h,
+            &serde_json::to_vec(recipe).map_err(anyhow::Error::from)?,
+        );
+        field(
+            &mut h,
+            &serde_json::to_vec(&canister.wasm).map_err(anyhow::Error::from)?,
+        );
+
+        let toolchain = self
+            .toolchain(recipe)
+            .await
+            .map_err(CacheError::Toolchain)?;
+        field(&mut h, toolchain.as_bytes());
+
+        for name in &canister.env {
+            field(&mut h, name.as_bytes());
+            match std::env::var_os(name) {
+                Some(v) => field(&mut h, v.as_encoded_bytes()),
+                None => h.update([0xff]),
+            }
+        }
+
+        for (name, v) in env {
+            field(&mut h, name.as_bytes());
+            field(&mut h, v.as_bytes());
+        }
+
+        for input in &inputs {
+            self.digest(&mut h, input).await?;
+        }
+
+        Ok(Some(hex::encode(h.finalize())))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        let path = self.artifact(key);
+
+        match tokio::fs::read(&path).await {
+            Ok(module) => Ok(Some(module)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(CacheError::Read(path, err)),
+        }
+    }
+
+    async fn put(&self, key: &str, module: &[u8]) -> Result<(), CacheError> {
+        let path = self.artifact(key);
+        let partial = path.with_extension("wasm.partial");
+
+        tokio::fs::create_dir_all(self.dir.join(CACHE))
+            .await
+            .map_err(|err| CacheError::Write(path.to_owned(), err))?;
+
+        // Renamed into place so a reader never sees half an artifact
+        tokio::fs::write(&partial, module)
+            .await
+            .map_err(|err| CacheError::Write(partial.to_owned(), err))?;
+
+        tokio::fs::rename(&partial, &path)
+            .await
+            .map_err(|err| CacheError::Write(path, err))
+    }
+
+    async fn prune(&self, keep: &BTreeSet<String>) -> Result<Pruned, CacheError> {
+        let dir = self.dir.join(CACHE);
+        let mut pruned = Pruned::default();
+
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(pruned),
+            Err(err) => return Err(CacheError::Read(dir, err)),
+        };
+
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|err| CacheError::Read(dir.to_owned(), err))?
+        {
+            let path = entry.path();
+
+            let kept = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(".wasm"))
+                .is_some_and(|key| keep.contains(key));
+
+            if kept {
+                continue;
+            }
+
+            let meta = entry
+                .metadata()
+                .await
+                .map_err(|err| CacheError::Read(path.to_owned(), err))?;
+
+            tokio::fs::remove_file(&path)
+                .await
+                .map_err(|err| CacheError::Write(path.to_owned(), err))?;
+
+            pruned.artifacts += 1;
+            pruned.bytes += meta.len();
+        }
+
+        Ok(pruned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::{LocalCache, Pruned};
+    use crate::operations::store::{Canister, Recipe};
+
+    #[tokio::test]
+    async fn key_follows_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        std::fs::write(dir.path().join("src/nested/lib.sh"), "v1").unwrap();
+
+        let cache = LocalCache::arc(dir.path());
+        let mut env = BTreeMap::new();
+
+        let mut canister = Canister {
+            build: Some(Recipe::Script {
+                command: "./build.sh".to_string(),
+                output: "out.wasm".into(),
+            }),
+            ..Default::default()
+        };
+
+        // Nothing declared, nothing to key on
+        assert_eq!(cache.key("backend", &canister, &env).await.unwrap(), None);
+
+        canister.inputs = vec!["src".into()];
+        let first = cache
+            .key("backend", &canister, &env)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            cache.key("backend", &canister, &env).await.unwrap(),
+            Some(first.clone())
+        );
+
+        std::fs::write(dir.path().join("src/nested/lib.sh"), "v2").unwrap();
+        let second = cache
+            .key("backend", &canister, &env)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(first, second);
+
+        canister.env = vec!["ICP_CACHE_TEST_UNSET".to_string()];
+        let third = cache.key("backend", &canister, &env).await.unwrap();
+        assert_ne!(third, Some(second));
+
+        // Dependency IDs passed to the recipe
+        env.insert("CANISTER_ID_LEDGER".to_string(), "aaaaa-aa".to_string());
+        assert_ne!(cache.key("backend", &canister, &env).await.unwrap(), third);
+    }
+
+    #[tokio::test]
+    async fn key_follows_canister_name() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("build.sh"), "echo $CANISTER_NAME").unwrap();
+
+        let cache = LocalCache::arc(dir.path());
+        let env = BTreeMap::new();
+
+        // Two canisters built by the same script
+        let canister = Canister {
+            build: Some(Recipe::Script {
+                command: "./build.sh".to_string(),
+                output: "out.wasm".into(),
+            }),
+            inputs: vec!["build.sh".into()],
+            ..Default::default()
+        };
+
+        assert_ne!(
+            cache.key("frontend", &canister, &env).await.unwrap(),
+            cache.key("backend", &canister, &env).await.unwrap(),
+        );
+    }
+
+    #[tokio::test]
+    async fn put_get_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = LocalCache::arc(dir.path());
+
+        assert_eq!(cache.get("a").await.unwrap(), None);
+
+        cache.put("a", b"\0asm").await.unwrap();
+        cache.put("b", b"\0asm\x01").await.unwrap();
+        assert_eq!(cache.get("a").await.unwrap(), Some(b"\0asm".to_vec()));
+
+        let pruned = cache
+            .prune(&BTreeSet::from(["a".to_string()]))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            pruned,
+            Pruned {
+                artifacts: 1,
+                bytes: 5
+            }
+        );
+        assert!(cache.get("a").await.unwrap().is_some());
+        assert_eq!(cache.get("b").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use candid::Principal;
+
+use crate::operations::store::Manifest;
+
+/// Dependencies of every canister, by name.
+pub type Graph = BTreeMap<String, BTreeSet<String>>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum GraphError {
+    #[error("canister `{canister}` depends on undeclared canister `{dependency}`")]
+    Unknown {
+        canister: String,
+        dependency: String,
+    },
+
+    #[error("canisters depend on each other: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+/// Dependency graph of the manifest canisters, checked to be complete and acyclic.
+pub fn graph(m: &Manifest) -> Result<Graph, GraphError> {
+    let mut g = Graph::new();
+
+    for (name, c) in &m.canisters {
+        for dependency in &c.dependencies {
+            if !m.canisters.contains_key(dependency) {
+                return Err(GraphError::Unknown {
+                    canister: name.to_owned(),
+                    dependency: dependency.to_owned(),
+                });
+            }
+        }
+
+        g.insert(name.to_owned(), c.dependencies.iter().cloned().collect());
+    }
+
+    // Depth-first, a canister met again while still on the path closes a cycle
+    let mut done = BTreeSet::new();
+
+    for name in g.keys() {
+        let mut path: Vec<&String> = vec![];
+        let mut stack = vec![(name, false)];
+
+        while let Some((name, leaving)) = stack.pop() {
+            if leaving {
+                path.pop();
+                done.insert(name);
+                continue;
+            }
+
+            if done.contains(name) {
+                continue;
+            }
+
+            if let Some(at) = path.iter().position(|n| *n == name) {
+                let mut cycle: Vec<String> = path[at..].iter().map(|n| n.to_string()).collect();
+                cycle.push(name.to_owned());
+                return Err(GraphError::Cycle(cycle));
+            }
+
+            path.push(name);
+            stack.push((name, true));
+            stack.extend(g[name].iter().map(|d| (d, false)));
+        }
+    }
+
+    Ok(g)
+}
+
+/// Canisters the named ones depend on, directly or not, including themselves.
+pub fn closure<'a>(g: &'a Graph, names: impl IntoIterator<Item = &'a String>) -> BTreeSet<String> {
+    let mut out = BTreeSet::new();
+    let mut pending: Vec<&String> = names.into_iter().collect();
+
+    while let Some(name) = pending.pop() {
+        if out.insert(name.to_owned()) {
+            pending.extend(g.get(name).into_iter().flatten());
+        }
+    }
+
+    out
+}
+
+/// Environment variable holding the ID of a canister, `CANISTER_ID_<NAME>`.
+pub fn variable(name: &str) -> String {
+    format!(
+        "CANISTER_ID_{}",
+        name.to_uppercase()
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    )
+}
+
+/// IDs of a canister's dependencies in an environment, as passed to its recipe.
+/// Dependencies that were not created yet are left out.
+pub fn exports(
+    m: &Manifest,
+    name: &str,
+    environment: &str,
+    deployed: &BTreeMap<String, Principal>,
+) -> BTreeMap<String, String> {
+    let Some(c) = m.canisters.get(name) else {
+        return BTreeMap::new();
+    };
+
+    c.dependencies
+        .iter()
+        .filter_map(|d| {
+            let cid = match m.remote(d, environment) {
+                Some(cid) => cid,
+                None => deployed.get(d).copied(),
+            }?;
+
+            Some((variable(d), cid.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use candid::Principal;
+
+    use super::{GraphError, closure, exports, graph};
+    use crate::operations::store::{Canister, Manifest};
+
+    fn manifest(deps: &[(&str, &[&str])]) -> Manifest {
+        Manifest {
+            canisters: deps
+                .iter()
+                .map(|(name, deps)| {
+                    let c = Canister {
+                        dependencies: deps.iter().map(|d| d.to_string()).collect(),
+                        ..Default::default()
+                    };
+                    (name.to_string(), c)
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn dependencies_form_a_dag() {
+        let m = manifest(&[
+            ("frontend", &["backend", "ledger"]),
+            ("backend", &["ledger"]),
+            ("ledger", &[]),
+            ("docs", &[]),
+        ]);
+
+        let g = graph(&m).unwrap();
+        let frontend = "frontend".to_string();
+
+        assert_eq!(
+            closure(&g, [&frontend]).into_iter().collect::<Vec<_>>(),
+            ["backend", "frontend", "ledger"]
+        );
+
+        let cid = Principal::from_slice(&[1]);
+        let deployed = BTreeMap::from([("ledger".to_string(), cid)]);
+
+        // The backend was not created yet
+        assert_eq!(
+            exports(&m, "frontend", "local", &deployed),
+            BTreeMap::from([("CANISTER_ID_LEDGER".to_string(), cid.to_string())])
+        );
+    }
+
+    #[test]
+    fn cycles_and_unknown_dependencies_are_reported() {
+        let m = manifest(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"]), ("d", &[])]);
+
+        match graph(&m) {
+            Err(GraphError::Cycle(cycle)) => assert_eq!(cycle, ["a", "b", "c", "a"]),
+            out => panic!("expected a cycle: {out:?}"),
+        }
+
+        let m = manifest(&[("a", &["ghost"])]);
+
+        assert!(matches!(
+            graph(&m),
+            Err(GraphError::Unknown { dependency, .. }) if dependency == "ghost"
+        ));
+    }
+}
@@ -0,0 +1,32 @@
+use std::{path::Path, sync::Arc};
+
+mod cache;
+pub use cache::*;
+
+mod graph;
+pub use graph::*;
+
+mod pipeline;
+pub use pipeline::*;
+
+mod recipe;
+pub use recipe::*;
+
+/// Directory of build outputs, relative to the project root.
+pub const OUTPUT: &str = ".icp/build";
+
+pub struct Initializers {
+    pub build: Box<dyn Fn(&Path) -> Arc<dyn Build>>,
+    pub cache: Box<dyn Fn(&Path) -> Arc<dyn Cache>>,
+    pub process: Box<dyn Fn(&Path) -> Arc<dyn Process>>,
+}
+
+impl Default for Initializers {
+    fn default() -> Self {
+        Self {
+            build: Box::new(|_| unimplemented!()),
+            cache: Box::new(|_| unimplemented!()),
+            process: Box::new(|_| unimplemented!()),
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use mockall::automock;
+use tokio::process::Command;
+use wasm_encoder::{CustomSection, Module, RawSection};
+use wasmparser::{BinaryReaderError, Parser, Payload};
+
+use crate::operations::{
+    build::{BuildError, OUTPUT, recipe::run},
+    store::{MetadataVisibility, Wasm},
+};
+
+/// Custom sections removed by the strip step.
+const DEBUG_SECTIONS: &[&str] = &["name", "sourceMappingURL", "external_debug_info"];
+
+/// One transformation of a built module.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    Shrink,
+    Strip,
+    Metadata {
+        name: String,
+        visibility: MetadataVisibility,
+        content: Vec<u8>,
+    },
+    Gzip,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Shrink => write!(f, "shrink"),
+            Step::Strip => write!(f, "strip"),
+            Step::Metadata {
+                name, visibility, ..
+            } => write!(f, "metadata `{}`", section(name, visibility)),
+            Step::Gzip => write!(f, "gzip"),
+        }
+    }
+}
+
+/// Name of the custom section holding a metadata entry.
+fn section(name: &str, visibility: &MetadataVisibility) -> String {
+    match visibility {
+        MetadataVisibility::Public => format!("icp:public {name}"),
+        MetadataVisibility::Private => format!("icp:private {name}"),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StepError {
+    #[error("not a valid wasm module")]
+    Invalid(#[from] BinaryReaderError),
+
+    #[error(transparent)]
+    Tool(#[from] BuildError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProcessError {
+    #[error("failed to read `{0}`")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("metadata `{0}` needs exactly one of `value` or `file`")]
+    Content(String),
+
+    #[error("{step} step failed")]
+    Step {
+        step: String,
+
+        #[source]
+        err: StepError,
+    },
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Steps declared for a canister, in the order they apply. Gzip always comes last,
+/// nothing can be done to a module once it is compressed.
+pub async fn steps(dir: &Path, wasm: &Wasm) -> Result<Vec<Step>, ProcessError> {
+    let mut steps = vec![];
+
+    if wasm.shrink {
+        steps.push(Step::Shrink);
+    }
+
+    if wasm.strip {
+        steps.push(Step::Strip);
+    }
+
+    if let Some(path) = &wasm.candid {
+        let path = dir.join(path);
+        let content = tokio::fs::read(&path)
+            .await
+            .map_err(|err| ProcessError::Read(path, err))?;
+
+        steps.push(Step::Metadata {
+            name: "candid:service".to_string(),
+            visibility: MetadataVisibility::Public,
+            content,
+        });
+    }
+
+    for m in &wasm.metadata {
+        let content = match (&m.value, &m.file) {
+            (Some(value), None) => value.as_bytes().to_vec(),
+            (None, Some(path)) => {
+                let path = dir.join(path);
+                tokio::fs::read(&path)
+                    .await
+                    .map_err(|err| ProcessError::Read(path, err))?
+            }
+            _ => return Err(ProcessError::Content(m.name.to_owned())),
+        };
+
+        steps.push(Step::Metadata {
+            name: m.name.to_owned(),
+            visibility: m.visibility,
+            content,
+        });
+    }
+
+    if wasm.gzip {
+        steps.push(Step::Gzip);
+    }
+
+    Ok(steps)
+}
+
+/// Copies every section of a module the filter keeps, custom sections by name.
+fn rewrite(module: &[u8], keep: impl Fn(&str) -> bool) -> Result<Module, BinaryReaderError> {
+    let mut out = Module::new();
+
+    for payload in Parser::new(0).parse_all(module) {
+        let payload = payload?;
+
+        if let Payload::CustomSection(r) = &payload {
+            if keep(r.name()) {
+                out.section(&CustomSection {
+                    name: Cow::Borrowed(r.name()),
+                    data: Cow::Borrowed(r.data()),
+                });
+            }
+            continue;
+        }
+
+        if let Some((id, range)) = payload.as_section() {
+            out.section(&RawSection {
+                id,
+                data: &module[range.start as usize..range.end as usize],
+            });
+        }
+    }
+
+    Ok(out)
+}
+
+/// Drops debug information and names, which the IC has no use for.
+pub fn strip(module: &[u8]) -> Result<Vec<u8>, BinaryReaderError> {
+    let out = rewrite(module, |name| {
+        !name.starts_with(".debug") && !DEBUG_SECTIONS.contains(&name)
+    })?;
+
+    Ok(out.finish())
+}
+
+/// Sets a metadata section, replacing any previous one of the same name whatever its visibility.
+pub fn metadata(
+    module: &[u8],
+    name: &str,
+    visibility: &MetadataVisibility,
+    content: &[u8],
+) -> Result<Vec<u8>, BinaryReaderError> {
+    let (public, private) = (
+        section(name, &MetadataVisibility::Public),
+        section(name, &MetadataVisibility::Private),
+    );
+
+    let mut out = rewrite(module, |s| s != public && s != private)?;
+
+    out.section(&CustomSection {
+        name: Cow::Owned(section(name, visibility)),
+        data: Cow::Borrowed(content),
+    });
+
+    Ok(out.finish())
+}
+
+#[automock]
+#[async_trait]
+pub trait Process: Sync + Send {
+    /// Applies the steps in order to the module of a canister and returns the result.
+    async fn process(
+        &self,
+        name: &str,
+        module: Vec<u8>,
+        steps: &[Step],
+    ) -> Result<Vec<u8>, ProcessError>;
+}
+
+pub struct Processor {
+    dir: PathBuf,
+}
+
+impl Processor {
+    pub fn arc(dir: &Path) -> Arc<dyn Process> {
+        Arc::new(Processor {
+            dir: dir.to_owned(),
+        })
+    }
+
+    /// Runs an external tool on the module, through a scratch file in the build directory.
+    async fn external(
+        &self,
+        name: &str,
+        module: &[u8],
+        cmd: impl FnOnce(&Path) -> (Command, PathBuf),
+    ) -> Result<Vec<u8>, StepError> {
+        let scratch = self.dir.join(OUTPUT).join(format!("{name}.step.wasm"));
+
+        tokio::fs::create_dir_all(self.dir.join(OUTPUT)).await?;
+        tokio::fs::write(&scratch, module).await?;
+
+        let (cmd, output) = cmd(&scratch);
+        let out = run(name, cmd).await;
+
+        let module = match out {
+            Ok(()) => tokio::fs::read(&output).await.map_err(StepError::from),
+            Err(err) => Err(err.into()),
+        };
+
+        let _ = tokio::fs::remove_file(&scratch).await;
+        let _ = tokio::fs::remove_file(&output).await;
+
+        module
+    }
+
+    async fn apply(&self, name: &str, module: Vec<u8>, step: &Step) -> Result<Vec<u8>, StepError> {
+        match step {
+            Step::Shrink => {
+                let tool = std::env::var_os("IC_WASM").unwrap_or("ic-wasm".into());
+
+                self.external(name, &module, |path| {
+                    let mut cmd = Command::new(tool);
+                    cmd.arg(path).arg("-o").arg(path).arg("shrink");
+                    (cmd, path.to_owned())
+                })
+                .await
+            }
+
+            Step::Strip => Ok(strip(&module)?),
+
+            Step::Metadata {
+                name,
+                visibility,
+                content,
+            } => Ok(metadata(&module, name, visibility, content)?),
+
+            // Without a timestamp so identical modules compress identically
+            Step::Gzip => {
+                self.external(name, &module, |path| {
+                    let mut cmd = Command::new("gzip");
+                    cmd.args(["-n", "-9", "-f"]).arg(path);
+                    (cmd, path.with_extension("wasm.gz"))
+                })
+                .await
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Process for Processor {
+    async fn process(
+        &self,
+        name: &str,
+        mut module: Vec<u8>,
+        steps: &[Step],
+    ) -> Result<Vec<u8>, ProcessError> {
+        for step in steps {
+            println!("{name} | {step}");
+
+            module = self
+                .apply(name, module, step)
+                .await
+                .map_err(|err| ProcessError::Step {
+                    step: step.to_string(),
+                    err,
+                })?;
+        }
+
+        Ok(module)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasmparser::{Parser, Payload};
+
+    use super::{Processor, Step, metadata, strip};
+    use crate::operations::store::MetadataVisibility;
+
+    fn custom_sections(module: &[u8]) -> Vec<(String, Vec<u8>)> {
+        Parser::new(0)
+            .parse_all(module)
+            .filter_map(|p| match p.unwrap() {
+                Payload::CustomSection(r) => Some((r.name().to_string(), r.data().to_vec())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn strip_and_set_metadata() {
+        let module = wat::parse_str(r#"(module (func $greet (export "greet")))"#).unwrap();
+        assert_eq!(custom_sections(&module)[0].0, "name");
+
+        let module = strip(&module).unwrap();
+        assert!(custom_sections(&module).is_empty());
+
+        // Setting a section again replaces it, even with another visibility
+        let module = metadata(&module, "git:commit", &MetadataVisibility::Public, b"a").unwrap();
+        let module = metadata(&module, "git:commit", &MetadataVisibility::Private, b"b").unwrap();
+
+        assert_eq!(
+            custom_sections(&module),
+            [("icp:private git:commit".to_string(), b"b".to_vec())]
+        );
+        wasmparser::validate(&module).expect("module should remain valid");
+    }
+
+    #[tokio::test]
+    async fn failing_step_is_named() {
+        let dir = tempfile::tempdir().unwrap();
+        let processor = Processor::arc(dir.path());
+
+        let err = processor
+            .process("backend", b"not wasm".to_vec(), &[Step::Strip])
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "strip step failed");
+
+        let module = wat::parse_str("(module)").unwrap();
+        let out = processor
+            .process("backend", module, &[Step::Gzip])
+            .await
+            .unwrap();
+
+        assert_eq!(out[..2], [0x1f, 0x8b]);
+    }
+}
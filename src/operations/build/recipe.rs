@@ -0,0 +1,253 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use mockall::automock;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::Command,
+};
+
+use crate::operations::{build::OUTPUT, store::Recipe};
+
+/// Target rust canisters are compiled for.
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    #[error("failed to run `{0}`")]
+    Spawn(String, #[source] std::io::Error),
+
+    #[error("`{tool}` failed ({status})")]
+    Failed { tool: String, status: String },
+
+    #[error("build did not produce `{0}`")]
+    MissingOutput(PathBuf),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[automock]
+#[async_trait]
+pub trait Build: Sync + Send {
+    /// Runs the recipe of a canister and returns the path of the wasm module it produced.
+    ///
+    /// Output of the underlying tools is streamed, prefixed with the canister name. The
+    /// variables are set for the tools on top of the inherited environment.
+    async fn build(
+        &self,
+        name: &str,
+        recipe: &Recipe,
+        env: &BTreeMap<String, String>,
+    ) -> Result<PathBuf, BuildError>;
+}
+
+pub struct Builder {
+    dir: PathBuf,
+}
+
+impl Builder {
+    pub fn arc(dir: &Path) -> Arc<dyn Build> {
+        Arc::new(Builder {
+            dir: dir.to_owned(),
+        })
+    }
+}
+
+/// Runs a tool to completion, streaming its output prefixed with the canister name.
+pub(super) async fn run(name: &str, mut cmd: Command) -> Result<(), BuildError> {
+    let tool = cmd.as_std().get_program().to_string_lossy().to_string();
+
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| BuildError::Spawn(tool.to_owned(), err))?;
+
+    let (stdout, stderr) = (child.stdout.take(), child.stderr.take());
+
+    let out = async {
+        if let Some(stdout) = stdout {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                println!("{name} | {line}");
+            }
+        }
+    };
+
+    let err = async {
+        if let Some(stderr) = stderr {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                eprintln!("{name} | {line}");
+            }
+        }
+    };
+
+    let (_, _, status) = tokio::join!(out, err, child.wait());
+    let status = status.map_err(|err| BuildError::Spawn(tool.to_owned(), err))?;
+
+    match status.success() {
+        true => Ok(()),
+        false => Err(BuildError::Failed {
+            tool,
+            status: status.to_string(),
+        }),
+    }
+}
+
+#[async_trait]
+impl Build for Builder {
+    async fn build(
+        &self,
+        name: &str,
+        recipe: &Recipe,
+        env: &BTreeMap<String, String>,
+    ) -> Result<PathBuf, BuildError> {
+        let output = match recipe {
+            Recipe::Rust {
+                package,
+                profile,
+                path,
+            } => {
+                let dir = match path {
+                    Some(path) => self.dir.join(path),
+                    None => self.dir.to_owned(),
+                };
+
+                let mut cmd = Command::new("cargo");
+                cmd.current_dir(&dir).envs(env).args([
+                    "build",
+                    "--target",
+                    WASM_TARGET,
+                    "--profile",
+                    profile,
+                    "--package",
+                    package,
+                ]);
+
+                run(name, cmd).await?;
+
+                // Built-in profiles keep their historical directory names
+                let profile = match profile.as_str() {
+                    "dev" | "test" => "debug",
+                    "bench" => "release",
+                    profile => profile,
+                };
+
+                let target = std::env::var_os("CARGO_TARGET_DIR")
+                    .map(|t| dir.join(t))
+                    .unwrap_or(dir.join("target"));
+
+                target
+                    .join(WASM_TARGET)
+                    .join(profile)
+                    .join(format!("{}.wasm", package.replace('-', "_")))
+            }
+
+            Recipe::Motoko { main, args } => {
+                let output = self.dir.join(OUTPUT).join(format!("{name}.moc.wasm"));
+
+                tokio::fs::create_dir_all(self.dir.join(OUTPUT))
+                    .await
+                    .map_err(anyhow::Error::from)?;
+
+                let moc = std::env::var_os("MOC").unwrap_or("moc".into());
+
+                let mut cmd = Command::new(moc);
+                cmd.current_dir(&self.dir)
+                    .envs(env)
+                    .arg(main)
+                    .arg("-o")
+                    .arg(&output)
+                    .args(args);
+
+                run(name, cmd).await?;
+
+                output
+            }
+
+            Recipe::Script { command, output } => {
+                let mut cmd = Command::new("sh");
+                cmd.current_dir(&self.dir)
+                    .envs(env)
+                    .env("CANISTER_NAME", name)
+                    .args(["-c", command]);
+
+                run(name, cmd).await?;
+
+                self.dir.join(output)
+            }
+
+            Recipe::Prebuilt { path } => self.dir.join(path),
+        };
+
+        match output.is_file() {
+            true => Ok(output),
+            false => Err(BuildError::MissingOutput(output)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{BuildError, Builder};
+    use crate::operations::store::Recipe;
+
+    #[tokio::test]
+    async fn script_leaves_module_at_output() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let recipe = Recipe::Script {
+            command:
+                r#"echo building $CANISTER_NAME && printf "\0asm$CANISTER_ID_LEDGER" > out.wasm"#
+                    .to_string(),
+            output: "out.wasm".into(),
+        };
+
+        let out = Builder::arc(dir.path())
+            .build(
+                "backend",
+                &recipe,
+                &BTreeMap::from([("CANISTER_ID_LEDGER".to_string(), "aaaaa-aa".to_string())]),
+            )
+            .await
+            .expect("script should succeed");
+
+        assert_eq!(out, dir.path().join("out.wasm"));
+        assert_eq!(std::fs::read(out).unwrap(), b"\0asmaaaaa-aa");
+    }
+
+    #[tokio::test]
+    async fn failures_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let builder = Builder::arc(dir.path());
+
+        let failing = Recipe::Script {
+            command: "echo broken >&2; exit 3".to_string(),
+            output: "out.wasm".into(),
+        };
+
+        assert!(matches!(
+            builder.build("backend", &failing, &BTreeMap::new()).await,
+            Err(BuildError::Failed { tool, .. }) if tool == "sh"
+        ));
+
+        let missing = Recipe::Prebuilt {
+            path: "vendor/ledger.wasm".into(),
+        };
+
+        assert!(matches!(
+            builder.build("ledger", &missing, &BTreeMap::new()).await,
+            Err(BuildError::MissingOutput(_))
+        ));
+    }
+}
@@ -0,0 +1,773 @@
+use std::{
+    collections::BTreeMap,
+    io::SeekFrom,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use candid::{Nat, Principal};
+use futures::{StreamExt, TryStreamExt, stream};
+use ic_agent::{Agent, AgentError};
+use ic_management_canister_types::{
+    CanisterTimer, OnLowWasmMemoryHookStatus, ReadCanisterSnapshotMetadataResult, SnapshotDataKind,
+    SnapshotDataOffset, SnapshotMetadataGlobal, UploadCanisterSnapshotMetadataArgs,
+};
+use mockall::automock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use crate::operations::management::{self, Management};
+
+/// Snapshot metadata within an archive directory.
+pub const METADATA: &str = "metadata.json";
+
+/// SHA-256 of every file within an archive directory but itself.
+pub const CHECKSUMS: &str = "checksums.json";
+
+/// Directory holding the snapshot's wasm chunk store, one file per chunk.
+const WASM_CHUNKS: &str = "wasm_chunks";
+
+/// Size of the pieces snapshot data is transferred in.
+const DATA_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Number of pieces in flight at once.
+const TRANSFER_CONCURRENCY: usize = 8;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error(transparent)]
+    Agent(AgentError),
+
+    #[error("failed to access `{0}`")]
+    Io(PathBuf, #[source] std::io::Error),
+
+    #[error("checksum mismatch for `{0}`")]
+    ChecksumMismatch(String),
+
+    #[error("no checksum recorded for `{0}`")]
+    ChecksumMissing(String),
+
+    #[error("`{0}` is outside the archive")]
+    OutsideArchive(String),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Transfers snapshots between a canister and a local directory.
+#[automock]
+#[async_trait]
+pub trait Archive: Sync + Send {
+    /// Writes the snapshot to the directory, along with a checksum manifest.
+    async fn download(&self, cid: &Principal, id: &[u8], dir: &Path) -> Result<(), ArchiveError>;
+
+    /// Recreates a snapshot from the directory, returning the new snapshot ID.
+    async fn upload(
+        &self,
+        cid: &Principal,
+        dir: &Path,
+        replace: Option<Vec<u8>>,
+    ) -> Result<Vec<u8>, ArchiveError>;
+}
+
+/// Snapshot metadata as written to disk.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Metadata {
+    pub taken_at_timestamp: u64,
+    pub canister_version: u64,
+    pub wasm_module_size: u64,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    pub globals: Vec<Global>,
+
+    /// Hex encoded.
+    pub certified_data: String,
+
+    pub global_timer: Option<Timer>,
+    pub on_low_wasm_memory_hook_status: Option<HookStatus>,
+
+    /// Hex encoded hashes of the chunks in the wasm chunk store.
+    pub wasm_chunks: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Global {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+
+    /// Decimal encoded.
+    V128(String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Timer {
+    Inactive,
+    Active(u64),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookStatus {
+    ConditionNotSatisfied,
+    Ready,
+    Executed,
+}
+
+impl From<ReadCanisterSnapshotMetadataResult> for Metadata {
+    fn from(m: ReadCanisterSnapshotMetadataResult) -> Self {
+        Metadata {
+            taken_at_timestamp: m.taken_at_timestamp,
+            canister_version: m.canister_version,
+            wasm_module_size: m.wasm_module_size,
+            wasm_memory_size: m.wasm_memory_size,
+            stable_memory_size: m.stable_memory_size,
+            globals: m
+                .globals
+                .into_iter()
+                .map(|g| match g {
+                    SnapshotMetadataGlobal::I32(v) => Global::I32(v),
+                    SnapshotMetadataGlobal::I64(v) => Global::I64(v),
+                    SnapshotMetadataGlobal::F32(v) => Global::F32(v),
+                    SnapshotMetadataGlobal::F64(v) => Global::F64(v),
+                    SnapshotMetadataGlobal::V128(v) => Global::V128(v.0.to_string()),
+                })
+                .collect(),
+            certified_data: hex::encode(m.certified_data),
+            global_timer: m.global_timer.map(|t| match t {
+                CanisterTimer::Inactive => Timer::Inactive,
+                CanisterTimer::Active(v) => Timer::Active(v),
+            }),
+            on_low_wasm_memory_hook_status: m.on_low_wasm_memory_hook_status.map(|s| match s {
+                OnLowWasmMemoryHookStatus::ConditionNotSatisfied => {
+                    HookStatus::ConditionNotSatisfied
+                }
+                OnLowWasmMemoryHookStatus::Ready => HookStatus::Ready,
+                OnLowWasmMemoryHookStatus::Executed => HookStatus::Executed,
+            }),
+            wasm_chunks: m
+                .wasm_chunk_store
+                .into_iter()
+                .map(|c| hex::encode(c.hash))
+                .collect(),
+        }
+    }
+}
+
+impl Metadata {
+    fn upload_args(
+        &self,
+        cid: &Principal,
+        replace: Option<Vec<u8>>,
+    ) -> Result<UploadCanisterSnapshotMetadataArgs, anyhow::Error> {
+        let globals = self
+            .globals
+            .iter()
+            .map(|g| {
+                Ok(match g {
+                    Global::I32(v) => SnapshotMetadataGlobal::I32(*v),
+                    Global::I64(v) => SnapshotMetadataGlobal::I64(*v),
+                    Global::F32(v) => SnapshotMetadataGlobal::F32(*v),
+                    Global::F64(v) => SnapshotMetadataGlobal::F64(*v),
+                    Global::V128(v) => SnapshotMetadataGlobal::V128(Nat(v.parse()?)),
+                })
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+
+        Ok(UploadCanisterSnapshotMetadataArgs {
+            canister_id: *cid,
+            replace_snapshot: replace,
+            wasm_module_size: self.wasm_module_size,
+            globals,
+            wasm_memory_size: self.wasm_memory_size,
+            stable_memory_size: self.stable_memory_size,
+            certified_data: hex::decode(&self.certified_data)?,
+            global_timer: self.global_timer.as_ref().map(|t| match t {
+                Timer::Inactive => CanisterTimer::Inactive,
+                Timer::Active(v) => CanisterTimer::Active(*v),
+            }),
+            on_low_wasm_memory_hook_status: self.on_low_wasm_memory_hook_status.as_ref().map(|s| {
+                match s {
+                    HookStatus::ConditionNotSatisfied => {
+                        OnLowWasmMemoryHookStatus::ConditionNotSatisfied
+                    }
+                    HookStatus::Ready => OnLowWasmMemoryHookStatus::Ready,
+                    HookStatus::Executed => OnLowWasmMemoryHookStatus::Executed,
+                }
+            }),
+        })
+    }
+}
+
+/// Contiguous sections of snapshot data, each stored in its own file.
+#[derive(Clone, Copy)]
+enum Section {
+    WasmModule,
+    WasmMemory,
+    StableMemory,
+}
+
+impl Section {
+    const ALL: [Section; 3] = [
+        Section::WasmModule,
+        Section::WasmMemory,
+        Section::StableMemory,
+    ];
+
+    fn file(&self) -> &'static str {
+        match self {
+            Section::WasmModule => "wasm_module.bin",
+            Section::WasmMemory => "wasm_memory.bin",
+            Section::StableMemory => "stable_memory.bin",
+        }
+    }
+
+    fn size(&self, m: &Metadata) -> u64 {
+        match self {
+            Section::WasmModule => m.wasm_module_size,
+            Section::WasmMemory => m.wasm_memory_size,
+            Section::StableMemory => m.stable_memory_size,
+        }
+    }
+
+    fn read(&self, offset: u64, size: u64) -> SnapshotDataKind {
+        match self {
+            Section::WasmModule => SnapshotDataKind::WasmModule { offset, size },
+            Section::WasmMemory => SnapshotDataKind::WasmMemory { offset, size },
+            Section::StableMemory => SnapshotDataKind::StableMemory { offset, size },
+        }
+    }
+
+    fn write(&self, offset: u64) -> SnapshotDataOffset {
+        match self {
+            Section::WasmModule => SnapshotDataOffset::WasmModule { offset },
+            Section::WasmMemory => SnapshotDataOffset::WasmMemory { offset },
+            Section::StableMemory => SnapshotDataOffset::StableMemory { offset },
+        }
+    }
+}
+
+/// Offsets and sizes of the pieces a section is transferred in.
+fn pieces(size: u64) -> Vec<(u64, u64)> {
+    (0..size)
+        .step_by(DATA_CHUNK_SIZE as usize)
+        .map(|offset| (offset, DATA_CHUNK_SIZE.min(size - offset)))
+        .collect()
+}
+
+pub struct Archiver {
+    mgmt: Arc<dyn Management>,
+}
+
+impl Archiver {
+    pub fn arc(agent: &Agent) -> Arc<dyn Archive> {
+        Arc::new(Archiver {
+            mgmt: management::Client::arc(agent),
+        })
+    }
+
+    /// Fills a snapshot created from the metadata with the data of the archive.
+    async fn upload_data(
+        &self,
+        cid: &Principal,
+        id: &[u8],
+        dir: &Path,
+        metadata: &Metadata,
+    ) -> Result<(), ArchiveError> {
+        for section in Section::ALL {
+            let path = dir.join(section.file());
+
+            stream::iter(pieces(section.size(metadata)))
+                .map(|(offset, size)| {
+                    let (mgmt, cid, id, path) = (self.mgmt.clone(), *cid, id.to_vec(), &path);
+
+                    async move {
+                        let piece = read_at(path, offset, size).await?;
+
+                        mgmt.upload_canister_snapshot_data(&cid, id, section.write(offset), piece)
+                            .await
+                            .map_err(ArchiveError::Agent)
+                    }
+                })
+                .buffer_unordered(TRANSFER_CONCURRENCY)
+                .try_collect::<()>()
+                .await?;
+        }
+
+        for hash in &metadata.wasm_chunks {
+            let chunk = read(&dir.join(self::chunk(hash))).await?;
+
+            self.mgmt
+                .upload_canister_snapshot_data(
+                    cid,
+                    id.to_vec(),
+                    SnapshotDataOffset::WasmChunk,
+                    chunk,
+                )
+                .await
+                .map_err(ArchiveError::Agent)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Archive for Archiver {
+    async fn download(&self, cid: &Principal, id: &[u8], dir: &Path) -> Result<(), ArchiveError> {
+        let chunks_dir = dir.join(WASM_CHUNKS);
+        tokio::fs::create_dir_all(&chunks_dir)
+            .await
+            .map_err(|err| ArchiveError::Io(chunks_dir.to_owned(), err))?;
+
+        let metadata = Metadata::from(
+            self.mgmt
+                .read_canister_snapshot_metadata(cid, id.to_vec())
+                .await
+                .map_err(ArchiveError::Agent)?,
+        );
+
+        let mut checksums = BTreeMap::new();
+
+        for section in Section::ALL {
+            let path = dir.join(section.file());
+
+            let mut f = tokio::fs::File::create(&path)
+                .await
+                .map_err(|err| ArchiveError::Io(path.to_owned(), err))?;
+
+            let mut h = Sha256::new();
+
+            // Pieces are fetched concurrently but written in order
+            let mut stream = stream::iter(pieces(section.size(&metadata)))
+                .map(|(offset, size)| {
+                    let (mgmt, cid, id) = (self.mgmt.clone(), *cid, id.to_vec());
+
+                    async move {
+                        mgmt.read_canister_snapshot_data(&cid, id, section.read(offset, size))
+                            .await
+                    }
+                })
+                .buffered(TRANSFER_CONCURRENCY);
+
+            while let Some(piece) = stream.next().await {
+                let piece = piece.map_err(ArchiveError::Agent)?;
+
+                h.update(&piece);
+
+                f.write_all(&piece)
+                    .await
+                    .map_err(|err| ArchiveError::Io(path.to_owned(), err))?;
+            }
+
+            f.flush()
+                .await
+                .map_err(|err| ArchiveError::Io(path.to_owned(), err))?;
+
+            checksums.insert(section.file().to_string(), hex::encode(h.finalize()));
+        }
+
+        for hash in &metadata.wasm_chunks {
+            let name = chunk(hash);
+
+            let chunk = self
+                .mgmt
+                .read_canister_snapshot_data(
+                    cid,
+                    id.to_vec(),
+                    SnapshotDataKind::WasmChunk {
+                        hash: hex::decode(hash).map_err(anyhow::Error::from)?,
+                    },
+                )
+                .await
+                .map_err(ArchiveError::Agent)?;
+
+            // Chunks are addressed by their hash
+            if hex::encode(Sha256::digest(&chunk)) != *hash {
+                return Err(ArchiveError::ChecksumMismatch(name));
+            }
+
+            write(&dir.join(&name), &chunk).await?;
+            checksums.insert(name, hash.to_owned());
+        }
+
+        let bs = serde_json::to_vec_pretty(&metadata).map_err(anyhow::Error::from)?;
+        write(&dir.join(METADATA), &bs).await?;
+        checksums.insert(METADATA.to_string(), hex::encode(Sha256::digest(&bs)));
+
+        write(
+            &dir.join(CHECKSUMS),
+            &serde_json::to_vec_pretty(&checksums).map_err(anyhow::Error::from)?,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn upload(
+        &self,
+        cid: &Principal,
+        dir: &Path,
+        replace: Option<Vec<u8>>,
+    ) -> Result<Vec<u8>, ArchiveError> {
+        let checksums: BTreeMap<String, String> =
+            serde_json::from_slice(&read(&dir.join(CHECKSUMS)).await?)
+                .map_err(anyhow::Error::from)?;
+
+        // Verify the whole archive before creating anything
+        for (name, sum) in &checksums {
+            if checksum(&within(dir, name)?).await? != *sum {
+                return Err(ArchiveError::ChecksumMismatch(name.to_owned()));
+            }
+        }
+
+        let metadata: Metadata = serde_json::from_slice(&read(&dir.join(METADATA)).await?)
+            .map_err(anyhow::Error::from)?;
+
+        // Nothing is uploaded unverified
+        let required = [METADATA.to_string()]
+            .into_iter()
+            .chain(Section::ALL.iter().map(|s| s.file().to_string()))
+            .chain(metadata.wasm_chunks.iter().map(|h| chunk(h)));
+
+        for name in required {
+            if !checksums.contains_key(&name) {
+                return Err(ArchiveError::ChecksumMissing(name));
+            }
+        }
+
+        let id = self
+            .mgmt
+            .upload_canister_snapshot_metadata(metadata.upload_args(cid, replace)?)
+            .await
+            .map_err(ArchiveError::Agent)?;
+
+        // A partial snapshot would load as if it were whole
+        if let Err(err) = self.upload_data(cid, &id, dir, &metadata).await {
+            let _ = self.mgmt.delete_canister_snapshot(cid, id).await;
+            return Err(err);
+        }
+
+        Ok(id)
+    }
+}
+
+/// Name of a wasm chunk within an archive directory.
+fn chunk(hash: &str) -> String {
+    format!("{WASM_CHUNKS}/{hash}.bin")
+}
+
+/// Path of a file named in an archive, which must not lead out of its directory.
+fn within(dir: &Path, name: &str) -> Result<PathBuf, ArchiveError> {
+    match Path::new(name)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        true => Ok(dir.join(name)),
+        false => Err(ArchiveError::OutsideArchive(name.to_owned())),
+    }
+}
+
+async fn read(path: &Path) -> Result<Vec<u8>, ArchiveError> {
+    tokio::fs::read(path)
+        .await
+        .map_err(|err| ArchiveError::Io(path.to_owned(), err))
+}
+
+async fn write(path: &Path, bs: &[u8]) -> Result<(), ArchiveError> {
+    tokio::fs::write(path, bs)
+        .await
+        .map_err(|err| ArchiveError::Io(path.to_owned(), err))
+}
+
+async fn read_at(path: &Path, offset: u64, size: u64) -> Result<Vec<u8>, ArchiveError> {
+    let io = |err| ArchiveError::Io(path.to_owned(), err);
+
+    let mut f = tokio::fs::File::open(path).await.map_err(io)?;
+    f.seek(SeekFrom::Start(offset)).await.map_err(io)?;
+
+    let mut buf = vec![0; size as usize];
+    f.read_exact(&mut buf).await.map_err(io)?;
+
+    Ok(buf)
+}
+
+/// SHA-256 of a file, without loading it whole.
+async fn checksum(path: &Path) -> Result<String, ArchiveError> {
+    let io = |err| ArchiveError::Io(path.to_owned(), err);
+
+    let mut f = tokio::fs::File::open(path).await.map_err(io)?;
+    let mut h = Sha256::new();
+    let mut buf = vec![0; DATA_CHUNK_SIZE as usize];
+
+    loop {
+        let n = f.read(&mut buf).await.map_err(io)?;
+
+        if n == 0 {
+            break;
+        }
+
+        h.update(&buf[..n]);
+    }
+
+    Ok(hex::encode(h.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use candid::Principal;
+    use ic_agent::AgentError;
+    use ic_management_canister_types::{
+        ChunkHash, ReadCanisterSnapshotMetadataResult, SnapshotDataKind, SnapshotDataOffset,
+        SnapshotSource,
+    };
+    use sha2::{Digest, Sha256};
+
+    use super::{Archive, ArchiveError, Archiver, CHECKSUMS, DATA_CHUNK_SIZE, METADATA, Metadata};
+    use crate::operations::management::MockManagement;
+
+    /// Deterministic contents for a snapshot section.
+    fn data(size: u64, seed: u8) -> Vec<u8> {
+        (0..size).map(|i| (i as u8).wrapping_add(seed)).collect()
+    }
+
+    #[tokio::test]
+    async fn download_then_upload_round_trips() -> Result<(), ArchiveError> {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        let module = data(3, 1);
+        let memory = data(DATA_CHUNK_SIZE + 5, 2);
+        let chunk = b"chunk".to_vec();
+        let chunk_hash = Sha256::digest(&chunk).to_vec();
+
+        // Download
+        let mut mgmt = MockManagement::new();
+
+        let hash = chunk_hash.to_owned();
+        mgmt.expect_read_canister_snapshot_metadata()
+            .once()
+            .returning(move |_, _| {
+                Ok(ReadCanisterSnapshotMetadataResult {
+                    source: SnapshotSource::TakenFromCanister(candid::Reserved),
+                    taken_at_timestamp: 42,
+                    wasm_module_size: 3,
+                    globals: vec![],
+                    wasm_memory_size: DATA_CHUNK_SIZE + 5,
+                    stable_memory_size: 0,
+                    wasm_chunk_store: vec![ChunkHash { hash: hash.clone() }],
+                    canister_version: 7,
+                    certified_data: vec![0xff],
+                    global_timer: None,
+                    on_low_wasm_memory_hook_status: None,
+                })
+            });
+
+        let (m, w, c) = (module.clone(), memory.clone(), chunk.clone());
+        mgmt.expect_read_canister_snapshot_data()
+            .times(4)
+            .returning(move |_, _, kind| {
+                Ok(match kind {
+                    SnapshotDataKind::WasmModule { offset, size } => {
+                        m[offset as usize..(offset + size) as usize].to_vec()
+                    }
+                    SnapshotDataKind::WasmMemory { offset, size } => {
+                        w[offset as usize..(offset + size) as usize].to_vec()
+                    }
+                    SnapshotDataKind::StableMemory { .. } => unreachable!("stable memory is empty"),
+                    SnapshotDataKind::WasmChunk { .. } => c.clone(),
+                })
+            });
+
+        let archiver = Archiver {
+            mgmt: Arc::new(mgmt),
+        };
+
+        archiver
+            .download(&Principal::anonymous(), &[1], dir.path())
+            .await?;
+
+        assert!(dir.path().join(CHECKSUMS).is_file());
+
+        // Upload
+        let mut mgmt = MockManagement::new();
+
+        mgmt.expect_upload_canister_snapshot_metadata()
+            .withf(|args| {
+                args.wasm_module_size == 3
+                    && args.wasm_memory_size == DATA_CHUNK_SIZE + 5
+                    && args.certified_data == [0xff]
+            })
+            .once()
+            .returning(|_| Ok(vec![9]));
+
+        let uploaded = Arc::new(Mutex::new(vec![0u8; (DATA_CHUNK_SIZE + 5) as usize]));
+        let chunks = Arc::new(Mutex::new(vec![]));
+
+        let (u, cs) = (uploaded.clone(), chunks.clone());
+        mgmt.expect_upload_canister_snapshot_data()
+            .withf(|_, id, _, _| id == &[9])
+            .times(4)
+            .returning(move |_, _, kind, piece| {
+                match kind {
+                    SnapshotDataOffset::WasmModule { .. } => assert_eq!(piece, data(3, 1)),
+                    SnapshotDataOffset::WasmMemory { offset } => {
+                        let offset = offset as usize;
+                        u.lock().unwrap()[offset..offset + piece.len()].copy_from_slice(&piece);
+                    }
+                    SnapshotDataOffset::StableMemory { .. } => {
+                        unreachable!("stable memory is empty")
+                    }
+                    SnapshotDataOffset::WasmChunk => cs.lock().unwrap().push(piece),
+                }
+                Ok(())
+            });
+
+        let archiver = Archiver {
+            mgmt: Arc::new(mgmt),
+        };
+
+        let id = archiver
+            .upload(&Principal::anonymous(), dir.path(), None)
+            .await?;
+
+        assert_eq!(id, vec![9]);
+        assert_eq!(*uploaded.lock().unwrap(), memory);
+        assert_eq!(*chunks.lock().unwrap(), vec![chunk]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn upload_rejects_corrupted_archive() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        std::fs::write(dir.path().join("wasm_module.bin"), b"tampered").unwrap();
+        std::fs::write(dir.path().join(CHECKSUMS), r#"{ "wasm_module.bin": "00" }"#).unwrap();
+
+        // Nothing is uploaded
+        let archiver = Archiver {
+            mgmt: Arc::new(MockManagement::new()),
+        };
+
+        let out = archiver
+            .upload(&Principal::anonymous(), dir.path(), None)
+            .await;
+
+        assert!(matches!(out, Err(ArchiveError::ChecksumMismatch(_))));
+    }
+
+    /// Writes an archive of a snapshot holding a 3 byte module, with checksums for `listed`.
+    fn archive(dir: &std::path::Path, listed: &[&str]) {
+        let metadata = Metadata {
+            taken_at_timestamp: 42,
+            canister_version: 7,
+            wasm_module_size: 3,
+            wasm_memory_size: 0,
+            stable_memory_size: 0,
+            globals: vec![],
+            certified_data: String::new(),
+            global_timer: None,
+            on_low_wasm_memory_hook_status: None,
+            wasm_chunks: vec![],
+        };
+
+        let files = [
+            (METADATA, serde_json::to_vec(&metadata).unwrap()),
+            ("wasm_module.bin", data(3, 1)),
+            ("wasm_memory.bin", vec![]),
+            ("stable_memory.bin", vec![]),
+        ];
+
+        let mut checksums = std::collections::BTreeMap::new();
+
+        for (name, bs) in files {
+            std::fs::write(dir.join(name), &bs).unwrap();
+
+            if listed.contains(&name) {
+                checksums.insert(name, hex::encode(Sha256::digest(&bs)));
+            }
+        }
+
+        std::fs::write(dir.join(CHECKSUMS), serde_json::to_vec(&checksums).unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn upload_requires_checksums_within_archive() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let archiver = Archiver {
+            mgmt: Arc::new(MockManagement::new()),
+        };
+
+        // Every file uploaded must be covered
+        archive(
+            dir.path(),
+            &[METADATA, "wasm_module.bin", "wasm_memory.bin"],
+        );
+
+        let out = archiver
+            .upload(&Principal::anonymous(), dir.path(), None)
+            .await;
+
+        assert!(
+            matches!(out, Err(ArchiveError::ChecksumMissing(name)) if name == "stable_memory.bin")
+        );
+
+        // Nothing is read from outside the archive
+        std::fs::write(
+            dir.path().join(CHECKSUMS),
+            r#"{ "../wasm_module.bin": "00" }"#,
+        )
+        .unwrap();
+
+        let out = archiver
+            .upload(&Principal::anonymous(), dir.path(), None)
+            .await;
+
+        assert!(matches!(out, Err(ArchiveError::OutsideArchive(_))));
+    }
+
+    #[tokio::test]
+    async fn failed_upload_deletes_snapshot() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        archive(
+            dir.path(),
+            &[
+                METADATA,
+                "wasm_module.bin",
+                "wasm_memory.bin",
+                "stable_memory.bin",
+            ],
+        );
+
+        let mut mgmt = MockManagement::new();
+
+        mgmt.expect_upload_canister_snapshot_metadata()
+            .once()
+            .returning(|_| Ok(vec![9]));
+
+        mgmt.expect_upload_canister_snapshot_data()
+            .returning(|_, _, _, _| Err(AgentError::MessageError("rejected".to_string())));
+
+        mgmt.expect_delete_canister_snapshot()
+            .withf(|_, id| id == &[9])
+            .once()
+            .returning(|_, _| Ok(()));
+
+        let archiver = Archiver {
+            mgmt: Arc::new(mgmt),
+        };
+
+        let out = archiver
+            .upload(&Principal::anonymous(), dir.path(), None)
+            .await;
+
+        assert!(matches!(out, Err(ArchiveError::Agent(_))));
+    }
+}
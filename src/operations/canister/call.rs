@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use candid::{
+    Principal, TypeEnv,
+    types::{Function, Type},
+};
+use candid_parser::utils::CandidSource;
+use ic_agent::{Agent, AgentError};
+use mockall::automock;
+
+/// Metadata section holding the canister's Candid interface.
+pub const CANDID_SERVICE: &str = "candid:service";
+
+#[derive(Debug, thiserror::Error)]
+pub enum CallError {
+    #[error(transparent)]
+    Agent(AgentError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[automock]
+#[async_trait]
+pub trait Call: Sync + Send {
+    /// Candid interface published in the canister metadata, if any.
+    async fn interface(&self, cid: &Principal) -> Result<Option<String>, CallError>;
+
+    async fn query(&self, cid: &Principal, method: &str, arg: &[u8]) -> Result<Vec<u8>, CallError>;
+
+    async fn update(&self, cid: &Principal, method: &str, arg: &[u8])
+    -> Result<Vec<u8>, CallError>;
+}
+
+pub struct Caller {
+    agent: Agent,
+}
+
+impl Caller {
+    pub fn arc(agent: &Agent) -> Arc<dyn Call> {
+        Arc::new(Caller {
+            agent: agent.to_owned(),
+        })
+    }
+}
+
+#[async_trait]
+impl Call for Caller {
+    async fn interface(&self, cid: &Principal) -> Result<Option<String>, CallError> {
+        let bs = match self
+            .agent
+            .read_state_canister_metadata(*cid, CANDID_SERVICE)
+            .await
+        {
+            Ok(bs) => bs,
+
+            // The section is optional
+            Err(AgentError::LookupPathAbsent(_)) => return Ok(None),
+
+            Err(err) => return Err(CallError::Agent(err)),
+        };
+
+        let s = String::from_utf8(bs).map_err(anyhow::Error::from)?;
+
+        Ok(Some(s))
+    }
+
+    async fn query(&self, cid: &Principal, method: &str, arg: &[u8]) -> Result<Vec<u8>, CallError> {
+        self.agent
+            .query(cid, method)
+            .with_arg(arg)
+            .call()
+            .await
+            .map_err(CallError::Agent)
+    }
+
+    async fn update(
+        &self,
+        cid: &Principal,
+        method: &str,
+        arg: &[u8],
+    ) -> Result<Vec<u8>, CallError> {
+        self.agent
+            .update(cid, method)
+            .with_arg(arg)
+            .call_and_wait()
+            .await
+            .map_err(CallError::Agent)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InterfaceError {
+    #[error("failed to parse candid interface")]
+    Parse(#[source] candid_parser::Error),
+
+    #[error("candid interface does not declare a service")]
+    NoService,
+
+    #[error("method `{0}` is not part of the candid interface")]
+    UnknownMethod(String),
+}
+
+/// A parsed Candid service description.
+pub struct Interface {
+    pub env: TypeEnv,
+    pub service: Type,
+}
+
+impl Interface {
+    pub fn parse(did: &str) -> Result<Self, InterfaceError> {
+        let (env, service) = CandidSource::Text(did)
+            .load()
+            .map_err(InterfaceError::Parse)?;
+
+        let service = service.ok_or(InterfaceError::NoService)?;
+
+        Ok(Interface { env, service })
+    }
+
+    pub fn method<'a>(&'a self, name: &'a str) -> Result<&'a Function, InterfaceError> {
+        self.env
+            .get_method(&self.service, name)
+            .map_err(|_| InterfaceError::UnknownMethod(name.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::types::FuncMode;
+
+    use super::{Interface, InterfaceError};
+
+    #[test]
+    fn interface_methods() {
+        let iface =
+            Interface::parse("service : { greet : (text) -> (text) query; inc : () -> (nat) }")
+                .expect("failed to parse interface");
+
+        let greet = iface.method("greet").expect("missing method");
+        assert_eq!(greet.modes, vec![FuncMode::Query]);
+        assert_eq!(greet.args.len(), 1);
+
+        let inc = iface.method("inc").expect("missing method");
+        assert!(inc.modes.is_empty());
+
+        assert!(matches!(
+            iface.method("nope"),
+            Err(InterfaceError::UnknownMethod(_))
+        ));
+    }
+}
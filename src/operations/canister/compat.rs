@@ -0,0 +1,61 @@
+use candid::types::subtype::{Incompatibility, format_report};
+use candid_parser::utils::{CandidSource, service_compatibility_report};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CompatError {
+    #[error("failed to parse candid interface")]
+    Parse(#[source] candid_parser::Error),
+}
+
+/// Changes in `new` that break clients of `old`, empty if `new` is a subtype of `old`.
+pub fn breaking_changes(old: &str, new: &str) -> Result<Vec<Incompatibility>, CompatError> {
+    service_compatibility_report(CandidSource::Text(new), CandidSource::Text(old))
+        .map_err(CompatError::Parse)
+}
+
+/// Breaking changes grouped by method, one per line.
+pub fn report(changes: &[Incompatibility]) -> String {
+    format_report(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{breaking_changes, report};
+
+    const OLD: &str = indoc! {r#"
+        service : {
+            get : (nat) -> (record { name : text; age : nat }) query;
+            put : (text) -> ();
+        }
+    "#};
+
+    #[test]
+    fn additions_are_compatible() {
+        let new = indoc! {r#"
+            service : {
+                get : (nat) -> (record { name : text; age : nat; email : opt text }) query;
+                put : (text, opt nat) -> ();
+                delete : (nat) -> ();
+            }
+        "#};
+
+        assert!(breaking_changes(OLD, new).unwrap().is_empty());
+    }
+
+    #[test]
+    fn removals_and_narrowing_are_breaking() {
+        let new = indoc! {r#"
+            service : {
+                get : (nat) -> (record { name : text }) query;
+            }
+        "#};
+
+        let changes = breaking_changes(OLD, new).unwrap();
+        let report = report(&changes);
+
+        assert!(report.contains("get"), "{report}");
+        assert!(report.contains("put"), "{report}");
+    }
+}
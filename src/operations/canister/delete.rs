@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use candid::Principal;
+use ic_agent::{Agent, AgentError};
+use mockall::automock;
+
+use crate::operations::management::{self, Management};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteError {
+    #[error(transparent)]
+    Agent(AgentError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[automock]
+#[async_trait]
+pub trait Delete: Sync + Send {
+    async fn delete(&self, cid: &Principal) -> Result<(), DeleteError>;
+}
+
+pub struct Deleter {
+    mgmt: Arc<dyn Management>,
+}
+
+impl Deleter {
+    pub fn arc(agent: &Agent) -> Arc<dyn Delete> {
+        Arc::new(Deleter {
+            mgmt: management::Client::arc(agent),
+        })
+    }
+}
+
+#[async_trait]
+impl Delete for Deleter {
+    async fn delete(&self, cid: &Principal) -> Result<(), DeleteError> {
+        self.mgmt
+            .delete_canister(cid)
+            .await
+            .map_err(DeleteError::Agent)
+    }
+}
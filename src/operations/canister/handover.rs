@@ -0,0 +1,274 @@
+use std::{fmt, sync::Arc};
+
+use candid::Principal;
+
+use crate::operations::canister::{CanisterSettings, Info, InfoError, Settings, SettingsError};
+
+/// Steps of a handover, reported as they begin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Step {
+    Adding,
+    Confirming,
+    Removing,
+    Verifying,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Step::Adding => "adding new controller",
+            Step::Confirming => "confirming new controller",
+            Step::Removing => "removing previous controllers",
+            Step::Verifying => "verifying controllers",
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HandoverError {
+    #[error("failed to read canister status")]
+    Status(#[source] SettingsError),
+
+    #[error("failed to update controllers")]
+    Update(#[source] SettingsError),
+
+    #[error("failed to read controllers from the certified state")]
+    Info(#[source] InfoError),
+
+    #[error("{0} is not a controller of the canister, it cannot be kept as a backup")]
+    Backup(Principal),
+
+    #[error("{0} is not listed as a controller, no controllers were removed")]
+    Unconfirmed(Principal),
+
+    #[error("controllers ended up as {0:?}")]
+    Mismatch(Vec<Principal>),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Moves control of a canister to another principal.
+///
+/// Every step starts from the controllers currently in effect, so running a
+/// handover again after an interruption picks up where it stopped.
+pub struct Handover {
+    pub settings: Arc<dyn Settings>,
+    pub info: Arc<dyn Info>,
+}
+
+impl Handover {
+    /// Leaves `to` and the `keep` backups as the only controllers.
+    ///
+    /// Previous controllers, `caller` included, are only removed once `to`
+    /// shows up in the canister status.
+    pub async fn run(
+        &self,
+        cid: &Principal,
+        to: &Principal,
+        keep: &[Principal],
+        caller: &Principal,
+        report: &(dyn Fn(Step) + Sync),
+    ) -> Result<Vec<Principal>, HandoverError> {
+        let mut target = vec![*to];
+        for p in keep {
+            if !target.contains(p) {
+                target.push(*p);
+            }
+        }
+
+        let controllers = match self.settings.get(cid).await {
+            Ok(settings) => settings.controllers,
+
+            // The caller may have handed over control already, which the
+            // certified state shows to anyone
+            Err(err) => {
+                return match self.info.controllers(cid).await {
+                    Ok(cs) if same(&cs, &target) => Ok(cs),
+                    _ => Err(HandoverError::Status(err)),
+                };
+            }
+        };
+
+        if let Some(p) = keep.iter().find(|p| !controllers.contains(p)) {
+            return Err(HandoverError::Backup(*p));
+        }
+
+        if !controllers.contains(to) {
+            report(Step::Adding);
+            self.set(cid, controllers.iter().chain([to]).copied().collect())
+                .await?;
+        }
+
+        report(Step::Confirming);
+        let confirmed = self
+            .settings
+            .get(cid)
+            .await
+            .map_err(HandoverError::Status)?
+            .controllers;
+
+        if !confirmed.contains(to) {
+            return Err(HandoverError::Unconfirmed(*to));
+        }
+
+        if same(&confirmed, &target) {
+            return Ok(confirmed);
+        }
+
+        report(Step::Removing);
+        self.set(cid, target.clone()).await?;
+
+        report(Step::Verifying);
+        let controllers = match target.contains(caller) {
+            true => {
+                self.settings
+                    .get(cid)
+                    .await
+                    .map_err(HandoverError::Status)?
+                    .controllers
+            }
+
+            // Without control, the status is out of reach
+            false => self
+                .info
+                .controllers(cid)
+                .await
+                .map_err(HandoverError::Info)?,
+        };
+
+        match same(&controllers, &target) {
+            true => Ok(controllers),
+            false => Err(HandoverError::Mismatch(controllers)),
+        }
+    }
+
+    async fn set(&self, cid: &Principal, controllers: Vec<Principal>) -> Result<(), HandoverError> {
+        self.settings
+            .update(
+                cid,
+                CanisterSettings {
+                    controllers: Some(controllers),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(HandoverError::Update)
+    }
+}
+
+fn same(a: &[Principal], b: &[Principal]) -> bool {
+    a.len() == b.len() && a.iter().all(|p| b.contains(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use candid::Principal;
+    use mockall::predicate::{always, eq, function};
+
+    use super::{Handover, HandoverError, Step};
+    use crate::operations::canister::{
+        CanisterSettings, DefiniteCanisterSettings, MockInfo, MockSettings,
+    };
+
+    fn controllers(cs: Vec<Principal>) -> DefiniteCanisterSettings {
+        DefiniteCanisterSettings {
+            controllers: cs,
+            ..Default::default()
+        }
+    }
+
+    fn recorder() -> (Arc<Mutex<Vec<Step>>>, impl Fn(Step) + Sync) {
+        let steps = Arc::new(Mutex::new(vec![]));
+        let s = steps.clone();
+        (steps, move |step| s.lock().unwrap().push(step))
+    }
+
+    #[tokio::test]
+    async fn resumed_handover_skips_adding() {
+        let (me, dao) = (Principal::from_slice(&[1]), Principal::from_slice(&[2]));
+
+        // Interrupted after the new controller was added
+        let current = Arc::new(Mutex::new(vec![me, dao]));
+
+        let mut settings = MockSettings::new();
+        let c = current.clone();
+        settings
+            .expect_get()
+            .times(2)
+            .returning(move |_| Ok(controllers(c.lock().unwrap().clone())));
+        let c = current.clone();
+        settings
+            .expect_update()
+            .with(
+                always(),
+                eq(CanisterSettings {
+                    controllers: Some(vec![dao]),
+                    ..Default::default()
+                }),
+            )
+            .once()
+            .returning(move |_, s| {
+                *c.lock().unwrap() = s.controllers.unwrap();
+                Ok(())
+            });
+
+        let mut info = MockInfo::new();
+        let c = current.clone();
+        info.expect_controllers()
+            .once()
+            .returning(move |_| Ok(c.lock().unwrap().clone()));
+
+        let h = Handover {
+            settings: Arc::new(settings),
+            info: Arc::new(info),
+        };
+
+        let (steps, report) = recorder();
+
+        let out = h
+            .run(&Principal::anonymous(), &dao, &[], &me, &report)
+            .await
+            .expect("handover should complete");
+
+        assert_eq!(out, [dao]);
+        assert_eq!(
+            *steps.lock().unwrap(),
+            [Step::Confirming, Step::Removing, Step::Verifying]
+        );
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_controller_keeps_previous_ones() {
+        let (me, dao) = (Principal::from_slice(&[1]), Principal::from_slice(&[2]));
+
+        let mut settings = MockSettings::new();
+        settings
+            .expect_get()
+            .times(2)
+            .returning(move |_| Ok(controllers(vec![me])));
+
+        // The addition is accepted but does not take effect
+        settings
+            .expect_update()
+            .with(
+                always(),
+                function(move |s: &CanisterSettings| s.controllers == Some(vec![me, dao])),
+            )
+            .once()
+            .returning(|_, _| Ok(()));
+
+        let h = Handover {
+            settings: Arc::new(settings),
+            info: Arc::new(MockInfo::new()),
+        };
+
+        let out = h
+            .run(&Principal::anonymous(), &dao, &[], &me, &|_| {})
+            .await;
+
+        assert!(matches!(out, Err(HandoverError::Unconfirmed(p)) if p == dao));
+    }
+}
@@ -0,0 +1,384 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use candid::{
+    IDLArgs, IDLValue, TypeEnv,
+    types::{Label, value::IDLField},
+};
+use candid_parser::{parse_idl_args, parse_idl_value};
+use ic_agent::Agent;
+use mockall::automock;
+
+use crate::operations::{
+    canister::{Call, CallError, Caller},
+    store,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum HealthError {
+    #[error("invalid health check: {0}")]
+    Invalid(String),
+
+    #[error("health check `{method}` did not pass after {attempts} attempt(s): {last}")]
+    Failed {
+        method: String,
+        attempts: u32,
+        last: String,
+    },
+
+    #[error("health check `{method}` did not pass within {deadline:?}: {last}")]
+    Deadline {
+        method: String,
+        deadline: Duration,
+        last: String,
+    },
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// A health check ready to run against a canister.
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub method: String,
+    pub arg: Vec<u8>,
+    pub expect: Expect,
+    pub attempts: u32,
+    pub interval: Duration,
+    pub deadline: Duration,
+}
+
+/// What a healthy reply looks like.
+#[derive(Clone, Debug)]
+pub enum Expect {
+    /// Any reply will do.
+    Reply,
+
+    /// The reply must equal these values.
+    Equals(IDLArgs),
+
+    /// A value within the reply must (not) equal another.
+    Predicate(Predicate),
+}
+
+/// `<path> == <value>` or `<path> != <value>` over the first reply value.
+///
+/// Path segments are separated by dots, names select record fields or variant
+/// cases, numbers select vector elements or tuple fields. Options are unwrapped.
+#[derive(Clone, Debug)]
+pub struct Predicate {
+    pub path: Vec<String>,
+    pub negate: bool,
+    pub value: IDLValue,
+}
+
+impl Predicate {
+    pub fn parse(s: &str) -> Result<Self, HealthError> {
+        let (path, value, negate) = match (s.split_once("!="), s.split_once("==")) {
+            (Some((path, value)), _) => (path, value, true),
+            (None, Some((path, value))) => (path, value, false),
+            (None, None) => {
+                return Err(HealthError::Invalid(format!(
+                    "predicate `{s}` must compare with `==` or `!=`"
+                )));
+            }
+        };
+
+        let value = parse_idl_value(value.trim())
+            .map_err(|err| HealthError::Invalid(format!("predicate `{s}`: {err}")))?;
+
+        let path = path
+            .trim()
+            .split('.')
+            .filter(|seg| !seg.is_empty())
+            .map(str::to_owned)
+            .collect();
+
+        Ok(Predicate {
+            path,
+            negate,
+            value,
+        })
+    }
+
+    fn eval(&self, reply: &IDLArgs) -> Result<(), String> {
+        let mut v = reply.args.first().ok_or("empty reply")?;
+
+        for seg in &self.path {
+            v = select(unwrap(v), seg).ok_or_else(|| format!("no `{seg}` in {v}"))?;
+        }
+
+        let v = unwrap(v);
+        let equal = matches(v, &self.value);
+
+        match (equal, self.negate) {
+            (true, false) | (false, true) => Ok(()),
+            (false, false) => Err(format!("expected {}, got {v}", self.value)),
+            (true, true) => Err(format!("expected anything but {}", self.value)),
+        }
+    }
+}
+
+impl Check {
+    /// Prepares a check declared in the manifest.
+    pub fn parse(h: &store::Health) -> Result<Self, HealthError> {
+        let arg = parse_idl_args(h.arg.as_deref().unwrap_or("()"))
+            .and_then(|args| Ok(args.to_bytes()?))
+            .map_err(|err| HealthError::Invalid(format!("argument: {err}")))?;
+
+        let expect = match (&h.reply, &h.predicate) {
+            (Some(_), Some(_)) => {
+                return Err(HealthError::Invalid(
+                    "declare either a reply or a predicate, not both".to_string(),
+                ));
+            }
+
+            (Some(reply), None) => Expect::Equals(
+                parse_idl_args(reply)
+                    .map_err(|err| HealthError::Invalid(format!("reply: {err}")))?,
+            ),
+
+            (None, Some(predicate)) => Expect::Predicate(Predicate::parse(predicate)?),
+
+            (None, None) => Expect::Reply,
+        };
+
+        Ok(Check {
+            method: h.method.to_owned(),
+            arg,
+            expect,
+            attempts: h.attempts.max(1),
+            interval: Duration::from_secs(h.interval),
+            deadline: Duration::from_secs(h.deadline),
+        })
+    }
+
+    /// Judges a single reply.
+    fn judge(&self, bs: &[u8]) -> Result<(), String> {
+        let reply = IDLArgs::from_bytes(bs).map_err(|err| err.to_string())?;
+
+        match &self.expect {
+            Expect::Reply => Ok(()),
+
+            Expect::Equals(expected) => (expected.args.len() == reply.args.len()
+                && expected
+                    .args
+                    .iter()
+                    .zip(&reply.args)
+                    .all(|(e, v)| matches(v, e)))
+            .then_some(())
+            .ok_or_else(|| format!("expected {expected}, got {reply}")),
+
+            Expect::Predicate(p) => p.eval(&reply),
+        }
+    }
+}
+
+/// Compares a decoded value with a parsed one, typing the latter after the former.
+fn matches(v: &IDLValue, parsed: &IDLValue) -> bool {
+    parsed
+        .to_owned()
+        .annotate_type(true, &TypeEnv::new(), &v.value_ty())
+        .is_ok_and(|parsed| parsed == *v)
+}
+
+fn unwrap(mut v: &IDLValue) -> &IDLValue {
+    while let IDLValue::Opt(inner) = v {
+        v = inner;
+    }
+    v
+}
+
+fn select<'a>(v: &'a IDLValue, seg: &str) -> Option<&'a IDLValue> {
+    let label = match seg.parse::<u32>() {
+        Ok(n) => Label::Id(n),
+        Err(_) => Label::Named(seg.to_owned()),
+    };
+
+    match v {
+        IDLValue::Record(fields) => fields.iter().find(|f| f.id == label).map(|f| &f.val),
+
+        IDLValue::Variant(variant) => {
+            let IDLField { id, val } = variant.0.as_ref();
+            (*id == label).then_some(val)
+        }
+
+        IDLValue::Vec(vs) => seg.parse::<usize>().ok().and_then(|i| vs.get(i)),
+
+        _ => None,
+    }
+}
+
+/// Probes a canister until it reports healthy.
+#[automock]
+#[async_trait]
+pub trait Health: Sync + Send {
+    async fn check(&self, cid: &candid::Principal, check: &Check) -> Result<(), HealthError>;
+}
+
+pub struct HealthChecker {
+    call: Arc<dyn Call>,
+}
+
+impl HealthChecker {
+    pub fn arc(agent: &Agent) -> Arc<dyn Health> {
+        Arc::new(HealthChecker {
+            call: Caller::arc(agent),
+        })
+    }
+}
+
+#[async_trait]
+impl Health for HealthChecker {
+    async fn check(&self, cid: &candid::Principal, check: &Check) -> Result<(), HealthError> {
+        let mut last = String::from("not checked");
+
+        let probe = async {
+            for attempt in 1..=check.attempts {
+                let out = match self.call.query(cid, &check.method, &check.arg).await {
+                    Ok(bs) => check.judge(&bs),
+
+                    // Rejections are expected while a canister warms up
+                    Err(CallError::Agent(err)) => Err(err.to_string()),
+                    Err(err) => return Err(HealthError::Unexpected(anyhow::Error::new(err))),
+                };
+
+                match out {
+                    Ok(()) => return Ok(()),
+                    Err(err) => last = err,
+                }
+
+                if attempt < check.attempts {
+                    tokio::time::sleep(check.interval).await;
+                }
+            }
+
+            Err(HealthError::Failed {
+                method: check.method.to_owned(),
+                attempts: check.attempts,
+                last: last.to_owned(),
+            })
+        };
+
+        match tokio::time::timeout(check.deadline, probe).await {
+            Ok(out) => out,
+            Err(_) => Err(HealthError::Deadline {
+                method: check.method.to_owned(),
+                deadline: check.deadline,
+                last: last.to_owned(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicU32, Ordering},
+        },
+        time::Duration,
+    };
+
+    use candid::{CandidType, Encode, Principal};
+
+    use super::{Check, Health, HealthChecker, HealthError};
+    use crate::operations::{canister::MockCall, store};
+
+    #[derive(CandidType)]
+    enum Status {
+        #[allow(dead_code)]
+        Starting,
+        Healthy,
+    }
+
+    #[derive(CandidType)]
+    struct Report {
+        status: Status,
+        version: u64,
+    }
+
+    fn declared(predicate: Option<&str>, reply: Option<&str>) -> store::Health {
+        store::Health {
+            method: "health".to_string(),
+            arg: None,
+            reply: reply.map(str::to_owned),
+            predicate: predicate.map(str::to_owned),
+            attempts: 3,
+            interval: 0,
+            deadline: 5,
+        }
+    }
+
+    #[test]
+    fn judge_replies() {
+        let bs = Encode!(&Report {
+            status: Status::Healthy,
+            version: 2,
+        })
+        .unwrap();
+
+        for (predicate, reply, ok) in [
+            (Some("status == variant { Healthy }"), None, true),
+            (Some("status != variant { Healthy }"), None, false),
+            (Some("version == 2"), None, true),
+            (Some("version == 3"), None, false),
+            (Some("missing == 3"), None, false),
+            (
+                None,
+                Some("(record { status = variant { Healthy }; version = 2 })"),
+                true,
+            ),
+            (None, Some("(\"ok\")"), false),
+            (None, None, true),
+        ] {
+            let check = Check::parse(&declared(predicate, reply)).unwrap();
+            assert_eq!(check.judge(&bs).is_ok(), ok, "{predicate:?} {reply:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn check_retries_until_healthy() {
+        let calls = Arc::new(AtomicU32::new(0));
+
+        let mut call = MockCall::new();
+        let c = calls.clone();
+        call.expect_query().times(2).returning(move |_, _, _| {
+            Ok(match c.fetch_add(1, Ordering::SeqCst) {
+                0 => Encode!(&"starting").unwrap(),
+                _ => Encode!(&"ok").unwrap(),
+            })
+        });
+
+        let checker = HealthChecker {
+            call: Arc::new(call),
+        };
+
+        let check = Check::parse(&declared(None, Some("(\"ok\")"))).unwrap();
+
+        checker
+            .check(&Principal::anonymous(), &check)
+            .await
+            .expect("second attempt should pass");
+    }
+
+    #[tokio::test]
+    async fn check_gives_up_after_attempts() {
+        let mut call = MockCall::new();
+        call.expect_query()
+            .times(3)
+            .returning(|_, _, _| Ok(Encode!(&"starting").unwrap()));
+
+        let checker = HealthChecker {
+            call: Arc::new(call),
+        };
+
+        let mut check = Check::parse(&declared(None, Some("(\"ok\")"))).unwrap();
+        check.interval = Duration::from_millis(1);
+
+        let out = checker.check(&Principal::anonymous(), &check).await;
+
+        assert!(matches!(out, Err(HealthError::Failed { attempts: 3, .. })));
+    }
+}
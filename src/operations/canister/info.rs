@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use candid::Principal;
+use ic_agent::{Agent, AgentError};
+use mockall::automock;
+
+#[derive(Debug, thiserror::Error)]
+pub enum InfoError {
+    #[error(transparent)]
+    Agent(AgentError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Reads what the network publishes about any canister, controlled or not.
+#[automock]
+#[async_trait]
+pub trait Info: Sync + Send {
+    /// Controllers, from the certified state tree.
+    async fn controllers(&self, cid: &Principal) -> Result<Vec<Principal>, InfoError>;
+
+    /// Hash of the installed module, from the certified state tree. None if empty.
+    async fn module_hash(&self, cid: &Principal) -> Result<Option<Vec<u8>>, InfoError>;
+
+    /// A metadata section, from the certified state tree. None if absent.
+    async fn metadata(&self, cid: &Principal, name: &str) -> Result<Option<Vec<u8>>, InfoError>;
+}
+
+pub struct Inspector {
+    agent: Agent,
+}
+
+impl Inspector {
+    pub fn arc(agent: &Agent) -> Arc<dyn Info> {
+        Arc::new(Inspector {
+            agent: agent.to_owned(),
+        })
+    }
+}
+
+/// Absent paths are how the state tree reports missing optional entries.
+fn optional(out: Result<Vec<u8>, AgentError>) -> Result<Option<Vec<u8>>, InfoError> {
+    match out {
+        Ok(bs) => Ok(Some(bs)),
+        Err(AgentError::LookupPathAbsent(_)) => Ok(None),
+        Err(err) => Err(InfoError::Agent(err)),
+    }
+}
+
+#[async_trait]
+impl Info for Inspector {
+    async fn controllers(&self, cid: &Principal) -> Result<Vec<Principal>, InfoError> {
+        self.agent
+            .read_state_canister_controllers(*cid)
+            .await
+            .map_err(InfoError::Agent)
+    }
+
+    async fn module_hash(&self, cid: &Principal) -> Result<Option<Vec<u8>>, InfoError> {
+        optional(self.agent.read_state_canister_module_hash(*cid).await)
+    }
+
+    async fn metadata(&self, cid: &Principal, name: &str) -> Result<Option<Vec<u8>>, InfoError> {
+        optional(self.agent.read_state_canister_metadata(*cid, name).await)
+    }
+}
@@ -0,0 +1,344 @@
+use std::{collections::HashSet, sync::Arc};
+
+use async_trait::async_trait;
+use candid::Principal;
+use futures::{StreamExt, TryStreamExt, stream};
+use ic_agent::{Agent, AgentError};
+use mockall::automock;
+use sha2::{Digest, Sha256};
+
+pub use ic_utils::interfaces::management_canister::builders::CanisterInstallMode;
+
+use crate::operations::management::{self, Management};
+
+// Ingress messages are capped at 2MiB, keep some room for the message envelope
+const CHUNK_CUTOFF: usize = (1.85 * 1024. * 1024.) as usize;
+
+// Maximum size of a single chunk accepted by `upload_chunk`
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+// Number of chunks uploaded at the same time
+const UPLOAD_CONCURRENCY: usize = 8;
+
+#[derive(Debug, thiserror::Error)]
+pub enum InstallError {
+    #[error("failed to install code")]
+    InstallCode(#[source] AgentError),
+
+    #[error("failed to list stored chunks")]
+    StoredChunks(#[source] AgentError),
+
+    #[error("failed to upload chunk")]
+    UploadChunk(#[source] AgentError),
+
+    #[error("uploaded chunk hash does not match the local chunk hash")]
+    ChunkHashMismatch,
+
+    #[error("failed to clear chunk store")]
+    ClearChunkStore(#[source] AgentError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[automock]
+#[async_trait]
+pub trait Install: Sync + Send {
+    async fn install(
+        &self,
+        cid: &Principal,
+        mode: CanisterInstallMode,
+        wasm: &[u8],
+        arg: &[u8],
+    ) -> Result<(), InstallError>;
+}
+
+pub struct Installer {
+    mgmt: Arc<dyn Management>,
+}
+
+impl Installer {
+    pub fn arc(agent: &Agent) -> Arc<dyn Install> {
+        Arc::new(Installer {
+            mgmt: management::Client::arc(agent),
+        })
+    }
+
+    /// Uploads the module to the canister's chunk store, skipping chunks that are
+    /// already stored, and installs it from there.
+    async fn install_chunked(
+        &self,
+        cid: &Principal,
+        mode: CanisterInstallMode,
+        wasm: &[u8],
+        arg: &[u8],
+    ) -> Result<(), InstallError> {
+        let stored: HashSet<Vec<u8>> = self
+            .mgmt
+            .stored_chunks(cid)
+            .await
+            .map_err(InstallError::StoredChunks)?
+            .into_iter()
+            .collect();
+
+        let chunks: Vec<(Vec<u8>, &[u8])> = wasm
+            .chunks(CHUNK_SIZE)
+            .map(|chunk| (Sha256::digest(chunk).to_vec(), chunk))
+            .collect();
+
+        // Only upload chunks the canister does not already have
+        let pending: Vec<(Vec<u8>, Vec<u8>)> = chunks
+            .iter()
+            .filter(|(hash, _)| !stored.contains(hash))
+            .map(|(hash, chunk)| (hash.clone(), chunk.to_vec()))
+            .collect();
+
+        stream::iter(pending)
+            .map(|(hash, chunk)| async move {
+                let out = self
+                    .mgmt
+                    .upload_chunk(cid, chunk)
+                    .await
+                    .map_err(InstallError::UploadChunk)?;
+
+                if out != hash {
+                    return Err(InstallError::ChunkHashMismatch);
+                }
+
+                Ok(())
+            })
+            .buffer_unordered(UPLOAD_CONCURRENCY)
+            .try_collect::<()>()
+            .await?;
+
+        self.mgmt
+            .install_chunked_code(
+                cid,
+                mode,
+                chunks.into_iter().map(|(hash, _)| hash).collect(),
+                Sha256::digest(wasm).to_vec(),
+                arg.to_vec(),
+            )
+            .await
+            .map_err(InstallError::InstallCode)
+    }
+}
+
+#[async_trait]
+impl Install for Installer {
+    async fn install(
+        &self,
+        cid: &Principal,
+        mode: CanisterInstallMode,
+        wasm: &[u8],
+        arg: &[u8],
+    ) -> Result<(), InstallError> {
+        // Small modules fit in a single message
+        if wasm.len() + arg.len() < CHUNK_CUTOFF {
+            return self
+                .mgmt
+                .install_code(cid, mode, wasm.to_vec(), arg.to_vec())
+                .await
+                .map_err(InstallError::InstallCode);
+        }
+
+        let out = self.install_chunked(cid, mode, wasm, arg).await;
+
+        // Leave no chunks behind, whether the install succeeded or not
+        let cleared = self
+            .mgmt
+            .clear_chunk_store(cid)
+            .await
+            .map_err(InstallError::ClearChunkStore);
+
+        out.and(cleared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::Error;
+    use candid::Principal;
+    use ic_agent::AgentError;
+    use mockall::predicate::{always, eq};
+    use sha2::{Digest, Sha256};
+
+    use crate::operations::{
+        canister::{CanisterInstallMode, Install, InstallError, Installer},
+        management::MockManagement,
+    };
+
+    use super::{CHUNK_CUTOFF, CHUNK_SIZE};
+
+    fn hash(v: &[u8]) -> Vec<u8> {
+        Sha256::digest(v).to_vec()
+    }
+
+    #[tokio::test]
+    async fn small_module_is_installed_in_a_single_message() -> Result<(), Error> {
+        let wasm = vec![0; 1024];
+
+        let mut mgmt = MockManagement::new();
+        mgmt.expect_install_code()
+            .with(
+                eq(Principal::anonymous()),
+                eq(CanisterInstallMode::Install),
+                eq(wasm.clone()),
+                eq(vec![]),
+            )
+            .once()
+            .returning(|_, _, _, _| Ok(()));
+
+        let installer = Installer {
+            mgmt: Arc::new(mgmt),
+        };
+
+        installer
+            .install(
+                &Principal::anonymous(),
+                CanisterInstallMode::Install,
+                &wasm,
+                &[],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn large_module_skips_stored_chunks() -> Result<(), Error> {
+        // Three chunks, the first of which is already stored
+        let wasm: Vec<u8> = (0..CHUNK_CUTOFF + CHUNK_SIZE)
+            .map(|i| (i / CHUNK_SIZE) as u8)
+            .collect();
+
+        let chunks: Vec<Vec<u8>> = wasm.chunks(CHUNK_SIZE).map(<[u8]>::to_vec).collect();
+        let hashes: Vec<Vec<u8>> = chunks.iter().map(|c| hash(c)).collect();
+
+        let uploaded = Arc::new(Mutex::new(vec![]));
+
+        let mut mgmt = MockManagement::new();
+
+        mgmt.expect_stored_chunks().once().returning({
+            let stored = hashes[0].clone();
+            move |_| Ok(vec![stored.clone()])
+        });
+
+        mgmt.expect_upload_chunk().times(2).returning({
+            let uploaded = uploaded.clone();
+            move |_, chunk| {
+                let h = hash(&chunk);
+                uploaded.lock().unwrap().push(h.clone());
+                Ok(h)
+            }
+        });
+
+        mgmt.expect_install_chunked_code()
+            .with(
+                eq(Principal::anonymous()),
+                eq(CanisterInstallMode::Upgrade(None)),
+                eq(hashes.clone()),
+                eq(hash(&wasm)),
+                always(),
+            )
+            .once()
+            .returning(|_, _, _, _, _| Ok(()));
+
+        mgmt.expect_clear_chunk_store().once().returning(|_| Ok(()));
+
+        let installer = Installer {
+            mgmt: Arc::new(mgmt),
+        };
+
+        installer
+            .install(
+                &Principal::anonymous(),
+                CanisterInstallMode::Upgrade(None),
+                &wasm,
+                &[],
+            )
+            .await?;
+
+        let mut uploaded = uploaded.lock().unwrap().clone();
+        uploaded.sort();
+
+        let mut expected = hashes[1..].to_vec();
+        expected.sort();
+
+        assert_eq!(uploaded, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn chunk_store_is_cleared_on_failure() -> Result<(), Error> {
+        let wasm = vec![0; CHUNK_CUTOFF];
+
+        let mut mgmt = MockManagement::new();
+
+        mgmt.expect_stored_chunks().returning(|_| Ok(vec![]));
+
+        mgmt.expect_upload_chunk()
+            .returning(|_, _| Err(AgentError::MessageError("boom".to_string())));
+
+        mgmt.expect_install_chunked_code().never();
+
+        mgmt.expect_clear_chunk_store().once().returning(|_| Ok(()));
+
+        let installer = Installer {
+            mgmt: Arc::new(mgmt),
+        };
+
+        let out = installer
+            .install(
+                &Principal::anonymous(),
+                CanisterInstallMode::Install,
+                &wasm,
+                &[],
+            )
+            .await;
+
+        match out {
+            Err(InstallError::UploadChunk(_)) => {}
+            _ => panic!("expected upload failure: {out:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stored_chunks_failure_is_reported() -> Result<(), Error> {
+        let wasm = vec![0; CHUNK_CUTOFF];
+
+        let mut mgmt = MockManagement::new();
+
+        mgmt.expect_stored_chunks()
+            .returning(|_| Err(AgentError::MessageError("boom".to_string())));
+
+        mgmt.expect_upload_chunk().never();
+
+        mgmt.expect_clear_chunk_store().once().returning(|_| Ok(()));
+
+        let installer = Installer {
+            mgmt: Arc::new(mgmt),
+        };
+
+        let out = installer
+            .install(
+                &Principal::anonymous(),
+                CanisterInstallMode::Install,
+                &wasm,
+                &[],
+            )
+            .await;
+
+        match out {
+            Err(InstallError::StoredChunks(_)) => {}
+            _ => panic!("expected stored chunks failure: {out:?}"),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use candid::Principal;
+use ic_agent::{Agent, AgentError};
+use mockall::automock;
+
+pub use ic_management_canister_types::CanisterLogRecord;
+
+use crate::operations::management::{self, Management};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogsError {
+    #[error(transparent)]
+    Agent(AgentError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[automock]
+#[async_trait]
+pub trait Logs: Sync + Send {
+    /// Log records currently retained by the canister, oldest first.
+    async fn fetch(&self, cid: &Principal) -> Result<Vec<CanisterLogRecord>, LogsError>;
+}
+
+pub struct LogReader {
+    mgmt: Arc<dyn Management>,
+}
+
+impl LogReader {
+    pub fn arc(agent: &Agent) -> Arc<dyn Logs> {
+        Arc::new(LogReader {
+            mgmt: management::Client::arc(agent),
+        })
+    }
+}
+
+#[async_trait]
+impl Logs for LogReader {
+    async fn fetch(&self, cid: &Principal) -> Result<Vec<CanisterLogRecord>, LogsError> {
+        self.mgmt
+            .fetch_canister_logs(cid)
+            .await
+            .map_err(LogsError::Agent)
+    }
+}
@@ -2,22 +2,91 @@ use std::sync::Arc;
 
 use ic_agent::Agent;
 
+mod archive;
+pub use archive::*;
+
+mod call;
+pub use call::*;
+
+mod compat;
+pub use compat::*;
+
+mod delete;
+pub use delete::*;
+
+mod handover;
+pub use handover::*;
+
+mod health;
+pub use health::*;
+
+mod info;
+pub use info::*;
+
+mod install;
+pub use install::*;
+
+mod logs;
+pub use logs::*;
+
+mod orchestrate;
+pub use orchestrate::*;
+
+mod settings;
+pub use settings::*;
+
+mod snapshot;
+pub use snapshot::*;
+
 mod start;
 pub use start::*;
 
+mod status;
+pub use status::*;
+
 mod stop;
 pub use stop::*;
 
+mod topup;
+pub use topup::*;
+
+mod withdraw;
+pub use withdraw::*;
+
 pub struct Initializers {
+    pub archive: Box<dyn Fn(&Agent) -> Arc<dyn Archive>>,
+    pub call: Box<dyn Fn(&Agent) -> Arc<dyn Call>>,
+    pub delete: Box<dyn Fn(&Agent) -> Arc<dyn Delete>>,
+    pub health: Box<dyn Fn(&Agent) -> Arc<dyn Health>>,
+    pub info: Box<dyn Fn(&Agent) -> Arc<dyn Info>>,
+    pub install: Box<dyn Fn(&Agent) -> Arc<dyn Install>>,
+    pub logs: Box<dyn Fn(&Agent) -> Arc<dyn Logs>>,
+    pub settings: Box<dyn Fn(&Agent) -> Arc<dyn Settings>>,
+    pub snapshot: Box<dyn Fn(&Agent) -> Arc<dyn Snapshots>>,
     pub start: Box<dyn Fn(&Agent) -> Arc<dyn Start>>,
+    pub status: Box<dyn Fn(&Agent) -> Arc<dyn Status>>,
     pub stop: Box<dyn Fn(&Agent) -> Arc<dyn Stop>>,
+    pub topup: Box<dyn Fn(&Agent) -> Arc<dyn TopUp>>,
+    pub withdraw: Box<dyn Fn(&Agent) -> Arc<dyn Withdraw>>,
 }
 
 impl Default for Initializers {
     fn default() -> Self {
         Self {
+            archive: Box::new(|_| unimplemented!()),
+            call: Box::new(|_| unimplemented!()),
+            delete: Box::new(|_| unimplemented!()),
+            health: Box::new(|_| unimplemented!()),
+            info: Box::new(|_| unimplemented!()),
+            install: Box::new(|_| unimplemented!()),
+            logs: Box::new(|_| unimplemented!()),
+            settings: Box::new(|_| unimplemented!()),
+            snapshot: Box::new(|_| unimplemented!()),
             start: Box::new(|_| unimplemented!()),
+            status: Box::new(|_| unimplemented!()),
             stop: Box::new(|_| unimplemented!()),
+            topup: Box::new(|_| unimplemented!()),
+            withdraw: Box::new(|_| unimplemented!()),
         }
     }
 }
@@ -0,0 +1,332 @@
+use std::{fmt, sync::Arc};
+
+use candid::Principal;
+
+use crate::operations::canister::{
+    CanisterInstallMode, Check, Health, HealthError, Install, InstallError, Start, StartError,
+    Stop, StopError,
+};
+
+/// Steps of a lifecycle change, reported as they begin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Stopping,
+    Installing,
+    Starting,
+    Checking,
+    RollingBack,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Phase::Stopping => "stopping",
+            Phase::Installing => "installing",
+            Phase::Starting => "starting",
+            Phase::Checking => "checking health",
+            Phase::RollingBack => "starting again after failure",
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OrchestrateError {
+    #[error("failed to stop canister")]
+    Stop(#[source] StopError),
+
+    #[error("failed to install code")]
+    Install(#[source] InstallError),
+
+    #[error("failed to start canister")]
+    Start(#[source] StartError),
+
+    #[error("canister was started but is not healthy")]
+    Health(#[source] HealthError),
+
+    #[error("the canister was started again after a failure")]
+    RolledBack {
+        #[source]
+        cause: Box<OrchestrateError>,
+    },
+
+    #[error("{cause}, and failed to start the canister again")]
+    Rollback {
+        cause: Box<OrchestrateError>,
+
+        #[source]
+        start: StartError,
+    },
+}
+
+/// Code to install, as passed to [`Install::install`].
+pub struct Code<'a> {
+    pub mode: CanisterInstallMode,
+    pub wasm: &'a [u8],
+    pub arg: &'a [u8],
+}
+
+/// Composes stop, install and start so that a canister is left running
+/// whatever step fails.
+pub struct Orchestrator {
+    pub stop: Arc<dyn Stop>,
+    pub start: Arc<dyn Start>,
+    pub health: Arc<dyn Health>,
+}
+
+impl Orchestrator {
+    /// Stops and starts the canister again.
+    pub async fn restart(
+        &self,
+        cid: &Principal,
+        check: Option<&Check>,
+        report: &(dyn Fn(Phase) + Sync),
+    ) -> Result<(), OrchestrateError> {
+        self.stopped(cid, check, report, async || Ok(())).await
+    }
+
+    /// Starts the canister and, if given, waits for it to pass its health check.
+    pub async fn start(
+        &self,
+        cid: &Principal,
+        check: Option<&Check>,
+        report: &(dyn Fn(Phase) + Sync),
+    ) -> Result<(), OrchestrateError> {
+        report(Phase::Starting);
+        self.start
+            .start(cid)
+            .await
+            .map_err(OrchestrateError::Start)?;
+
+        if let Some(check) = check {
+            report(Phase::Checking);
+            self.health
+                .check(cid, check)
+                .await
+                .map_err(OrchestrateError::Health)?;
+        }
+
+        Ok(())
+    }
+
+    /// Installs code into the stopped canister and starts it again.
+    pub async fn install(
+        &self,
+        installer: &dyn Install,
+        cid: &Principal,
+        code: Code<'_>,
+        check: Option<&Check>,
+        report: &(dyn Fn(Phase) + Sync),
+    ) -> Result<(), OrchestrateError> {
+        self.stopped(cid, check, report, async || {
+            report(Phase::Installing);
+
+            installer
+                .install(cid, code.mode, code.wasm, code.arg)
+                .await
+                .map_err(OrchestrateError::Install)
+        })
+        .await
+    }
+
+    /// Runs `f` while the canister is stopped, starting it again afterwards.
+    async fn stopped(
+        &self,
+        cid: &Principal,
+        check: Option<&Check>,
+        report: &(dyn Fn(Phase) + Sync),
+        f: impl AsyncFnOnce() -> Result<(), OrchestrateError>,
+    ) -> Result<(), OrchestrateError> {
+        report(Phase::Stopping);
+
+        // A failed stop can leave the canister stopping, which a start reverts
+        let out = match self.stop.stop(cid).await {
+            Ok(()) => f().await,
+            Err(err) => Err(OrchestrateError::Stop(err)),
+        };
+
+        match out {
+            Ok(()) => self.start(cid, check, report).await,
+
+            Err(cause) => {
+                report(Phase::RollingBack);
+
+                match self.start.start(cid).await {
+                    Ok(()) => Err(OrchestrateError::RolledBack {
+                        cause: Box::new(cause),
+                    }),
+                    Err(start) => Err(OrchestrateError::Rollback {
+                        cause: Box::new(cause),
+                        start,
+                    }),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use candid::Principal;
+    use mockall::{Sequence, predicate::eq};
+
+    use super::{Code, OrchestrateError, Orchestrator, Phase};
+    use crate::operations::canister::{
+        CanisterInstallMode, InstallError, MockHealth, MockInstall, MockStart, MockStop, StartError,
+    };
+
+    fn recorder() -> (Arc<Mutex<Vec<Phase>>>, impl Fn(Phase) + Sync) {
+        let phases = Arc::new(Mutex::new(vec![]));
+        let p = phases.clone();
+        (phases, move |phase| p.lock().unwrap().push(phase))
+    }
+
+    #[tokio::test]
+    async fn upgrade_runs_phases_in_order() {
+        let cid = Principal::anonymous();
+        let mut seq = Sequence::new();
+
+        let mut stop = MockStop::new();
+        stop.expect_stop()
+            .with(eq(cid))
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+
+        let mut install = MockInstall::new();
+        install
+            .expect_install()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_, _, _, _| Ok(()));
+
+        let mut start = MockStart::new();
+        start
+            .expect_start()
+            .with(eq(cid))
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+
+        let o = Orchestrator {
+            stop: Arc::new(stop),
+            start: Arc::new(start),
+            health: Arc::new(MockHealth::new()),
+        };
+
+        let (phases, report) = recorder();
+
+        o.install(
+            &install,
+            &cid,
+            Code {
+                mode: CanisterInstallMode::Upgrade(None),
+                wasm: b"\0asm",
+                arg: &[],
+            },
+            None,
+            &report,
+        )
+        .await
+        .expect("upgrade should succeed");
+
+        assert_eq!(
+            *phases.lock().unwrap(),
+            [Phase::Stopping, Phase::Installing, Phase::Starting]
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_install_starts_canister_again() {
+        let mut stop = MockStop::new();
+        stop.expect_stop().once().returning(|_| Ok(()));
+
+        let mut install = MockInstall::new();
+        install
+            .expect_install()
+            .once()
+            .returning(|_, _, _, _| Err(InstallError::Unexpected(anyhow::anyhow!("trapped"))));
+
+        let mut start = MockStart::new();
+        start.expect_start().once().returning(|_| Ok(()));
+
+        let o = Orchestrator {
+            stop: Arc::new(stop),
+            start: Arc::new(start),
+            health: Arc::new(MockHealth::new()),
+        };
+
+        let (phases, report) = recorder();
+
+        let out = o
+            .install(
+                &install,
+                &Principal::anonymous(),
+                Code {
+                    mode: CanisterInstallMode::Upgrade(None),
+                    wasm: b"\0asm",
+                    arg: &[],
+                },
+                None,
+                &report,
+            )
+            .await;
+
+        match out {
+            Err(OrchestrateError::RolledBack { cause }) => {
+                assert!(matches!(*cause, OrchestrateError::Install(_)))
+            }
+            out => panic!("expected the canister to be started again: {out:?}"),
+        }
+        assert_eq!(
+            *phases.lock().unwrap(),
+            [Phase::Stopping, Phase::Installing, Phase::RollingBack]
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_rollback_reports_both_errors() {
+        let mut stop = MockStop::new();
+        stop.expect_stop().once().returning(|_| Ok(()));
+
+        let mut install = MockInstall::new();
+        install
+            .expect_install()
+            .once()
+            .returning(|_, _, _, _| Err(InstallError::Unexpected(anyhow::anyhow!("trapped"))));
+
+        let mut start = MockStart::new();
+        start
+            .expect_start()
+            .once()
+            .returning(|_| Err(StartError::Unexpected(anyhow::anyhow!("unreachable"))));
+
+        let o = Orchestrator {
+            stop: Arc::new(stop),
+            start: Arc::new(start),
+            health: Arc::new(MockHealth::new()),
+        };
+
+        let out = o
+            .install(
+                &install,
+                &Principal::anonymous(),
+                Code {
+                    mode: CanisterInstallMode::Upgrade(None),
+                    wasm: b"\0asm",
+                    arg: &[],
+                },
+                None,
+                &|_| {},
+            )
+            .await;
+
+        match out {
+            Err(OrchestrateError::Rollback { cause, .. }) => {
+                assert!(matches!(*cause, OrchestrateError::Install(_)))
+            }
+            _ => panic!("expected a failed rollback"),
+        }
+    }
+}
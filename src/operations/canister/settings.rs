@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use candid::Principal;
+use ic_agent::{Agent, AgentError};
+use mockall::automock;
+
+pub use ic_management_canister_types::{
+    CanisterSettings, DefiniteCanisterSettings, EnvironmentVariable, LogVisibility,
+};
+
+use crate::operations::management::{self, Management};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error(transparent)]
+    Agent(AgentError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[automock]
+#[async_trait]
+pub trait Settings: Sync + Send {
+    /// Settings currently in effect.
+    async fn get(&self, cid: &Principal) -> Result<DefiniteCanisterSettings, SettingsError>;
+
+    /// Applies the settings that are set, leaving the others unchanged.
+    async fn update(
+        &self,
+        cid: &Principal,
+        settings: CanisterSettings,
+    ) -> Result<(), SettingsError>;
+}
+
+pub struct Configurator {
+    mgmt: Arc<dyn Management>,
+}
+
+impl Configurator {
+    pub fn arc(agent: &Agent) -> Arc<dyn Settings> {
+        Arc::new(Configurator {
+            mgmt: management::Client::arc(agent),
+        })
+    }
+}
+
+#[async_trait]
+impl Settings for Configurator {
+    async fn get(&self, cid: &Principal) -> Result<DefiniteCanisterSettings, SettingsError> {
+        let status = self
+            .mgmt
+            .canister_status(cid)
+            .await
+            .map_err(SettingsError::Agent)?;
+
+        Ok(status.settings)
+    }
+
+    async fn update(
+        &self,
+        cid: &Principal,
+        settings: CanisterSettings,
+    ) -> Result<(), SettingsError> {
+        self.mgmt
+            .update_settings(cid, settings)
+            .await
+            .map_err(SettingsError::Agent)
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use candid::Principal;
+use ic_agent::{Agent, AgentError};
+use mockall::automock;
+
+pub use ic_management_canister_types::Snapshot;
+
+use crate::operations::management::{self, Management};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    Agent(AgentError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Snapshots of a canister's state, the canister should be stopped while
+/// snapshots are taken or loaded.
+#[automock]
+#[async_trait]
+pub trait Snapshots: Sync + Send {
+    async fn create(
+        &self,
+        cid: &Principal,
+        replace: Option<Vec<u8>>,
+    ) -> Result<Snapshot, SnapshotError>;
+
+    async fn list(&self, cid: &Principal) -> Result<Vec<Snapshot>, SnapshotError>;
+
+    async fn load(&self, cid: &Principal, id: &[u8]) -> Result<(), SnapshotError>;
+
+    async fn delete(&self, cid: &Principal, id: &[u8]) -> Result<(), SnapshotError>;
+}
+
+pub struct Snapshotter {
+    mgmt: Arc<dyn Management>,
+}
+
+impl Snapshotter {
+    pub fn arc(agent: &Agent) -> Arc<dyn Snapshots> {
+        Arc::new(Snapshotter {
+            mgmt: management::Client::arc(agent),
+        })
+    }
+}
+
+#[async_trait]
+impl Snapshots for Snapshotter {
+    async fn create(
+        &self,
+        cid: &Principal,
+        replace: Option<Vec<u8>>,
+    ) -> Result<Snapshot, SnapshotError> {
+        self.mgmt
+            .take_canister_snapshot(cid, replace)
+            .await
+            .map_err(SnapshotError::Agent)
+    }
+
+    async fn list(&self, cid: &Principal) -> Result<Vec<Snapshot>, SnapshotError> {
+        self.mgmt
+            .list_canister_snapshots(cid)
+            .await
+            .map_err(SnapshotError::Agent)
+    }
+
+    async fn load(&self, cid: &Principal, id: &[u8]) -> Result<(), SnapshotError> {
+        self.mgmt
+            .load_canister_snapshot(cid, id.to_vec())
+            .await
+            .map_err(SnapshotError::Agent)
+    }
+
+    async fn delete(&self, cid: &Principal, id: &[u8]) -> Result<(), SnapshotError> {
+        self.mgmt
+            .delete_canister_snapshot(cid, id.to_vec())
+            .await
+            .map_err(SnapshotError::Agent)
+    }
+}
@@ -2,11 +2,16 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use candid::Principal;
-use ic_agent::Agent;
+use ic_agent::{Agent, AgentError};
 use mockall::automock;
 
+use crate::operations::management::{self, Management};
+
 #[derive(Debug, thiserror::Error)]
 pub enum StartError {
+    #[error(transparent)]
+    Agent(AgentError),
+
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -17,17 +22,24 @@ pub trait Start: Sync + Send {
     async fn start(&self, cid: &Principal) -> Result<(), StartError>;
 }
 
-pub struct Starter;
+pub struct Starter {
+    mgmt: Arc<dyn Management>,
+}
 
 impl Starter {
     pub fn arc(agent: &Agent) -> Arc<dyn Start> {
-        Arc::new(Starter)
+        Arc::new(Starter {
+            mgmt: management::Client::arc(agent),
+        })
     }
 }
 
 #[async_trait]
 impl Start for Starter {
     async fn start(&self, cid: &Principal) -> Result<(), StartError> {
-        Ok(())
+        self.mgmt
+            .start_canister(cid)
+            .await
+            .map_err(StartError::Agent)
     }
 }
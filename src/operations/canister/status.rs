@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use candid::Principal;
+use ic_agent::{Agent, AgentError};
+use mockall::automock;
+
+pub use ic_management_canister_types::CanisterStatusType;
+
+use crate::operations::management::{self, Management};
+
+#[derive(Debug, thiserror::Error)]
+pub enum StatusError {
+    #[error(transparent)]
+    Agent(AgentError),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[automock]
+#[async_trait]
+pub trait Status: Sync + Send {
+    /// Whether the canister is running, stopping or stopped. Only controllers may ask.
+    async fn status(&self, cid: &Principal) -> Result<CanisterStatusType, StatusError>;
+}
+
+pub struct StatusReader {
+    mgmt: Arc<dyn Management>,
+}
+
+impl StatusReader {
+    pub fn arc(agent: &Agent) -> Arc<dyn Status> {
+        Arc::new(StatusReader {
+            mgmt: management::Client::arc(agent),
+        })
+    }
+}
+
+#[async_trait]
+impl Status for StatusReader {
+    async fn status(&self, cid: &Principal) -> Result<CanisterStatusType, StatusError> {
+        let out = self
+            .mgmt
+            .canister_status(cid)
+            .await
+            .map_err(StatusError::Agent)?;
+
+        Ok(out.status)
+    }
+}
@@ -2,11 +2,16 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use candid::Principal;
-use ic_agent::Agent;
+use ic_agent::{Agent, AgentError};
 use mockall::automock;
 
+use crate::operations::management::{self, Management};
+
 #[derive(Debug, thiserror::Error)]
 pub enum StopError {
+    #[error(transparent)]
+    Agent(AgentError),
+
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -17,17 +22,21 @@ pub trait Stop: Sync + Send {
     async fn stop(&self, cid: &Principal) -> Result<(), StopError>;
 }
 
-pub struct Stopper;
+pub struct Stopper {
+    mgmt: Arc<dyn Management>,
+}
 
 impl Stopper {
     pub fn arc(agent: &Agent) -> Arc<dyn Stop> {
-        Arc::new(Stopper)
+        Arc::new(Stopper {
+            mgmt: management::Client::arc(agent),
+        })
     }
 }
 
 #[async_trait]
 impl Stop for Stopper {
     async fn stop(&self, cid: &Principal) -> Result<(), StopError> {
-        Ok(())
+        self.mgmt.stop_canister(cid).await.map_err(StopError::Agent)
     }
 }
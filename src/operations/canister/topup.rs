@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use candid::{CandidType, Decode, Encode, IDLValue, Nat, Principal};
+use ic_agent::{Agent, AgentError};
+use mockall::automock;
+
+use crate::operations::{
+    canister::CYCLES_LEDGER,
+    management::{self, Management},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TopUpError {
+    #[error(transparent)]
+    Agent(AgentError),
+
+    #[error("the cycles ledger refused the withdrawal: {0}")]
+    Ledger(String),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[automock]
+#[async_trait]
+pub trait TopUp: Sync + Send {
+    /// Cycles balance of the canister, only readable by its controllers.
+    async fn balance(&self, cid: &Principal) -> Result<u128, TopUpError>;
+
+    /// Moves cycles from the caller's cycles ledger account into the canister,
+    /// returning the ledger block index.
+    async fn top_up(&self, cid: &Principal, amount: u128) -> Result<Nat, TopUpError>;
+}
+
+#[derive(CandidType)]
+struct WithdrawArgs {
+    to: Principal,
+    from_subaccount: Option<Vec<u8>>,
+    created_at_time: Option<u64>,
+    amount: Nat,
+}
+
+pub struct TopUpper {
+    agent: Agent,
+    mgmt: Arc<dyn Management>,
+}
+
+impl TopUpper {
+    pub fn arc(agent: &Agent) -> Arc<dyn TopUp> {
+        Arc::new(TopUpper {
+            agent: agent.to_owned(),
+            mgmt: management::Client::arc(agent),
+        })
+    }
+}
+
+#[async_trait]
+impl TopUp for TopUpper {
+    async fn balance(&self, cid: &Principal) -> Result<u128, TopUpError> {
+        let status = self
+            .mgmt
+            .canister_status(cid)
+            .await
+            .map_err(TopUpError::Agent)?;
+
+        Ok(status
+            .cycles
+            .0
+            .try_into()
+            .map_err(|_| anyhow::format_err!("cycles balance out of range"))?)
+    }
+
+    async fn top_up(&self, cid: &Principal, amount: u128) -> Result<Nat, TopUpError> {
+        let ledger = Principal::from_text(CYCLES_LEDGER).map_err(anyhow::Error::from)?;
+
+        let arg = Encode!(&WithdrawArgs {
+            to: *cid,
+            from_subaccount: None,
+            created_at_time: None,
+            amount: Nat::from(amount),
+        })
+        .map_err(anyhow::Error::from)?;
+
+        let bs = self
+            .agent
+            .update(&ledger, "withdraw")
+            .with_arg(arg)
+            .call_and_wait()
+            .await
+            .map_err(TopUpError::Agent)?;
+
+        // Errors are only shown, so they are decoded without their type
+        match Decode!(&bs, Result<Nat, IDLValue>).map_err(anyhow::Error::from)? {
+            Ok(block) => Ok(block),
+            Err(err) => Err(TopUpError::Ledger(err.to_string())),
+        }
+    }
+}
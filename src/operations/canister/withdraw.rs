@@ -0,0 +1,278 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use candid::{CandidType, Encode, Nat, Principal};
+use ic_agent::{Agent, AgentError};
+use ic_management_canister_types::{CanisterIdRecord, CanisterSettings};
+use mockall::automock;
+
+use crate::operations::{
+    canister::CanisterInstallMode,
+    management::{self, Management},
+};
+
+/// Module installed on a canister to forward its remaining cycles, see `withdraw.wat`.
+const FORWARDER: &str = include_str!("withdraw.wat");
+
+/// Cycles left behind to pay for installing the forwarder and executing the withdrawal.
+const WITHDRAW_MARGIN: u128 = 50_000_000_000;
+
+/// Cycles ledger canister on mainnet.
+pub const CYCLES_LEDGER: &str = "um5iw-rqaaa-aaaaq-qaaba-cai";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WithdrawTarget {
+    /// Deposit the cycles into another canister.
+    Canister(Principal),
+
+    /// Deposit the cycles into a cycles ledger account.
+    Account {
+        owner: Principal,
+        subaccount: Option<[u8; 32]>,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WithdrawError {
+    #[error("failed to prepare canister for withdrawal")]
+    Prepare(#[source] AgentError),
+
+    #[error("failed to forward cycles")]
+    Forward(#[source] AgentError),
+
+    #[error("the canister's code and state were already replaced, re-run `delete` to finish")]
+    Replaced(#[source] Box<WithdrawError>),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[automock]
+#[async_trait]
+pub trait Withdraw: Sync + Send {
+    /// Moves all but a small margin of a stopped canister's cycles to `to`,
+    /// returning the amount withdrawn. This replaces the canister's code.
+    async fn withdraw(&self, cid: &Principal, to: &WithdrawTarget) -> Result<u128, WithdrawError>;
+}
+
+pub struct Withdrawer {
+    agent: Agent,
+    mgmt: Arc<dyn Management>,
+}
+
+impl Withdrawer {
+    pub fn arc(agent: &Agent) -> Arc<dyn Withdraw> {
+        Arc::new(Withdrawer {
+            agent: agent.clone(),
+            mgmt: management::Client::arc(agent),
+        })
+    }
+
+    /// Runs the installed forwarder, returning the amount withdrawn.
+    async fn forward(&self, cid: &Principal, to: &WithdrawTarget) -> Result<u128, WithdrawError> {
+        self.mgmt
+            .start_canister(cid)
+            .await
+            .map_err(WithdrawError::Prepare)?;
+
+        let status = self
+            .mgmt
+            .canister_status(cid)
+            .await
+            .map_err(WithdrawError::Prepare)?;
+
+        let balance: u128 = status
+            .cycles
+            .0
+            .try_into()
+            .map_err(|_| anyhow::format_err!("cycles balance out of range"))?;
+
+        let amount = balance.saturating_sub(WITHDRAW_MARGIN);
+
+        if amount > 0 {
+            self.agent
+                .update(cid, "withdraw")
+                .with_arg(forward_arg(amount, to)?)
+                .call_and_wait()
+                .await
+                .map_err(WithdrawError::Forward)?;
+        }
+
+        self.mgmt
+            .stop_canister(cid)
+            .await
+            .map_err(WithdrawError::Prepare)?;
+
+        Ok(amount)
+    }
+}
+
+#[derive(CandidType)]
+struct Account {
+    owner: Principal,
+    subaccount: Option<Vec<u8>>,
+}
+
+#[derive(CandidType)]
+struct DepositArgs {
+    to: Account,
+    memo: Option<Vec<u8>>,
+}
+
+/// Encodes the forwarded call in the layout expected by the forwarder module.
+fn forward_arg(amount: u128, to: &WithdrawTarget) -> Result<Vec<u8>, anyhow::Error> {
+    let (callee, method, payload) = match to {
+        WithdrawTarget::Canister(cid) => (
+            Principal::management_canister(),
+            "deposit_cycles",
+            Encode!(&CanisterIdRecord { canister_id: *cid })?,
+        ),
+
+        WithdrawTarget::Account { owner, subaccount } => (
+            Principal::from_text(CYCLES_LEDGER)?,
+            "deposit",
+            Encode!(&DepositArgs {
+                to: Account {
+                    owner: *owner,
+                    subaccount: subaccount.map(|v| v.to_vec()),
+                },
+                memo: None,
+            })?,
+        ),
+    };
+
+    let mut out = amount.to_le_bytes().to_vec();
+
+    out.push(callee.as_slice().len() as u8);
+    out.extend_from_slice(callee.as_slice());
+
+    out.push(method.len() as u8);
+    out.extend_from_slice(method.as_bytes());
+
+    out.extend(payload);
+
+    Ok(out)
+}
+
+#[async_trait]
+impl Withdraw for Withdrawer {
+    async fn withdraw(&self, cid: &Principal, to: &WithdrawTarget) -> Result<u128, WithdrawError> {
+        let forwarder = wat::parse_str(FORWARDER).map_err(anyhow::Error::from)?;
+
+        // Let the canister spend down to zero without freezing
+        self.mgmt
+            .update_settings(
+                cid,
+                CanisterSettings {
+                    freezing_threshold: Some(Nat::from(0u8)),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(WithdrawError::Prepare)?;
+
+        self.mgmt
+            .install_code(cid, CanisterInstallMode::Reinstall, forwarder, vec![])
+            .await
+            .map_err(WithdrawError::Prepare)?;
+
+        // The canister's state is gone from here on, but the withdrawal can be run again
+        self.forward(cid, to)
+            .await
+            .map_err(|err| WithdrawError::Replaced(Box::new(err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use candid::{Decode, Principal};
+    use ic_agent::{Agent, AgentError};
+    use ic_management_canister_types::CanisterIdRecord;
+    use mockall::predicate::eq;
+
+    use crate::operations::{
+        canister::{CanisterInstallMode, Withdraw, WithdrawError, Withdrawer},
+        management::MockManagement,
+    };
+
+    use super::{FORWARDER, WithdrawTarget, forward_arg};
+
+    #[test]
+    fn forwarder_compiles() {
+        wat::parse_str(FORWARDER).expect("failed to compile forwarder");
+    }
+
+    #[test]
+    fn forward_arg_layout() {
+        let out = forward_arg(
+            u128::MAX - 1,
+            &WithdrawTarget::Canister(Principal::anonymous()),
+        )
+        .expect("failed to encode argument");
+
+        let callee = Principal::management_canister();
+        let callee = callee.as_slice();
+        let method = b"deposit_cycles";
+
+        // Amount
+        assert_eq!(out[..16], (u128::MAX - 1).to_le_bytes());
+
+        // Callee
+        assert_eq!(out[16] as usize, callee.len());
+        assert_eq!(&out[17..17 + callee.len()], callee);
+
+        // Method
+        let offset = 17 + callee.len();
+        assert_eq!(out[offset] as usize, method.len());
+        assert_eq!(&out[offset + 1..offset + 1 + method.len()], method);
+
+        // Payload
+        let payload = &out[offset + 1 + method.len()..];
+        let out = Decode!(payload, CanisterIdRecord).expect("failed to decode payload");
+        assert_eq!(out.canister_id, Principal::anonymous());
+    }
+
+    #[tokio::test]
+    async fn failure_after_reinstall_says_so() {
+        let mut mgmt = MockManagement::new();
+        mgmt.expect_update_settings()
+            .once()
+            .returning(|_, _| Ok(()));
+        mgmt.expect_install_code()
+            .with(
+                eq(Principal::anonymous()),
+                eq(CanisterInstallMode::Reinstall),
+                eq(wat::parse_str(FORWARDER).unwrap()),
+                eq(vec![]),
+            )
+            .once()
+            .returning(|_, _, _, _| Ok(()));
+        mgmt.expect_start_canister()
+            .once()
+            .returning(|_| Err(AgentError::MessageError("rejected".to_string())));
+
+        let withdrawer = Withdrawer {
+            agent: Agent::builder()
+                .with_url("http://127.0.0.1:4943")
+                .build()
+                .unwrap(),
+            mgmt: Arc::new(mgmt),
+        };
+
+        let out = withdrawer
+            .withdraw(
+                &Principal::anonymous(),
+                &WithdrawTarget::Canister(Principal::management_canister()),
+            )
+            .await;
+
+        match out {
+            Err(err @ WithdrawError::Replaced(_)) => {
+                assert!(err.to_string().contains("re-run `delete`"))
+            }
+            out => panic!("unexpected result {out:?}"),
+        }
+    }
+}
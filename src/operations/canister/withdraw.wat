@@ -0,0 +1,89 @@
+;; Forwards cycles out of the canister it is installed on.
+;;
+;; `withdraw` expects a raw (non-Candid) argument laid out as:
+;;
+;;   [0..16)   amount of cycles to attach, u128 little-endian
+;;   [16]      callee principal length
+;;   [..]      callee principal bytes
+;;   [..]      method name length
+;;   [..]      method name bytes
+;;   [..]      Candid payload of the forwarded call
+;;
+;; Only controllers may call it. The reply or reject of the forwarded call is
+;; relayed back to the caller.
+(module
+  (import "ic0" "msg_arg_data_size" (func $msg_arg_data_size (result i32)))
+  (import "ic0" "msg_arg_data_copy" (func $msg_arg_data_copy (param i32 i32 i32)))
+  (import "ic0" "msg_caller_size" (func $msg_caller_size (result i32)))
+  (import "ic0" "msg_caller_copy" (func $msg_caller_copy (param i32 i32 i32)))
+  (import "ic0" "is_controller" (func $is_controller (param i32 i32) (result i32)))
+  (import "ic0" "call_new" (func $call_new (param i32 i32 i32 i32 i32 i32 i32 i32)))
+  (import "ic0" "call_data_append" (func $call_data_append (param i32 i32)))
+  (import "ic0" "call_cycles_add128" (func $call_cycles_add128 (param i64 i64)))
+  (import "ic0" "call_perform" (func $call_perform (result i32)))
+  (import "ic0" "msg_reply_data_append" (func $msg_reply_data_append (param i32 i32)))
+  (import "ic0" "msg_reply" (func $msg_reply))
+  (import "ic0" "msg_reject_msg_size" (func $msg_reject_msg_size (result i32)))
+  (import "ic0" "msg_reject_msg_copy" (func $msg_reject_msg_copy (param i32 i32 i32)))
+  (import "ic0" "msg_reject" (func $msg_reject (param i32 i32)))
+  (import "ic0" "trap" (func $trap (param i32 i32)))
+
+  (memory 2)
+
+  ;; Empty Candid reply
+  (data (i32.const 0) "DIDL\00\00")
+  (data (i32.const 16) "caller is not a controller")
+  (data (i32.const 64) "failed to perform call")
+
+  ;; Scratch space for arguments and reject messages starts at 1024
+  (table 2 funcref)
+  (elem (i32.const 0) $on_reply $on_reject)
+
+  (func $on_reply (param $env i32)
+    (call $msg_reply_data_append (i32.const 0) (i32.const 6))
+    (call $msg_reply))
+
+  (func $on_reject (param $env i32)
+    (local $size i32)
+    (local.set $size (call $msg_reject_msg_size))
+    (call $msg_reject_msg_copy (i32.const 1024) (i32.const 0) (local.get $size))
+    (call $msg_reject (i32.const 1024) (local.get $size)))
+
+  (func $withdraw
+    (local $size i32)
+    (local $callee i32)
+    (local $callee_size i32)
+    (local $method i32)
+    (local $method_size i32)
+    (local $payload i32)
+
+    ;; Only controllers may withdraw
+    (local.set $size (call $msg_caller_size))
+    (call $msg_caller_copy (i32.const 1024) (i32.const 0) (local.get $size))
+    (if (i32.eqz (call $is_controller (i32.const 1024) (local.get $size)))
+      (then (call $trap (i32.const 16) (i32.const 26))))
+
+    (local.set $size (call $msg_arg_data_size))
+    (call $msg_arg_data_copy (i32.const 1024) (i32.const 0) (local.get $size))
+
+    (local.set $callee_size (i32.load8_u (i32.const 1040)))
+    (local.set $callee (i32.const 1041))
+    (local.set $method_size
+      (i32.load8_u (i32.add (local.get $callee) (local.get $callee_size))))
+    (local.set $method
+      (i32.add (i32.add (local.get $callee) (local.get $callee_size)) (i32.const 1)))
+    (local.set $payload (i32.add (local.get $method) (local.get $method_size)))
+
+    (call $call_new
+      (local.get $callee) (local.get $callee_size)
+      (local.get $method) (local.get $method_size)
+      (i32.const 0) (i32.const 0)
+      (i32.const 1) (i32.const 0))
+    (call $call_data_append
+      (local.get $payload)
+      (i32.sub (i32.add (i32.const 1024) (local.get $size)) (local.get $payload)))
+    (call $call_cycles_add128 (i64.load (i32.const 1032)) (i64.load (i32.const 1024)))
+    (if (call $call_perform)
+      (then (call $trap (i32.const 64) (i32.const 22)))))
+
+  (export "canister_update withdraw" (func $withdraw)))
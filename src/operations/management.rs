@@ -0,0 +1,385 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use candid::Principal;
+use ic_agent::{Agent, AgentError};
+use ic_management_canister_types::{
+    CanisterLogRecord, CanisterSettings, CanisterStatusResult, DeleteCanisterSnapshotArgs,
+    LoadCanisterSnapshotArgs, ReadCanisterSnapshotDataArgs, ReadCanisterSnapshotMetadataArgs,
+    ReadCanisterSnapshotMetadataResult, Snapshot, SnapshotDataKind, SnapshotDataOffset,
+    TakeCanisterSnapshotArgs, UpdateSettingsArgs, UploadCanisterSnapshotDataArgs,
+    UploadCanisterSnapshotMetadataArgs, UploadChunkArgs,
+};
+use ic_utils::{
+    call::{AsyncCall, SyncCall},
+    interfaces::{
+        ManagementCanister,
+        management_canister::{ChunkHash, builders::CanisterInstallMode},
+    },
+};
+use mockall::automock;
+
+/// Thin wrapper around the calls operations make to the management canister,
+/// so that multi-step operations can be tested against a mock.
+#[automock]
+#[async_trait]
+pub trait Management: Sync + Send {
+    async fn start_canister(&self, cid: &Principal) -> Result<(), AgentError>;
+
+    async fn stop_canister(&self, cid: &Principal) -> Result<(), AgentError>;
+
+    async fn delete_canister(&self, cid: &Principal) -> Result<(), AgentError>;
+
+    async fn canister_status(&self, cid: &Principal) -> Result<CanisterStatusResult, AgentError>;
+
+    async fn update_settings(
+        &self,
+        cid: &Principal,
+        settings: CanisterSettings,
+    ) -> Result<(), AgentError>;
+
+    async fn install_code(
+        &self,
+        cid: &Principal,
+        mode: CanisterInstallMode,
+        wasm: Vec<u8>,
+        arg: Vec<u8>,
+    ) -> Result<(), AgentError>;
+
+    async fn stored_chunks(&self, cid: &Principal) -> Result<Vec<Vec<u8>>, AgentError>;
+
+    async fn upload_chunk(&self, cid: &Principal, chunk: Vec<u8>) -> Result<Vec<u8>, AgentError>;
+
+    async fn install_chunked_code(
+        &self,
+        cid: &Principal,
+        mode: CanisterInstallMode,
+        chunk_hashes: Vec<Vec<u8>>,
+        module_hash: Vec<u8>,
+        arg: Vec<u8>,
+    ) -> Result<(), AgentError>;
+
+    async fn clear_chunk_store(&self, cid: &Principal) -> Result<(), AgentError>;
+
+    async fn fetch_canister_logs(
+        &self,
+        cid: &Principal,
+    ) -> Result<Vec<CanisterLogRecord>, AgentError>;
+
+    async fn take_canister_snapshot(
+        &self,
+        cid: &Principal,
+        replace: Option<Vec<u8>>,
+    ) -> Result<Snapshot, AgentError>;
+
+    async fn load_canister_snapshot(&self, cid: &Principal, id: Vec<u8>) -> Result<(), AgentError>;
+
+    async fn list_canister_snapshots(&self, cid: &Principal) -> Result<Vec<Snapshot>, AgentError>;
+
+    async fn delete_canister_snapshot(
+        &self,
+        cid: &Principal,
+        id: Vec<u8>,
+    ) -> Result<(), AgentError>;
+
+    async fn read_canister_snapshot_metadata(
+        &self,
+        cid: &Principal,
+        id: Vec<u8>,
+    ) -> Result<ReadCanisterSnapshotMetadataResult, AgentError>;
+
+    async fn read_canister_snapshot_data(
+        &self,
+        cid: &Principal,
+        id: Vec<u8>,
+        kind: SnapshotDataKind,
+    ) -> Result<Vec<u8>, AgentError>;
+
+    /// Creates an empty snapshot from metadata, returning its ID.
+    async fn upload_canister_snapshot_metadata(
+        &self,
+        args: UploadCanisterSnapshotMetadataArgs,
+    ) -> Result<Vec<u8>, AgentError>;
+
+    async fn upload_canister_snapshot_data(
+        &self,
+        cid: &Principal,
+        id: Vec<u8>,
+        kind: SnapshotDataOffset,
+        chunk: Vec<u8>,
+    ) -> Result<(), AgentError>;
+}
+
+pub struct Client {
+    agent: Agent,
+}
+
+impl Client {
+    pub fn arc(agent: &Agent) -> Arc<dyn Management> {
+        Arc::new(Client {
+            agent: agent.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl Management for Client {
+    async fn start_canister(&self, cid: &Principal) -> Result<(), AgentError> {
+        ManagementCanister::create(&self.agent)
+            .start_canister(cid)
+            .call_and_wait()
+            .await
+    }
+
+    async fn stop_canister(&self, cid: &Principal) -> Result<(), AgentError> {
+        ManagementCanister::create(&self.agent)
+            .stop_canister(cid)
+            .call_and_wait()
+            .await
+    }
+
+    async fn delete_canister(&self, cid: &Principal) -> Result<(), AgentError> {
+        ManagementCanister::create(&self.agent)
+            .delete_canister(cid)
+            .call_and_wait()
+            .await
+    }
+
+    async fn canister_status(&self, cid: &Principal) -> Result<CanisterStatusResult, AgentError> {
+        let (out,) = ManagementCanister::create(&self.agent)
+            .canister_status(cid)
+            .call_and_wait()
+            .await?;
+
+        Ok(out)
+    }
+
+    async fn update_settings(
+        &self,
+        cid: &Principal,
+        settings: CanisterSettings,
+    ) -> Result<(), AgentError> {
+        // Settings are passed through as-is rather than re-assembled through the builder
+        ManagementCanister::create(&self.agent)
+            .update("update_settings")
+            .with_arg(UpdateSettingsArgs {
+                canister_id: *cid,
+                settings,
+                sender_canister_version: None,
+            })
+            .with_effective_canister_id(*cid)
+            .build::<()>()
+            .call_and_wait()
+            .await
+    }
+
+    async fn install_code(
+        &self,
+        cid: &Principal,
+        mode: CanisterInstallMode,
+        wasm: Vec<u8>,
+        arg: Vec<u8>,
+    ) -> Result<(), AgentError> {
+        ManagementCanister::create(&self.agent)
+            .install_code(cid, &wasm)
+            .with_mode(mode)
+            .with_raw_arg(arg)
+            .await
+    }
+
+    async fn stored_chunks(&self, cid: &Principal) -> Result<Vec<Vec<u8>>, AgentError> {
+        let (chunks,) = ManagementCanister::create(&self.agent)
+            .stored_chunks(cid)
+            .call_and_wait()
+            .await?;
+
+        Ok(chunks.into_iter().map(|c| c.hash).collect())
+    }
+
+    async fn upload_chunk(&self, cid: &Principal, chunk: Vec<u8>) -> Result<Vec<u8>, AgentError> {
+        let (out,) = ManagementCanister::create(&self.agent)
+            .upload_chunk(
+                cid,
+                &UploadChunkArgs {
+                    canister_id: *cid,
+                    chunk,
+                },
+            )
+            .call_and_wait()
+            .await?;
+
+        Ok(out.hash)
+    }
+
+    async fn install_chunked_code(
+        &self,
+        cid: &Principal,
+        mode: CanisterInstallMode,
+        chunk_hashes: Vec<Vec<u8>>,
+        module_hash: Vec<u8>,
+        arg: Vec<u8>,
+    ) -> Result<(), AgentError> {
+        ManagementCanister::create(&self.agent)
+            .install_chunked_code(cid, &module_hash)
+            .with_chunk_hashes(
+                chunk_hashes
+                    .into_iter()
+                    .map(|hash| ChunkHash { hash })
+                    .collect(),
+            )
+            .with_install_mode(mode)
+            .with_raw_arg(arg)
+            .await
+    }
+
+    async fn clear_chunk_store(&self, cid: &Principal) -> Result<(), AgentError> {
+        ManagementCanister::create(&self.agent)
+            .clear_chunk_store(cid)
+            .call_and_wait()
+            .await
+    }
+
+    async fn fetch_canister_logs(
+        &self,
+        cid: &Principal,
+    ) -> Result<Vec<CanisterLogRecord>, AgentError> {
+        let (out,) = ManagementCanister::create(&self.agent)
+            .fetch_canister_logs(cid)
+            .call()
+            .await?;
+
+        Ok(out.canister_log_records)
+    }
+
+    async fn take_canister_snapshot(
+        &self,
+        cid: &Principal,
+        replace: Option<Vec<u8>>,
+    ) -> Result<Snapshot, AgentError> {
+        let (out,) = ManagementCanister::create(&self.agent)
+            .take_canister_snapshot(
+                cid,
+                &TakeCanisterSnapshotArgs {
+                    canister_id: *cid,
+                    replace_snapshot: replace,
+                },
+            )
+            .call_and_wait()
+            .await?;
+
+        Ok(out)
+    }
+
+    async fn load_canister_snapshot(&self, cid: &Principal, id: Vec<u8>) -> Result<(), AgentError> {
+        ManagementCanister::create(&self.agent)
+            .load_canister_snapshot(
+                cid,
+                &LoadCanisterSnapshotArgs {
+                    canister_id: *cid,
+                    snapshot_id: id,
+                    sender_canister_version: None,
+                },
+            )
+            .call_and_wait()
+            .await
+    }
+
+    async fn list_canister_snapshots(&self, cid: &Principal) -> Result<Vec<Snapshot>, AgentError> {
+        let (out,) = ManagementCanister::create(&self.agent)
+            .list_canister_snapshots(cid)
+            .call_and_wait()
+            .await?;
+
+        Ok(out)
+    }
+
+    async fn delete_canister_snapshot(
+        &self,
+        cid: &Principal,
+        id: Vec<u8>,
+    ) -> Result<(), AgentError> {
+        ManagementCanister::create(&self.agent)
+            .delete_canister_snapshot(
+                cid,
+                &DeleteCanisterSnapshotArgs {
+                    canister_id: *cid,
+                    snapshot_id: id,
+                },
+            )
+            .call_and_wait()
+            .await
+    }
+
+    async fn read_canister_snapshot_metadata(
+        &self,
+        cid: &Principal,
+        id: Vec<u8>,
+    ) -> Result<ReadCanisterSnapshotMetadataResult, AgentError> {
+        let (out,) = ManagementCanister::create(&self.agent)
+            .read_canister_snapshot_metadata(
+                cid,
+                &ReadCanisterSnapshotMetadataArgs {
+                    canister_id: *cid,
+                    snapshot_id: id,
+                },
+            )
+            .call_and_wait()
+            .await?;
+
+        Ok(out)
+    }
+
+    async fn read_canister_snapshot_data(
+        &self,
+        cid: &Principal,
+        id: Vec<u8>,
+        kind: SnapshotDataKind,
+    ) -> Result<Vec<u8>, AgentError> {
+        let (out,) = ManagementCanister::create(&self.agent)
+            .read_canister_snapshot_data(
+                cid,
+                &ReadCanisterSnapshotDataArgs {
+                    canister_id: *cid,
+                    snapshot_id: id,
+                    kind,
+                },
+            )
+            .call_and_wait()
+            .await?;
+
+        Ok(out.chunk)
+    }
+
+    async fn upload_canister_snapshot_metadata(
+        &self,
+        args: UploadCanisterSnapshotMetadataArgs,
+    ) -> Result<Vec<u8>, AgentError> {
+        let (out,) = ManagementCanister::create(&self.agent)
+            .upload_canister_snapshot_metadata(&args.canister_id.to_owned(), &args)
+            .call_and_wait()
+            .await?;
+
+        Ok(out.snapshot_id)
+    }
+
+    async fn upload_canister_snapshot_data(
+        &self,
+        cid: &Principal,
+        id: Vec<u8>,
+        kind: SnapshotDataOffset,
+        chunk: Vec<u8>,
+    ) -> Result<(), AgentError> {
+        ManagementCanister::create(&self.agent)
+            .upload_canister_snapshot_data(
+                cid,
+                &UploadCanisterSnapshotDataArgs {
+                    canister_id: *cid,
+                    snapshot_id: id,
+                    kind,
+                    chunk,
+                },
+            )
+            .call_and_wait()
+            .await
+    }
+}
@@ -1,8 +1,13 @@
+pub mod build;
 pub mod canister;
+pub mod management;
+pub mod store;
 pub mod token;
 
 #[derive(Default)]
 pub struct Initializers {
+    pub build: build::Initializers,
     pub canister: canister::Initializers,
+    pub store: store::Initializers,
     pub token: token::Initializers,
 }
@@ -0,0 +1,108 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use mockall::automock;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::operations::store::Recipe;
+
+/// Artifacts file, relative to the project root.
+pub const ARTIFACTS: &str = ".icp/build/artifacts.json";
+
+/// A built canister module.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Artifact {
+    /// Module to install, relative to the project root.
+    pub wasm: PathBuf,
+
+    /// Hex SHA-256 of the module, what the IC reports as its module hash once installed.
+    pub sha256: String,
+
+    /// Candid interface of the module, relative to the project root.
+    pub candid: Option<PathBuf>,
+
+    pub recipe: Recipe,
+
+    /// Environment the module was built for, the IDs of its dependencies are linked into it.
+    #[serde(default)]
+    pub environment: String,
+
+    /// When the module was built, RFC 3339.
+    pub built_at: String,
+}
+
+/// Artifacts of the built canisters, keyed by canister name.
+pub type Artifacts = BTreeMap<String, Artifact>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArtifactsError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// What the last build produced for each canister.
+#[automock]
+#[async_trait]
+pub trait Record: Sync + Send {
+    /// Every canister built so far.
+    async fn load(&self) -> Result<Artifacts, ArtifactsError>;
+
+    /// Records the artifact of a canister, replacing any previous one.
+    async fn record(&self, name: &str, artifact: &Artifact) -> Result<(), ArtifactsError>;
+}
+
+pub struct ArtifactStore {
+    path: PathBuf,
+
+    // Canisters built concurrently record one at a time
+    lock: Mutex<()>,
+}
+
+impl ArtifactStore {
+    pub fn arc(dir: &Path) -> Arc<dyn Record> {
+        Arc::new(ArtifactStore {
+            path: dir.join(ARTIFACTS),
+            lock: Mutex::new(()),
+        })
+    }
+}
+
+#[async_trait]
+impl Record for ArtifactStore {
+    async fn load(&self) -> Result<Artifacts, ArtifactsError> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bs) => Ok(serde_json::from_slice(&bs).map_err(anyhow::Error::from)?),
+
+            // Nothing was built yet
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Artifacts::default()),
+
+            Err(err) => Err(anyhow::Error::from(err).into()),
+        }
+    }
+
+    async fn record(&self, name: &str, artifact: &Artifact) -> Result<(), ArtifactsError> {
+        let _guard = self.lock.lock().await;
+
+        let mut artifacts = self.load().await?;
+        artifacts.insert(name.to_owned(), artifact.to_owned());
+
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(anyhow::Error::from)?;
+        }
+
+        let bs = serde_json::to_vec_pretty(&artifacts).map_err(anyhow::Error::from)?;
+
+        tokio::fs::write(&self.path, bs)
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use candid::Principal;
+use mockall::automock;
+
+/// Canister IDs keyed by environment, then by canister name.
+pub type Ids = BTreeMap<String, BTreeMap<String, Principal>>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LookupError {
+    #[error("canister `{name}` has no ID in environment `{environment}`")]
+    NotFound { environment: String, name: String },
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ListError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegisterError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UnregisterError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Mapping of project canister names to their IDs in each environment.
+#[automock]
+#[async_trait]
+pub trait Store: Sync + Send {
+    async fn lookup(&self, environment: &str, name: &str) -> Result<Principal, LookupError>;
+
+    /// All canisters with an ID in the environment.
+    async fn list(&self, environment: &str) -> Result<BTreeMap<String, Principal>, ListError>;
+
+    /// Every canister with an ID, in every environment.
+    async fn all(&self) -> Result<Ids, ListError>;
+
+    /// Records the IDs of many canisters in a single write, replacing any previous ones.
+    async fn register_all(&self, ids: &Ids) -> Result<(), RegisterError>;
+
+    async fn unregister(&self, environment: &str, name: &str) -> Result<(), UnregisterError>;
+}
+
+pub struct IdStore {
+    path: PathBuf,
+}
+
+impl IdStore {
+    pub fn arc(dir: &Path) -> Arc<dyn Store> {
+        Arc::new(IdStore {
+            path: dir.join(".icp").join("ids.json"),
+        })
+    }
+
+    async fn load(&self) -> Result<Ids, anyhow::Error> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bs) => Ok(serde_json::from_slice(&bs)?),
+
+            // No canisters were created yet
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Ids::default()),
+
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn save(&self, ids: &Ids) -> Result<(), anyhow::Error> {
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        tokio::fs::write(&self.path, serde_json::to_vec_pretty(ids)?).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Store for IdStore {
+    async fn lookup(&self, environment: &str, name: &str) -> Result<Principal, LookupError> {
+        self.load()
+            .await?
+            .get(environment)
+            .and_then(|ids| ids.get(name))
+            .copied()
+            .ok_or_else(|| LookupError::NotFound {
+                environment: environment.to_string(),
+                name: name.to_string(),
+            })
+    }
+
+    async fn list(&self, environment: &str) -> Result<BTreeMap<String, Principal>, ListError> {
+        Ok(self.load().await?.remove(environment).unwrap_or_default())
+    }
+
+    async fn all(&self) -> Result<Ids, ListError> {
+        Ok(self.load().await?)
+    }
+
+    async fn register_all(&self, incoming: &Ids) -> Result<(), RegisterError> {
+        let mut ids = self.load().await?;
+
+        for (environment, incoming) in incoming {
+            ids.entry(environment.to_owned())
+                .or_default()
+                .extend(incoming.to_owned());
+        }
+
+        self.save(&ids).await?;
+
+        Ok(())
+    }
+
+    async fn unregister(&self, environment: &str, name: &str) -> Result<(), UnregisterError> {
+        let mut ids = self.load().await?;
+
+        if let Some(env) = ids.get_mut(environment) {
+            env.remove(name);
+
+            if env.is_empty() {
+                ids.remove(environment);
+            }
+        }
+
+        self.save(&ids).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use candid::Principal;
+use mockall::automock;
+use serde::{Deserialize, Serialize};
+
+/// A snapshot taken through the CLI, as recorded locally.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Labeled {
+    /// Snapshot ID, hex encoded.
+    pub id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// Time the snapshot was taken, in nanoseconds since the epoch.
+    pub taken_at: u64,
+}
+
+/// Recorded snapshots per canister ID.
+pub type Snapshots = BTreeMap<Principal, Vec<Labeled>>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LabelError {
+    #[error("snapshot label `{label}` is already used for canister {cid}")]
+    Taken { cid: Principal, label: String },
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Local record of snapshot IDs, so snapshots can be referred to by label.
+#[automock]
+#[async_trait]
+pub trait Labels: Sync + Send {
+    async fn list(&self, cid: &Principal) -> Result<Vec<Labeled>, LabelError>;
+
+    async fn record(&self, cid: &Principal, snapshot: Labeled) -> Result<(), LabelError>;
+
+    async fn remove(&self, cid: &Principal, id: &str) -> Result<(), LabelError>;
+}
+
+pub struct LabelStore {
+    path: PathBuf,
+}
+
+impl LabelStore {
+    pub fn arc(dir: &Path) -> Arc<dyn Labels> {
+        Arc::new(LabelStore {
+            path: dir.join(".icp").join("snapshots.json"),
+        })
+    }
+
+    async fn load(&self) -> Result<Snapshots, anyhow::Error> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bs) => Ok(serde_json::from_slice(&bs)?),
+
+            // No snapshots were taken yet
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Snapshots::default()),
+
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn save(&self, snapshots: &Snapshots) -> Result<(), anyhow::Error> {
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        tokio::fs::write(&self.path, serde_json::to_vec_pretty(snapshots)?).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Labels for LabelStore {
+    async fn list(&self, cid: &Principal) -> Result<Vec<Labeled>, LabelError> {
+        Ok(self.load().await?.remove(cid).unwrap_or_default())
+    }
+
+    async fn record(&self, cid: &Principal, snapshot: Labeled) -> Result<(), LabelError> {
+        let mut snapshots = self.load().await?;
+        let entries = snapshots.entry(*cid).or_default();
+
+        if let Some(label) = &snapshot.label
+            && entries.iter().any(|s| s.label.as_ref() == Some(label))
+        {
+            return Err(LabelError::Taken {
+                cid: *cid,
+                label: label.to_owned(),
+            });
+        }
+
+        // Recording an ID again moves its entry to the end, as the latest
+        entries.retain(|s| s.id != snapshot.id);
+        entries.push(snapshot);
+
+        self.save(&snapshots).await?;
+
+        Ok(())
+    }
+
+    async fn remove(&self, cid: &Principal, id: &str) -> Result<(), LabelError> {
+        let mut snapshots = self.load().await?;
+
+        if let Some(entries) = snapshots.get_mut(cid) {
+            entries.retain(|s| s.id != id);
+
+            if entries.is_empty() {
+                snapshots.remove(cid);
+            }
+        }
+
+        self.save(&snapshots).await?;
+
+        Ok(())
+    }
+}
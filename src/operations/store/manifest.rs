@@ -0,0 +1,428 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use candid::Principal;
+use mockall::automock;
+use serde::{Deserialize, Serialize};
+
+/// Name of the project manifest, its location marks the project root.
+pub const MANIFEST: &str = "icp.yaml";
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct Manifest {
+    #[serde(default)]
+    pub canisters: BTreeMap<String, Canister>,
+
+    #[serde(default)]
+    pub networks: BTreeMap<String, Network>,
+
+    #[serde(default)]
+    pub environments: BTreeMap<String, Environment>,
+
+    /// Other projects whose canisters are referenced as `<project>/<canister>`.
+    #[serde(default)]
+    pub projects: BTreeMap<String, Project>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct Canister {
+    /// Tags to select the canister by in batch commands.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Query run after the canister is started to confirm it is healthy.
+    pub health: Option<Health>,
+
+    /// Fixed IDs of a canister deployed by someone else, by environment or network name.
+    pub remote: Option<BTreeMap<String, Principal>>,
+
+    /// How the canister's wasm module is produced.
+    pub build: Option<Recipe>,
+
+    /// Files and directories the recipe reads, builds are reused from the cache while they
+    /// are unchanged. Canisters without inputs are always rebuilt.
+    #[serde(default)]
+    pub inputs: Vec<PathBuf>,
+
+    /// Environment variables the recipe reads.
+    #[serde(default)]
+    pub env: Vec<String>,
+
+    /// Canisters built before this one, their IDs are passed to its recipe.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+
+    /// Post-processing of the module produced by the recipe.
+    pub wasm: Option<Wasm>,
+}
+
+/// Build recipe as declared in the manifest, paths are relative to the project root.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Recipe {
+    /// `cargo build` to the `wasm32-unknown-unknown` target.
+    Rust {
+        package: String,
+
+        /// Cargo profile, `release` unless set.
+        #[serde(default = "Recipe::default_profile")]
+        profile: String,
+
+        /// Directory to run cargo in, the project root unless set.
+        path: Option<PathBuf>,
+    },
+
+    /// `moc` on the main actor file.
+    Motoko {
+        main: PathBuf,
+
+        /// Extra arguments passed to `moc`.
+        #[serde(default)]
+        args: Vec<String>,
+    },
+
+    /// A shell command that leaves a wasm module at `output`.
+    Script { command: String, output: PathBuf },
+
+    /// A wasm module built elsewhere.
+    Prebuilt { path: PathBuf },
+}
+
+impl Recipe {
+    fn default_profile() -> String {
+        "release".to_string()
+    }
+}
+
+/// Steps applied to a built module, always in the order of the fields.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct Wasm {
+    /// Remove unused functions and data with `ic-wasm shrink`.
+    #[serde(default)]
+    pub shrink: bool,
+
+    /// Remove debug and name sections.
+    #[serde(default)]
+    pub strip: bool,
+
+    /// Candid interface embedded as the public `candid:service` section.
+    pub candid: Option<PathBuf>,
+
+    /// Custom metadata sections.
+    #[serde(default)]
+    pub metadata: Vec<Metadata>,
+
+    /// Compress the final module, the IC accepts gzipped modules.
+    #[serde(default)]
+    pub gzip: bool,
+}
+
+/// A custom metadata section, with its content given inline or read from a file.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Metadata {
+    pub name: String,
+    pub value: Option<String>,
+    pub file: Option<PathBuf>,
+
+    #[serde(default)]
+    pub visibility: MetadataVisibility,
+}
+
+/// Public sections can be read by anyone, private ones only by controllers.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataVisibility {
+    #[default]
+    Public,
+    Private,
+}
+
+impl Manifest {
+    /// Network an environment targets: the one it declares, or the one it is named after.
+    pub fn network_of(&self, environment: &str) -> String {
+        self.environments
+            .get(environment)
+            .and_then(|env| env.network.to_owned())
+            .unwrap_or(environment.to_owned())
+    }
+
+    /// ID of a remote canister in an environment, declared for the environment or its network.
+    ///
+    /// None if the canister is not remote, Some(None) if it has no ID there.
+    pub fn remote(&self, name: &str, environment: &str) -> Option<Option<Principal>> {
+        let ids = self.canisters.get(name)?.remote.as_ref()?;
+
+        Some(
+            ids.get(environment)
+                .or_else(|| ids.get(&self.network_of(environment)))
+                .copied(),
+        )
+    }
+}
+
+/// Health check as declared in the manifest.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Health {
+    /// Query method to call.
+    pub method: String,
+
+    /// Candid text argument, `()` when unset.
+    pub arg: Option<String>,
+
+    /// Candid text reply the method must return.
+    pub reply: Option<String>,
+
+    /// Candid predicate over the reply, e.g. `status == variant { healthy }`.
+    pub predicate: Option<String>,
+
+    /// Number of calls before giving up.
+    #[serde(default = "Health::default_attempts")]
+    pub attempts: u32,
+
+    /// Seconds between calls.
+    #[serde(default = "Health::default_interval")]
+    pub interval: u64,
+
+    /// Seconds after which the check fails regardless of attempts left.
+    #[serde(default = "Health::default_deadline")]
+    pub deadline: u64,
+}
+
+impl Health {
+    fn default_attempts() -> u32 {
+        10
+    }
+
+    fn default_interval() -> u64 {
+        1
+    }
+
+    fn default_deadline() -> u64 {
+        30
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Project {
+    /// Project root, relative to this one.
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Network {
+    pub url: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct Environment {
+    /// Network the environment is deployed to, defaults to the environment name.
+    pub network: Option<String>,
+
+    /// Declared settings per canister.
+    #[serde(default)]
+    pub settings: BTreeMap<String, Settings>,
+}
+
+/// Canister settings as declared in the manifest, unset fields are left alone.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct Settings {
+    pub controllers: Option<Vec<Principal>>,
+    pub compute_allocation: Option<u8>,
+    pub memory_allocation: Option<u64>,
+    pub freezing_threshold: Option<u64>,
+    pub reserved_cycles_limit: Option<u128>,
+    pub wasm_memory_limit: Option<u64>,
+    pub log_visibility: Option<LogVisibility>,
+    pub environment_variables: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogVisibility {
+    Controllers,
+    Public,
+    AllowedViewers(Vec<Principal>),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+    #[error("failed to read `{0}`")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("failed to parse `{0}`")]
+    Parse(PathBuf, #[source] serde_yaml_ng::Error),
+
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[automock]
+#[async_trait]
+pub trait Load: Sync + Send {
+    async fn load(&self) -> Result<Manifest, LoadError>;
+}
+
+pub struct ManifestLoader {
+    path: PathBuf,
+}
+
+impl ManifestLoader {
+    pub fn arc(dir: &Path) -> Arc<dyn Load> {
+        Arc::new(ManifestLoader {
+            path: dir.join(MANIFEST),
+        })
+    }
+}
+
+#[async_trait]
+impl Load for ManifestLoader {
+    async fn load(&self) -> Result<Manifest, LoadError> {
+        let bs = tokio::fs::read(&self.path)
+            .await
+            .map_err(|err| LoadError::Read(self.path.to_owned(), err))?;
+
+        serde_yaml_ng::from_slice(&bs).map_err(|err| LoadError::Parse(self.path.to_owned(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::Principal;
+    use indoc::indoc;
+
+    use super::{LogVisibility, Manifest, Metadata, MetadataVisibility, Recipe};
+
+    #[test]
+    fn parse_environment_settings() {
+        let m: Manifest = serde_yaml_ng::from_str(indoc! {r#"
+            canisters:
+              backend: {}
+
+            environments:
+              prod:
+                network: ic
+                settings:
+                  backend:
+                    controllers:
+                      - aaaaa-aa
+                    freezing_threshold: 7776000
+                    log_visibility: !allowed_viewers
+                      - 2vxsx-fae
+                    environment_variables:
+                      MODE: production
+        "#})
+        .expect("failed to parse manifest");
+
+        let env = &m.environments["prod"];
+        assert_eq!(env.network.as_deref(), Some("ic"));
+
+        let settings = &env.settings["backend"];
+        assert_eq!(
+            settings.controllers,
+            Some(vec![Principal::management_canister()])
+        );
+        assert_eq!(settings.freezing_threshold, Some(7_776_000));
+        assert_eq!(
+            settings.log_visibility,
+            Some(LogVisibility::AllowedViewers(vec![Principal::anonymous()]))
+        );
+        assert_eq!(settings.compute_allocation, None);
+    }
+
+    #[test]
+    fn remote_ids_by_environment_or_network() {
+        let m: Manifest = serde_yaml_ng::from_str(indoc! {r#"
+            canisters:
+              backend: {}
+              ledger:
+                remote:
+                  ic: ryjl3-tyaaa-aaaaa-aaaba-cai
+                  staging: aaaaa-aa
+
+            environments:
+              prod:
+                network: ic
+              staging:
+                network: ic
+        "#})
+        .expect("failed to parse manifest");
+
+        let ledger = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+
+        assert_eq!(m.remote("backend", "prod"), None);
+        assert_eq!(m.remote("ledger", "prod"), Some(Some(ledger)));
+        assert_eq!(
+            m.remote("ledger", "staging"),
+            Some(Some(Principal::management_canister()))
+        );
+        assert_eq!(m.remote("ledger", "local"), Some(None));
+    }
+
+    #[test]
+    fn parse_build_recipes() {
+        let m: Manifest = serde_yaml_ng::from_str(indoc! {r#"
+            canisters:
+              backend:
+                build:
+                  type: rust
+                  package: backend
+              frontend:
+                build:
+                  type: script
+                  command: npm run build
+                  output: dist/frontend.wasm
+        "#})
+        .expect("failed to parse manifest");
+
+        assert_eq!(
+            m.canisters["backend"].build,
+            Some(Recipe::Rust {
+                package: "backend".to_string(),
+                profile: "release".to_string(),
+                path: None,
+            })
+        );
+        assert_eq!(
+            m.canisters["frontend"].build,
+            Some(Recipe::Script {
+                command: "npm run build".to_string(),
+                output: "dist/frontend.wasm".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_wasm_steps() {
+        let m: Manifest = serde_yaml_ng::from_str(indoc! {r#"
+            canisters:
+              backend:
+                wasm:
+                  strip: true
+                  candid: backend.did
+                  metadata:
+                    - name: git:commit
+                      value: abc123
+                      visibility: private
+                  gzip: true
+        "#})
+        .expect("failed to parse manifest");
+
+        let wasm = m.canisters["backend"].wasm.as_ref().unwrap();
+
+        assert!(wasm.strip && wasm.gzip && !wasm.shrink);
+        assert_eq!(wasm.candid, Some("backend.did".into()));
+        assert_eq!(
+            wasm.metadata,
+            [Metadata {
+                name: "git:commit".to_string(),
+                value: Some("abc123".to_string()),
+                file: None,
+                visibility: MetadataVisibility::Private,
+            }]
+        );
+    }
+}
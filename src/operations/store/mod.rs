@@ -0,0 +1,31 @@
+use std::{path::Path, sync::Arc};
+
+mod artifacts;
+pub use artifacts::*;
+
+mod ids;
+pub use ids::*;
+
+mod labels;
+pub use labels::*;
+
+mod manifest;
+pub use manifest::*;
+
+pub struct Initializers {
+    pub artifacts: Box<dyn Fn(&Path) -> Arc<dyn Record>>,
+    pub ids: Box<dyn Fn(&Path) -> Arc<dyn Store>>,
+    pub labels: Box<dyn Fn(&Path) -> Arc<dyn Labels>>,
+    pub manifest: Box<dyn Fn(&Path) -> Arc<dyn Load>>,
+}
+
+impl Default for Initializers {
+    fn default() -> Self {
+        Self {
+            artifacts: Box::new(|_| unimplemented!()),
+            ids: Box::new(|_| unimplemented!()),
+            labels: Box::new(|_| unimplemented!()),
+            manifest: Box::new(|_| unimplemented!()),
+        }
+    }
+}